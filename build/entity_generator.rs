@@ -30,6 +30,8 @@ use crate::{
     Handle,
     LwPolylineVertex,
     Point,
+    TableCell,
+    Transparency,
     Vector,
     XData,
 };
@@ -49,9 +51,11 @@ use crate::objects::*;
 
     fun.push_str("impl EntityType {\n");
     generate_is_supported_on_version(&mut fun, &element);
+    generate_min_version(&mut fun, &element);
     generate_type_string(&mut fun, &element);
     generate_try_apply_code_pair(&mut fun, &element);
     generate_get_code_pairs(&mut fun, &element);
+    generate_handle_references(&mut fun, &element);
     fun.push_str("}\n");
 
     let mut file = File::create(generated_dir.join("entities.rs"))
@@ -150,6 +154,7 @@ fn generate_base_entity(fun: &mut String, element: &Element) {
             fun.push_str(&methods_for_pointer_access(p));
         }
     }
+    fun.push_str(&method_for_handle_references(entity, &[]));
 
     ////////////////////////////////////////////////////// apply_individual_pair
     fun.push_str("    pub(crate) fn apply_individual_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<()> {\n");
@@ -223,6 +228,34 @@ fn generate_entity_types(fun: &mut String, element: &Element) {
     fun.push_str("}\n");
     fun.push('\n');
 
+    // conversions to `Entity`
+    for c in &element.children {
+        if name(c) != "Entity" && name(c) != "DimensionBase" {
+            fun.push_str(&format!("impl From<{typ}> for Entity {{\n", typ = name(c)));
+            fun.push_str(&format!(
+                "    fn from(specific: {typ}) -> Self {{\n",
+                typ = name(c)
+            ));
+            fun.push_str(&format!(
+                "        Entity::new(EntityType::{typ}(specific))\n",
+                typ = name(c)
+            ));
+            fun.push_str("    }\n");
+            fun.push_str("}\n");
+            fun.push('\n');
+        }
+    }
+
+    // typed accessors on `Entity`
+    fun.push_str("impl Entity {\n");
+    for c in &element.children {
+        if name(c) != "Entity" && name(c) != "DimensionBase" {
+            fun.push_str(&methods_for_typed_access("EntityType", "entity", &name(c)));
+        }
+    }
+    fun.push_str("}\n");
+    fun.push('\n');
+
     // individual structs
     for c in &element.children {
         if c.name != "Entity" {
@@ -384,6 +417,7 @@ fn generate_implementation(fun: &mut String, element: &Element) {
             implementation.push_str(&methods_for_pointer_access(field));
         }
     }
+    implementation.push_str(&method_for_handle_references(element, &[]));
 
     if !implementation.is_empty() {
         fun.push_str(&format!("impl {typ} {{\n", typ = name(element)));
@@ -393,6 +427,38 @@ fn generate_implementation(fun: &mut String, element: &Element) {
     }
 }
 
+fn generate_handle_references(fun: &mut String, element: &Element) {
+    let dimension_base = element
+        .children
+        .iter()
+        .find(|c| name(c) == "DimensionBase")
+        .unwrap();
+    let base_has_references = !method_for_handle_references(dimension_base, &[]).is_empty();
+    fun.push_str("    pub(crate) fn handle_references_mut(&mut self) -> Vec<&mut Handle> {\n");
+    fun.push_str("        match self {\n");
+    for c in &element.children {
+        if name(c) == "Entity" || name(c) == "DimensionBase" {
+            continue;
+        }
+        let has_references = !method_for_handle_references(c, &[]).is_empty();
+        let is_dimension = base_class(c) == "DimensionBase" && base_has_references;
+        let value = match (is_dimension, has_references) {
+            (false, false) => continue,
+            (false, true) => String::from("e.handle_references_mut()"),
+            (true, false) => String::from("e.dimension_base.handle_references_mut()"),
+            (true, true) => String::from("{ let mut handles = e.dimension_base.handle_references_mut(); handles.extend(e.handle_references_mut()); handles }"),
+        };
+        fun.push_str(&format!(
+            "            EntityType::{typ}(ref mut e) => {value},\n",
+            typ = name(c),
+            value = value
+        ));
+    }
+    fun.push_str("            _ => vec![],\n");
+    fun.push_str("        }\n");
+    fun.push_str("    }\n");
+}
+
 fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
     fun.push_str(
         "    pub(crate) fn is_supported_on_version(&self, version: AcadVersion) -> bool {\n",
@@ -420,6 +486,26 @@ fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
     fun.push_str("    }\n");
 }
 
+fn generate_min_version(fun: &mut String, element: &Element) {
+    fun.push_str("    pub(crate) fn min_version(&self) -> Option<AcadVersion> {\n");
+    fun.push_str("        match self {\n");
+    for entity in &element.children {
+        if name(entity) != "Entity" && name(entity) != "DimensionBase" {
+            let min = match min_version(entity).as_str() {
+                "" => String::from("None"),
+                min => format!("Some(AcadVersion::{min})"),
+            };
+            fun.push_str(&format!(
+                "            EntityType::{typ}(_) => {min},\n",
+                typ = name(entity),
+                min = min
+            ));
+        }
+    }
+    fun.push_str("        }\n");
+    fun.push_str("    }\n");
+}
+
 fn generate_type_string(fun: &mut String, element: &Element) {
     fun.push_str("    pub(crate) fn from_type_string(type_string: &str) -> Option<EntityType> {\n");
     fun.push_str("        match type_string {\n");
@@ -50,6 +50,7 @@ use self::uuid::Uuid;
     generate_flags(&mut fun, &element);
     generate_set_defaults(&mut fun, &element);
     generate_set_header_value(&mut fun, &element);
+    generate_is_known_variable(&mut fun, &element);
     generate_get_code_pairs_internal(&mut fun, &element);
     fun.push_str("}\n");
 
@@ -60,6 +61,7 @@ use self::uuid::Uuid;
 fn generate_struct(fun: &mut String, element: &Element) {
     let mut seen_fields = HashSet::new();
     fun.push_str("/// Contains common properties for the DXF file.\n");
+    fun.push_str("#[derive(Clone)]\n");
     fun.push_str("#[cfg_attr(feature = \"serialize\", derive(Serialize, Deserialize))]\n");
     fun.push_str("pub struct Header {\n");
     for v in &element.children {
@@ -82,6 +84,15 @@ fn generate_struct(fun: &mut String, element: &Element) {
         }
     }
 
+    // `$CUSTOMPROPERTYTAG`/`$CUSTOMPROPERTY` may appear any number of times and are handled in `header.rs`
+    fun.push_str("    #[doc(hidden)]\n");
+    fun.push_str("    pub __custom_properties: Vec<(String, String)>,\n");
+    // `$PREVIEWIMAGE` is moved to and from `Drawing::thumbnail` by `drawing.rs`
+    fun.push_str("    #[doc(hidden)]\n");
+    fun.push_str("    pub __preview_image_data: Vec<u8>,\n");
+    // unrecognized variables are kept and written back by `header.rs`
+    fun.push_str("    #[doc(hidden)]\n");
+    fun.push_str("    pub __unknown_variables: Vec<(String, Vec<CodePair>)>,\n");
     fun.push_str("}\n");
     fun.push('\n');
 }
@@ -103,6 +114,9 @@ fn generate_default(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str("            __custom_properties: vec![],\n");
+    fun.push_str("            __preview_image_data: vec![],\n");
+    fun.push_str("            __unknown_variables: vec![],\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
     fun.push_str("}\n");
@@ -180,6 +194,19 @@ fn generate_set_defaults(fun: &mut String, element: &Element) {
     fun.push_str("    }\n");
 }
 
+fn generate_is_known_variable(fun: &mut String, element: &Element) {
+    let mut seen_names = HashSet::new();
+    fun.push_str("    pub(crate) fn is_known_variable(variable: &str) -> bool {\n");
+    fun.push_str("        matches!(variable,\n");
+    for v in &element.children {
+        if seen_names.insert(name(v)) {
+            fun.push_str(&format!("            \"${name}\" |\n", name = name(v)));
+        }
+    }
+    fun.push_str("            \"$CUSTOMPROPERTYTAG\" | \"$CUSTOMPROPERTY\" | \"$PREVIEWIMAGE\")\n");
+    fun.push_str("    }\n");
+}
+
 fn generate_set_header_value(fun: &mut String, element: &Element) {
     let mut seen_fields = HashSet::new();
     fun.push_str("    #[allow(clippy::cognitive_complexity)] // generated method\n");
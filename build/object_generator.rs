@@ -58,10 +58,15 @@ use self::chrono::{DateTime, Local};
 
     fun.push_str("impl ObjectType {\n");
     generate_is_supported_on_version(&mut fun, &element);
+    generate_min_version(&mut fun, &element);
     generate_type_string(&mut fun, &element);
+    generate_kind(&mut fun, &element);
     generate_try_apply_code_pair(&mut fun, &element);
     generate_write(&mut fun, &element);
+    generate_handle_references(&mut fun, &element);
     fun.push_str("}\n");
+    fun.push('\n');
+    generate_typed_accessors(&mut fun, &element);
 
     let mut file = File::create(generated_dir.join("objects.rs")).ok().unwrap();
     file.write_all(fun.as_bytes()).ok().unwrap();
@@ -157,6 +162,7 @@ fn generate_base_object(fun: &mut String, element: &Element) {
             fun.push_str(&methods_for_pointer_access(p));
         }
     }
+    fun.push_str(&method_for_handle_references(object, &[]));
 
     ////////////////////////////////////////////////////// apply_individual_pair
     fun.push_str("    pub(crate) fn apply_individual_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<bool> {\n");
@@ -233,6 +239,19 @@ fn generate_object_types(fun: &mut String, element: &Element) {
     fun.push_str("}\n");
     fun.push('\n');
 
+    fun.push_str("/// The kind of an `ObjectType` without its data, e.g., for filtering.\n");
+    fun.push_str("#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]\n");
+    fun.push_str("#[cfg_attr(feature = \"serialize\", derive(Serialize, Deserialize))]\n");
+    fun.push_str("pub enum ObjectTypeKind {\n");
+    for c in &element.children {
+        if name(c) != "Object" {
+            fun.push_str(&format!("    {typ},\n", typ = name(c)));
+        }
+    }
+
+    fun.push_str("}\n");
+    fun.push('\n');
+
     // individual structs
     for c in &element.children {
         if c.name != "Object" {
@@ -376,6 +395,7 @@ fn generate_implementation(fun: &mut String, element: &Element) {
             implementation.push_str(&methods_for_pointer_access(field));
         }
     }
+    implementation.push_str(&method_for_handle_references(element, &[]));
 
     if !implementation.is_empty() {
         fun.push_str(&format!("impl {typ} {{\n", typ = name(element)));
@@ -415,6 +435,69 @@ fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
     fun.push_str("    }\n");
 }
 
+fn generate_min_version(fun: &mut String, element: &Element) {
+    fun.push_str("    pub(crate) fn min_version(&self) -> Option<AcadVersion> {\n");
+    fun.push_str("        match self {\n");
+    for object in &element.children {
+        if name(object) != "Object" {
+            let min = match min_version(object).as_str() {
+                "" => String::from("None"),
+                min => format!("Some(AcadVersion::{min})"),
+            };
+            fun.push_str(&format!(
+                "            ObjectType::{typ}(_) => {min},\n",
+                typ = name(object),
+                min = min
+            ));
+        }
+    }
+    fun.push_str("        }\n");
+    fun.push_str("    }\n");
+}
+
+fn generate_kind(fun: &mut String, element: &Element) {
+    fun.push_str("    /// Returns the kind of this object type.\n");
+    fun.push_str("    pub fn kind(&self) -> ObjectTypeKind {\n");
+    fun.push_str("        match *self {\n");
+    for c in &element.children {
+        if name(c) != "Object" {
+            fun.push_str(&format!(
+                "            ObjectType::{typ}(_) => ObjectTypeKind::{typ},\n",
+                typ = name(c)
+            ));
+        }
+    }
+    fun.push_str("        }\n");
+    fun.push_str("    }\n");
+}
+
+fn generate_handle_references(fun: &mut String, element: &Element) {
+    fun.push_str("    pub(crate) fn handle_references_mut(&mut self) -> Vec<&mut Handle> {\n");
+    fun.push_str("        match self {\n");
+    for c in &element.children {
+        if name(c) != "Object" && !method_for_handle_references(c, &[]).is_empty() {
+            fun.push_str(&format!(
+                "            ObjectType::{typ}(ref mut o) => o.handle_references_mut(),\n",
+                typ = name(c)
+            ));
+        }
+    }
+    fun.push_str("            _ => vec![],\n");
+    fun.push_str("        }\n");
+    fun.push_str("    }\n");
+}
+
+fn generate_typed_accessors(fun: &mut String, element: &Element) {
+    fun.push_str("impl Object {\n");
+    for c in &element.children {
+        if name(c) != "Object" {
+            fun.push_str(&methods_for_typed_access("ObjectType", "object", &name(c)));
+        }
+    }
+    fun.push_str("}\n");
+    fun.push('\n');
+}
+
 fn generate_type_string(fun: &mut String, element: &Element) {
     fun.push_str("    pub(crate) fn from_type_string(type_string: &str) -> Option<ObjectType> {\n");
     fun.push_str("        match type_string {\n");
@@ -692,6 +775,9 @@ fn generate_write_code_pairs_for_write_order(
                     attr(write_command, "DontWriteIfValueIs")
                 ));
             }
+            if !attr(write_command, "WriteCondition").is_empty() {
+                predicates.push(attr(write_command, "WriteCondition"));
+            }
             let code = code(write_command);
             let expected_type = ExpectedType::new(code).unwrap();
             let typ = code_pair_type(&expected_type);
@@ -25,3 +25,55 @@ pub fn code_pair_type(typ: &ExpectedType) -> String {
         Binary => String::from("binary"),
     }
 }
+
+pub fn snake_case(name: &str) -> String {
+    // `LwPolyline` => `lw_polyline`, `MText` => `mtext`, `Ole2Frame` => `ole2_frame`, `Face3D` => `face3d`
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut result = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if previous.is_ascii_lowercase() || (previous.is_ascii_digit() && next_is_lower) {
+                result.push('_');
+            }
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+/// Generates the `as_*` and `as_*_mut` accessors for the variant `typ` of `enum_name`, e.g., `EntityType`.
+pub fn methods_for_typed_access(enum_name: &str, item_kind: &str, typ: &str) -> String {
+    let mut fun = String::new();
+    for (suffix, reference, pattern, description) in &[
+        ("", "&", "ref", ""),
+        ("_mut", "&mut ", "ref mut", "mutable "),
+    ] {
+        fun.push_str(&format!(
+            "    /// Returns the {description}`{typ}`, or `None` for other {item_kind} types.\n",
+            description = description,
+            typ = typ,
+            item_kind = item_kind
+        ));
+        fun.push_str(&format!(
+            "    pub fn as_{snake}{suffix}(&{mutability}self) -> Option<{reference}{typ}> {{\n",
+            snake = snake_case(typ),
+            suffix = suffix,
+            mutability = &reference[1..],
+            reference = reference,
+            typ = typ
+        ));
+        fun.push_str("        match self.specific {\n");
+        fun.push_str(&format!(
+            "            {enum_name}::{typ}({pattern} item) => Some(item),\n",
+            enum_name = enum_name,
+            typ = typ,
+            pattern = pattern
+        ));
+        fun.push_str("            _ => None,\n");
+        fun.push_str("        }\n");
+        fun.push_str("    }\n");
+    }
+    fun
+}
@@ -36,6 +36,7 @@ use crate::{
     XData,
 };
 use crate::code_pair_put_back::CodePairPutBack;
+use crate::drawing::MissingTableRecords;
 use crate::helper_functions::*;
 use crate::extension_data;
 use crate::x_data;
@@ -56,7 +57,7 @@ fn generate_table_items(fun: &mut String, element: &Element) {
     for table in &element.children {
         let mut seen_fields = HashSet::new();
         let table_item = &table.children[0];
-        fun.push_str("#[derive(Debug)]\n");
+        fun.push_str("#[derive(Clone, Debug)]\n");
         fun.push_str("#[cfg_attr(feature = \"serialize\", derive(Serialize, Deserialize))]\n");
         fun.push_str(&format!("pub struct {name} {{\n", name = name(table_item)));
         fun.push_str("    pub name: String,\n");
@@ -138,6 +139,10 @@ fn generate_table_items(fun: &mut String, element: &Element) {
         fun.push_str("    pub fn set_owner<'a>(&mut self, item: &'a mut DrawingItemMut, drawing: &'a mut Drawing) {\n");
         fun.push_str("        self.__owner_handle = drawing.assign_and_get_handle(item);\n");
         fun.push_str("    }\n");
+        fun.push_str(&method_for_handle_references(
+            table_item,
+            &["__owner_handle"],
+        ));
         fun.push_str("}\n");
         fun.push('\n');
     }
@@ -317,7 +322,7 @@ fn generate_table_reader(fun: &mut String, element: &Element) {
 
 fn generate_table_writer(fun: &mut String, element: &Element) {
     fun.push_str(
-        "pub(crate) fn add_table_code_pairs(drawing: &Drawing, pairs: &mut Vec<CodePair>, write_handles: bool) {\n",
+        "pub(crate) fn add_table_code_pairs(drawing: &Drawing, missing: &MissingTableRecords, pairs: &mut Vec<CodePair>, write_handles: bool) {\n",
     );
     for table in &element.children {
         let mut indention = "";
@@ -336,7 +341,7 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
             ));
         }
         fun.push_str(&format!(
-            "    {indention}add_{collection}_code_pairs(pairs, drawing, write_handles);\n",
+            "    {indention}add_{collection}_code_pairs(pairs, drawing, missing, write_handles);\n",
             collection = attr(table, "Collection"),
             indention = indention
         ));
@@ -351,9 +356,9 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
     for table in &element.children {
         let table_item = &table.children[0];
         fun.push_str("#[allow(clippy::cognitive_complexity)] // long function, no good way to simplify this\n");
-        fun.push_str(&format!("fn add_{collection}_code_pairs(pairs: &mut Vec<CodePair>, drawing: &Drawing, write_handles: bool) {{\n", collection=attr(table, "Collection")));
+        fun.push_str(&format!("fn add_{collection}_code_pairs(pairs: &mut Vec<CodePair>, drawing: &Drawing, missing: &MissingTableRecords, write_handles: bool) {{\n", collection=attr(table, "Collection")));
         fun.push_str(&format!(
-            "    if !drawing.{collection}().any(|_| true) {{ // is empty\n",
+            "    if !drawing.{collection}().chain(missing.{collection}.iter()).any(|_| true) {{ // is empty\n",
             collection = attr(table, "Collection")
         ));
         fun.push_str("        return; // nothing to add\n");
@@ -376,7 +381,7 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
         fun.push_str("    pairs.push(CodePair::new_str(100, \"AcDbSymbolTable\"));\n");
         fun.push_str("    pairs.push(CodePair::new_i16(70, 0));\n");
         fun.push_str(&format!(
-            "    for item in drawing.{collection}() {{\n",
+            "    for item in drawing.{collection}().chain(missing.{collection}.iter()) {{\n",
             collection = attr(table, "Collection")
         ));
         fun.push_str(&format!(
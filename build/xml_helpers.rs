@@ -2,6 +2,7 @@ extern crate xmltree;
 use self::xmltree::Element;
 use crate::other_helpers::*;
 use crate::ExpectedType;
+use std::collections::HashSet;
 
 pub fn attr(element: &Element, name: &str) -> String {
     match element.attributes.get(name) {
@@ -27,10 +28,10 @@ pub fn codes(element: &Element) -> Vec<i32> {
     if code_overrides.is_empty() {
         vec![code(element)]
     } else {
-        return code_overrides
+        code_overrides
             .split(',')
             .map(|c| c.parse::<i32>().unwrap())
-            .collect::<Vec<_>>();
+            .collect::<Vec<_>>()
     }
 }
 
@@ -262,6 +263,57 @@ pub fn methods_for_pointer_access(pointer: &Element) -> String {
     fun
 }
 
+/// Generates a `handle_references_mut` method returning every pointer and handle-valued field of
+/// `element` (plus `extra_fields`), or an empty string if there are none.  The item's own handle,
+/// which always uses code 5, isn't a reference.
+pub fn method_for_handle_references(element: &Element, extra_fields: &[&str]) -> String {
+    let mut seen = HashSet::new();
+    let mut singles = extra_fields
+        .iter()
+        .map(|f| format!("&mut self.{}", f))
+        .collect::<Vec<_>>();
+    let mut multiples = vec![];
+    for field in &element.children {
+        let (field_name, is_map) = match (&*field.name, &*typ(field)) {
+            ("Pointer", _) => (format!("__{}_handle", name(field)), false),
+            ("Field", "Handle") if code(field) != 5 => (name(field), false),
+            ("Field", "HashMap<String, Handle>") => (name(field), true),
+            _ => continue,
+        };
+        if !seen.insert(field_name.clone()) {
+            continue;
+        }
+        if is_map {
+            multiples.push(format!("self.{}.values_mut()", field_name));
+        } else if allow_multiples(field) {
+            multiples.push(format!("self.{}.iter_mut()", field_name));
+        } else {
+            singles.push(format!("&mut self.{}", field_name));
+        }
+    }
+
+    if singles.is_empty() && multiples.is_empty() {
+        return String::new();
+    }
+
+    let mut fun = String::new();
+    fun.push_str("    pub(crate) fn handle_references_mut(&mut self) -> Vec<&mut Handle> {\n");
+    if multiples.is_empty() {
+        fun.push_str(&format!("        vec![{}]\n", singles.join(", ")));
+    } else {
+        fun.push_str(&format!(
+            "        let mut handles: Vec<&mut Handle> = vec![{}];\n",
+            singles.join(", ")
+        ));
+        for multiple in multiples {
+            fun.push_str(&format!("        handles.extend({});\n", multiple));
+        }
+        fun.push_str("        handles\n");
+    }
+    fun.push_str("    }\n");
+    fun
+}
+
 pub fn min_version(element: &Element) -> String {
     attr(element, "MinVersion")
 }
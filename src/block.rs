@@ -11,7 +11,8 @@ use crate::extension_data;
 use crate::helper_functions::*;
 use crate::x_data;
 
-/// A block is a collection of entities.
+/// A block is a collection of entities.  The insertion units and explodability of a block are stored on
+/// the `BlockRecord` with the same name.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Block {
@@ -43,6 +44,21 @@ pub struct Block {
 
 // public implementation
 impl Block {
+    /// Creates a new `Block` with the specified name and base insertion point.
+    pub fn new(name: &str, base_point: Point) -> Self {
+        Block {
+            name: String::from(name),
+            base_point,
+            ..Default::default()
+        }
+    }
+    /// Creates a new `Block` that is an external reference to the drawing at the specified path.
+    pub fn new_xref(name: &str, base_point: Point, xref_path_name: &str) -> Self {
+        let mut block = Block::new(name, base_point);
+        block.xref_path_name = String::from(xref_path_name);
+        block.set_is_xref(true);
+        block
+    }
     pub fn owner<'a>(&self, drawing: &'a Drawing) -> Option<DrawingItem<'a>> {
         drawing.item_by_handle(self.__owner_handle)
     }
@@ -206,7 +222,7 @@ impl Block {
         write_handles: bool,
     ) {
         pairs.push(CodePair::new_str(0, "BLOCK"));
-        if write_handles && version >= AcadVersion::R13 {
+        if write_handles && version.is_r13_or_later() {
             pairs.push(CodePair::new_string(5, &self.handle.as_string()));
         }
 
@@ -216,7 +232,7 @@ impl Block {
             }
         }
 
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             if !self.__owner_handle.is_empty() {
                 pairs.push(CodePair::new_string(330, &self.__owner_handle.as_string()));
             }
@@ -229,7 +245,7 @@ impl Block {
         }
 
         pairs.push(CodePair::new_string(8, &self.layer));
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, "AcDbBlockBegin"));
         }
 
@@ -266,7 +282,7 @@ impl Block {
             pairs.push(CodePair::new_string(330, &self.__owner_handle.as_string()));
         }
 
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, "AcDbEntity"));
         }
 
@@ -275,7 +291,7 @@ impl Block {
         }
 
         pairs.push(CodePair::new_string(8, &self.layer));
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, "AcDbBlockEnd"));
         }
 
@@ -673,6 +689,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_block_flags() {
+        let block = read_single_block(vec![
+            CodePair::new_str(2, "block-name"),
+            CodePair::new_i16(70, 3),
+        ]);
+        assert!(block.is_anonymous());
+        assert!(block.has_non_consistent_attribute_definitions());
+        assert!(!block.is_xref());
+    }
+
+    #[test]
+    fn write_block_base_point_and_flags() {
+        let mut block = Block::new("block-name", Point::new(1.1, 2.2, 3.3));
+        block.set_is_anonymous(true);
+        assert_block_contains(
+            block,
+            AcadVersion::R12,
+            vec![
+                CodePair::new_str(2, "block-name"),
+                CodePair::new_i16(70, 1),
+                CodePair::new_f64(10, 1.1),
+                CodePair::new_f64(20, 2.2),
+                CodePair::new_f64(30, 3.3),
+            ],
+        );
+    }
+
+    #[test]
+    fn round_trip_xref_block() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_block(Block::new_xref(
+            "xref-block",
+            Point::new(1.0, 2.0, 3.0),
+            "other-drawing.dxf",
+        ));
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let blocks = reparsed.blocks().collect::<Vec<_>>();
+        assert_eq!(1, blocks.len());
+        let block = blocks[0];
+        assert_eq!("xref-block", block.name);
+        assert_eq!(Point::new(1.0, 2.0, 3.0), block.base_point);
+        assert_eq!("other-drawing.dxf", block.xref_path_name);
+        assert!(block.is_xref());
+        assert!(!block.is_anonymous());
+    }
+
     #[test]
     fn round_trip_blocks() {
         let mut drawing = Drawing::new();
@@ -752,4 +817,41 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn round_trip_block_with_attribute_definition() {
+        for version in [AcadVersion::R12, AcadVersion::R2000, AcadVersion::R2018] {
+            let mut drawing = Drawing::new();
+            drawing.header.version = version;
+            let mut attdef = AttributeDefinition::new(
+                "TITLE",
+                "Enter the title",
+                "Untitled",
+                Point::new(1.0, 2.0, 0.0),
+            );
+            attdef.text_height = 2.5;
+            attdef.set_is_invisible(true);
+            let mut block = Block::new("title-block", Point::origin());
+            block
+                .entities
+                .push(Entity::new(EntityType::AttributeDefinition(attdef)));
+            drawing.add_block(block);
+
+            let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+            let block = reparsed.blocks().find(|b| b.name == "title-block").unwrap();
+            assert_eq!(1, block.entities.len());
+            match block.entities[0].specific {
+                EntityType::AttributeDefinition(ref attdef) => {
+                    assert_eq!("TITLE", attdef.text_tag);
+                    assert_eq!("Enter the title", attdef.prompt);
+                    assert_eq!("Untitled", attdef.value);
+                    assert_eq!(Point::new(1.0, 2.0, 0.0), attdef.location);
+                    assert!(approx_eq!(f64, 2.5, attdef.text_height));
+                    assert!(attdef.is_invisible());
+                    assert!(!attdef.is_constant());
+                }
+                _ => panic!("expected an attribute definition"),
+            }
+        }
+    }
 }
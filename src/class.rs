@@ -192,10 +192,8 @@ impl Class {
                             class.class_name = pair.assert_string()?;
                         }
                     }
-                    3 => {
-                        if drawing.header.version >= AcadVersion::R14 {
-                            class.application_name = pair.assert_string()?;
-                        }
+                    3 if drawing.header.version >= AcadVersion::R14 => {
+                        class.application_name = pair.assert_string()?;
                     }
                     90 => {
                         if drawing.header.version <= AcadVersion::R13 {
@@ -419,4 +417,46 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn round_trip_class_r2004() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.classes.push(Class {
+            record_name: "ACDBPLACEHOLDER".to_string(),
+            class_name: "AcDbPlaceHolder".to_string(),
+            application_name: "ObjectDBX Classes".to_string(),
+            version_number: 0,
+            proxy_capability_flags: 1025,
+            instance_count: 3,
+            was_class_loaded_with_file: false,
+            is_entity: true,
+        });
+        let drawing = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(1, drawing.classes.len());
+        let class = drawing.class_for("ACDBPLACEHOLDER").unwrap();
+        assert_eq!("AcDbPlaceHolder", class.class_name);
+        assert_eq!("ObjectDBX Classes", class.application_name);
+        assert_eq!(1025, class.proxy_capability_flags);
+        assert!(class.is_erase_allowed());
+        assert_eq!(3, class.instance_count);
+        assert!(!class.was_class_loaded_with_file);
+        assert!(class.is_entity);
+        assert!(drawing.class_for("SOME_OTHER_CLASS").is_none());
+    }
+
+    #[test]
+    fn classes_not_written_on_r12() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        drawing.classes.push(Class {
+            record_name: "ACDBPLACEHOLDER".to_string(),
+            class_name: "AcDbPlaceHolder".to_string(),
+            ..Default::default()
+        });
+        assert_not_contains_pairs(&drawing, vec![CodePair::new_str(2, "CLASSES")]);
+
+        drawing.header.version = AcadVersion::R13;
+        assert_contains_pairs(&drawing, vec![CodePair::new_str(2, "CLASSES")]);
+    }
 }
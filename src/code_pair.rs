@@ -20,6 +20,7 @@ pub struct CodePair {
 }
 
 impl CodePair {
+    /// Creates a new `CodePair` with the specified value and file offset.
     pub fn new(code: i32, value: CodePairValue, offset: usize) -> Self {
         CodePair {
             code,
@@ -27,30 +28,46 @@ impl CodePair {
             offset,
         }
     }
+    /// Creates a new `CodePair` with the specified value.
+    pub fn new_value(code: i32, value: CodePairValue) -> Self {
+        CodePair::new(code, value, 0)
+    }
+    /// Creates a new `CodePair` with a string value.
     pub fn new_str(code: i32, val: &str) -> Self {
         CodePair::new(code, CodePairValue::Str(val.to_string()), 0)
     }
+    /// Creates a new `CodePair` with a string value.
     pub fn new_string(code: i32, val: &str) -> Self {
         CodePair::new(code, CodePairValue::Str(val.to_string()), 0)
     }
+    /// Creates a new `CodePair` with a binary value.
     pub fn new_binary(code: i32, val: Vec<u8>) -> Self {
         CodePair::new(code, CodePairValue::Binary(val), 0)
     }
+    /// Creates a new `CodePair` with a 16-bit integer value.
     pub fn new_i16(code: i32, val: i16) -> Self {
         CodePair::new(code, CodePairValue::Short(val), 0)
     }
+    /// Creates a new `CodePair` with a floating point value.
     pub fn new_f64(code: i32, val: f64) -> Self {
         CodePair::new(code, CodePairValue::Double(val), 0)
     }
+    /// Creates a new `CodePair` with a 64-bit integer value.
     pub fn new_i64(code: i32, val: i64) -> Self {
         CodePair::new(code, CodePairValue::Long(val), 0)
     }
+    /// Creates a new `CodePair` with a 32-bit integer value.
     pub fn new_i32(code: i32, val: i32) -> Self {
         CodePair::new(code, CodePairValue::Integer(val), 0)
     }
+    /// Creates a new `CodePair` with a boolean value.
     pub fn new_bool(code: i32, val: bool) -> Self {
         CodePair::new(code, CodePairValue::Boolean(if val { 1 } else { 0 }), 0)
     }
+    /// Creates a new `CodePair` with a handle value written as a hex string.
+    pub fn new_handle(code: i32, val: &Handle) -> Self {
+        CodePair::new_string(code, &val.as_string())
+    }
     pub fn assert_bool(&self) -> DxfResult<bool> {
         match self.value {
             CodePairValue::Boolean(s) => Ok(s != 0),
@@ -128,7 +145,7 @@ impl PartialEq for CodePair {
 
 #[cfg(test)]
 mod tests {
-    use crate::{CodePair, Handle};
+    use crate::{CodePair, CodePairValue, Handle};
 
     #[test]
     fn as_handle() {
@@ -154,6 +171,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn construct_from_values() {
+        assert_eq!(
+            CodePair::new_f64(10, 1.5),
+            CodePair::new_value(10, CodePairValue::Double(1.5))
+        );
+        assert_eq!(
+            CodePair::new_str(5, "A1"),
+            CodePair::new_handle(5, &Handle(0xA1))
+        );
+        assert!(CodePair::new_bool(290, true).assert_bool().unwrap());
+    }
+
     #[test]
     fn impl_debug() {
         assert_eq!("[@0]0/str", format!("{:?}", CodePair::new_str(0, "str")));
@@ -6,21 +6,24 @@ use encoding_rs::Encoding;
 use std::io::{Cursor, Read};
 
 pub(crate) trait CodePairIter: Iterator<Item = DxfResult<CodePair>> {
-    fn read_as_utf8(&mut self);
+    fn read_with_encoding(&mut self, encoding: &'static Encoding);
 }
 
 /// Directly returns code pairs; primarily used in tests.
+#[cfg(test)]
 pub(crate) struct DirectCodePairIter {
     pairs: Vec<CodePair>,
     offset: usize,
 }
 
+#[cfg(test)]
 impl CodePairIter for DirectCodePairIter {
-    fn read_as_utf8(&mut self) {
+    fn read_with_encoding(&mut self, _encoding: &'static Encoding) {
         // noop
     }
 }
 
+#[cfg(test)]
 impl Iterator for DirectCodePairIter {
     type Item = DxfResult<CodePair>;
     fn next(&mut self) -> Option<DxfResult<CodePair>> {
@@ -41,6 +44,27 @@ impl DirectCodePairIter {
     }
 }
 
+/// Adds the code, raw value, and expected type to a numeric parse failure.
+fn with_value_context<T>(
+    result: DxfResult<T>,
+    code: i32,
+    value: &str,
+    expected_type: &'static str,
+) -> DxfResult<T> {
+    result.map_err(|e| match e {
+        DxfError::ParseFloatError(_, offset) | DxfError::ParseIntError(_, offset) => {
+            DxfError::InvalidValue {
+                code,
+                value: String::from(value.trim()),
+                expected_type,
+                offset,
+                cause: Box::new(e),
+            }
+        }
+        _ => e,
+    })
+}
+
 /// Returns code pairs as read from text.  Handles the most common DXF files and when parsed from strings.
 pub(crate) struct TextCodePairIter<T: Read> {
     reader: T,
@@ -51,8 +75,8 @@ pub(crate) struct TextCodePairIter<T: Read> {
 }
 
 impl<T: Read> CodePairIter for TextCodePairIter<T> {
-    fn read_as_utf8(&mut self) {
-        self.string_encoding = encoding_rs::UTF_8;
+    fn read_with_encoding(&mut self, encoding: &'static Encoding) {
+        self.string_encoding = encoding;
     }
 }
 
@@ -100,7 +124,7 @@ impl<T: Read> TextCodePairIter<T> {
         }
 
         let code_offset = self.offset;
-        let code = try_into_option!(parse_i32(String::from(code_line), code_offset));
+        let code = try_into_option!(parse_i32(code_line, code_offset));
 
         // Read value.  If no line is available die horribly.
         self.offset += 1;
@@ -115,23 +139,38 @@ impl<T: Read> TextCodePairIter<T> {
             None => return Some(Err(DxfError::UnexpectedEnumValue(self.offset))),
         };
         let value = match expected_type {
-            ExpectedType::Boolean => {
-                CodePairValue::Boolean(try_into_option!(parse_i16(value_line, self.offset)))
-            }
-            ExpectedType::Integer => {
-                CodePairValue::Integer(try_into_option!(parse_i32(value_line, self.offset)))
-            }
-            ExpectedType::Long => {
-                CodePairValue::Long(try_into_option!(parse_i64(value_line, self.offset)))
-            }
-            ExpectedType::Short => {
-                CodePairValue::Short(try_into_option!(parse_i16(value_line, self.offset)))
-            }
-            ExpectedType::Double => {
-                CodePairValue::Double(try_into_option!(parse_f64(value_line, self.offset)))
-            }
+            ExpectedType::Boolean => CodePairValue::Boolean(try_into_option!(with_value_context(
+                parse_i16(&value_line, self.offset),
+                code,
+                &value_line,
+                "bool",
+            ))),
+            ExpectedType::Integer => CodePairValue::Integer(try_into_option!(with_value_context(
+                parse_i32(&value_line, self.offset),
+                code,
+                &value_line,
+                "i32",
+            ))),
+            ExpectedType::Long => CodePairValue::Long(try_into_option!(with_value_context(
+                parse_i64(&value_line, self.offset),
+                code,
+                &value_line,
+                "i64",
+            ))),
+            ExpectedType::Short => CodePairValue::Short(try_into_option!(with_value_context(
+                parse_i16(&value_line, self.offset),
+                code,
+                &value_line,
+                "i16",
+            ))),
+            ExpectedType::Double => CodePairValue::Double(try_into_option!(with_value_context(
+                parse_f64(&value_line, self.offset),
+                code,
+                &value_line,
+                "f64",
+            ))),
             ExpectedType::Str => {
-                let value_line = if self.string_encoding == encoding_rs::WINDOWS_1252 {
+                let value_line = if self.string_encoding != encoding_rs::UTF_8 {
                     un_escape_ascii_to_unicode(&value_line)
                 } else {
                     value_line
@@ -155,14 +194,15 @@ impl<T: Read> TextCodePairIter<T> {
 /// Returns code pairs as read from a binary file.  Usually created _after_ the first line of a file has been read.
 pub(crate) struct BinaryCodePairIter<T: Read> {
     reader: T,
+    string_encoding: &'static Encoding,
     code_size_detection_complete: bool,
     codes_are_two_bytes: bool,
     offset: usize,
 }
 
 impl<T: Read> CodePairIter for BinaryCodePairIter<T> {
-    fn read_as_utf8(&mut self) {
-        // noop
+    fn read_with_encoding(&mut self, encoding: &'static Encoding) {
+        self.string_encoding = encoding;
     }
 }
 
@@ -174,9 +214,10 @@ impl<T: Read> Iterator for BinaryCodePairIter<T> {
 }
 
 impl<T: Read> BinaryCodePairIter<T> {
-    pub fn new(reader: T, offset: usize) -> Self {
+    pub fn new(reader: T, string_encoding: &'static Encoding, offset: usize) -> Self {
         BinaryCodePairIter {
             reader,
+            string_encoding,
             code_size_detection_complete: false,
             codes_are_two_bytes: false,
             offset,
@@ -249,9 +290,16 @@ impl<T: Read> BinaryCodePairIter<T> {
                     self.offset += 1; // account for the NULL byte that was interpreted as an empty string
                     value = try_from_dxf_result!(self.read_string_binary()); // now read the actual value
                 }
+                let read_bytes = value.len() + 1; // +1 to account for the NULL terminator
+                let value = self.string_encoding.decode_without_bom_handling(&value).0;
+                let value = if self.string_encoding != encoding_rs::UTF_8 {
+                    un_escape_ascii_to_unicode(&value)
+                } else {
+                    value.into_owned()
+                };
                 (
                     CodePairValue::Str(CodePairValue::un_escape_string(&value).into_owned()),
-                    value.len() + 1, // +1 to account for the NULL terminator
+                    read_bytes,
                 )
             }
             ExpectedType::Binary => {
@@ -269,12 +317,12 @@ impl<T: Read> BinaryCodePairIter<T> {
 
         Some(Ok(CodePair::new(code, value, self.offset)))
     }
-    fn read_string_binary(&mut self) -> DxfResult<String> {
-        let mut s = String::new();
+    fn read_string_binary(&mut self) -> DxfResult<Vec<u8>> {
+        let mut s = vec![];
         loop {
             match read_u8(&mut self.reader) {
                 Some(Ok(0)) => break,
-                Some(Ok(c)) => s.push(c as char),
+                Some(Ok(c)) => s.push(c),
                 Some(Err(e)) => return Err(DxfError::IoError(e)),
                 None => return Err(DxfError::UnexpectedEndOfInput),
             }
@@ -310,7 +358,7 @@ where
                 0x00,
                 19
             );
-            Box::new(BinaryCodePairIter::new(cursor, 20))
+            Box::new(BinaryCodePairIter::new(cursor, string_encoding, 20))
         }
         _ => Box::new(TextCodePairIter::new(
             cursor,
@@ -325,13 +373,14 @@ where
 #[cfg(test)]
 mod tests {
     use crate::code_pair_iter::{BinaryCodePairIter, TextCodePairIter};
-    use crate::CodePair;
+    use crate::{CodePair, DxfError};
 
     use super::DirectCodePairIter;
 
     fn read_in_binary(codes_are_two_bytes: bool, data: Vec<u8>) -> CodePair {
         let mut reader = BinaryCodePairIter {
             reader: data.as_slice(),
+            string_encoding: encoding_rs::WINDOWS_1252,
             code_size_detection_complete: true,
             codes_are_two_bytes,
             offset: 0,
@@ -404,4 +453,24 @@ mod tests {
         );
         assert!(reader.next().is_none());
     }
+
+    #[test]
+    fn invalid_numeric_value_reports_code_and_value() {
+        let mut reader = TextCodePairIter::<&[u8]> {
+            reader: "10\r\nabc".as_bytes(),
+            string_encoding: encoding_rs::WINDOWS_1252,
+            first_line: String::from("not-important"),
+            read_first_line: true,
+            offset: 0,
+        };
+        match reader.read_code_pair() {
+            Some(Err(e @ DxfError::InvalidValue { .. })) => {
+                assert_eq!(
+                    "\"abc\" is not a valid f64 for code 10 at line/offset 2",
+                    e.to_string()
+                );
+            }
+            other => panic!("expected an invalid value error, got {:?}", other),
+        }
+    }
 }
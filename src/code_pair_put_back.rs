@@ -1,21 +1,31 @@
 use crate::code_pair_iter::CodePairIter;
 use crate::dxf_result::DxfResult;
 use crate::CodePair;
+use encoding_rs::Encoding;
 
 pub(crate) struct CodePairPutBack {
     top: Vec<DxfResult<CodePair>>,
     iter: Box<dyn CodePairIter>,
+    use_code_page: bool,
 }
 
 impl CodePairPutBack {
-    pub fn from_code_pair_iter(iter: Box<dyn CodePairIter>) -> Self {
-        CodePairPutBack { top: vec![], iter }
+    /// When `use_code_page` is `false`, `$DWGCODEPAGE` doesn't change the text encoding.
+    pub fn from_code_pair_iter(iter: Box<dyn CodePairIter>, use_code_page: bool) -> Self {
+        CodePairPutBack {
+            top: vec![],
+            iter,
+            use_code_page,
+        }
+    }
+    pub fn use_code_page(&self) -> bool {
+        self.use_code_page
     }
     pub fn put_back(&mut self, item: DxfResult<CodePair>) {
         self.top.push(item);
     }
-    pub fn read_as_utf8(&mut self) {
-        self.iter.read_as_utf8()
+    pub fn read_with_encoding(&mut self, encoding: &'static Encoding) {
+        self.iter.read_with_encoding(encoding)
     }
 }
 
@@ -27,7 +37,7 @@ impl Iterator for CodePairPutBack {
             loop {
                 let pair = self.iter.next();
                 match pair {
-                    Some(Ok(CodePair { code, .. })) if code == 999 => (), // a 999 comment code, try again
+                    Some(Ok(CodePair { code: 999, .. })) => (), // a 999 comment code, try again
                     _ => return pair,
                 }
             }
@@ -2,6 +2,11 @@ use std::borrow::Cow;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 
+extern crate encoding_rs;
+use self::encoding_rs::Encoding;
+
+use crate::ExpectedType;
+
 /// Contains the data portion of a `CodePair`.
 #[derive(PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -15,6 +20,14 @@ pub enum CodePairValue {
     Binary(Vec<u8>),
 }
 
+impl CodePairValue {
+    /// Returns the type of value stored with the specified group code, or `None` if the code isn't
+    /// defined by the DXF spec.
+    pub fn expected_type(code: i32) -> Option<ExpectedType> {
+        ExpectedType::new(code)
+    }
+}
+
 // internal visibility only
 impl CodePairValue {
     pub(crate) fn un_escape_string(val: &'_ str) -> Cow<'_, str> {
@@ -194,6 +207,28 @@ pub(crate) fn escape_unicode_to_ascii(val: &str) -> String {
     result
 }
 
+pub(crate) fn encode_with_code_page(val: &str, encoding: &'static Encoding) -> Vec<u8> {
+    let mut result = vec![];
+    let mut buf = [0; 4];
+    for c in val.chars() {
+        if c.is_ascii() {
+            result.push(c as u8);
+            continue;
+        }
+
+        let c = c.encode_utf8(&mut buf);
+        let (bytes, _, had_errors) = encoding.encode(c);
+        if had_errors {
+            // not representable in the code page
+            result.extend(escape_unicode_to_ascii(c).bytes());
+        } else {
+            result.extend(bytes.iter());
+        }
+    }
+
+    result
+}
+
 pub(crate) fn un_escape_ascii_to_unicode(val: &str) -> String {
     let mut result = String::from("");
     let mut seq = String::from("");
@@ -232,19 +267,13 @@ pub(crate) fn un_escape_ascii_to_unicode(val: &str) -> String {
     result
 }
 
-/// Formats an `f64` value with up to 12 digits of precision, ensuring at least one trailing digit after the decimal.
-fn format_f64(val: f64) -> String {
-    // format with 12 digits of precision
-    let mut val = format!("{:.12}", val);
-
-    // trim trailing zeros
-    while val.ends_with('0') {
-        val.pop();
-    }
-
-    // ensure it doesn't end with a decimal
-    if val.ends_with('.') {
-        val.push('0');
+/// Formats an `f64` value with the fewest digits that read back as the same value, ensuring at least one trailing
+/// digit after the decimal.
+pub(crate) fn format_f64(val: f64) -> String {
+    // `Display` is the shortest round-trippable form and never uses an exponent
+    let mut val = format!("{}", val);
+    if val.bytes().all(|b| b == b'-' || b.is_ascii_digit()) {
+        val.push_str(".0");
     }
 
     val
@@ -349,4 +378,31 @@ mod tests {
             format!("{}", CodePairValue::Binary(vec![0x01, 0x02, 0x03, 0x04]))
         );
     }
+
+    #[test]
+    fn expected_type_boundaries() {
+        let expected = |code| CodePairValue::expected_type(code);
+        assert_eq!(Some(ExpectedType::Str), expected(9));
+        assert_eq!(Some(ExpectedType::Double), expected(10));
+        assert_eq!(Some(ExpectedType::Double), expected(39));
+        assert_eq!(Some(ExpectedType::Short), expected(60));
+        assert_eq!(Some(ExpectedType::Integer), expected(90));
+        assert_eq!(Some(ExpectedType::Boolean), expected(290));
+        assert_eq!(Some(ExpectedType::Binary), expected(310));
+        assert_eq!(Some(ExpectedType::Str), expected(330));
+        assert_eq!(Some(ExpectedType::Long), expected(160));
+        assert_eq!(Some(ExpectedType::Integer), expected(1071));
+        assert_eq!(None, expected(80));
+        assert_eq!(None, expected(-1));
+    }
+
+    #[test]
+    fn format_f64_is_shortest_round_trippable_form() {
+        assert_eq!("2.0", format_f64(2.0));
+        assert_eq!("-3.0", format_f64(-3.0));
+        assert_eq!("0.1", format_f64(0.1));
+        assert_eq!("6023412.123456789", format_f64(6023412.123456789));
+        assert_eq!("0.000000000000001", format_f64(1e-15));
+        assert_eq!("100000000000000000000.0", format_f64(1e20));
+    }
 }
@@ -3,9 +3,14 @@ use std::io::Write;
 extern crate byteorder;
 use self::byteorder::{LittleEndian, WriteBytesExt};
 
-use crate::code_pair_value::{escape_control_characters, escape_unicode_to_ascii};
+extern crate encoding_rs;
+use self::encoding_rs::Encoding;
+
+use crate::code_pair_value::{
+    encode_with_code_page, escape_control_characters, escape_unicode_to_ascii,
+};
 use crate::enums::AcadVersion;
-use crate::{CodePair, CodePairValue, DxfResult};
+use crate::{CodePair, CodePairValue, DxfResult, ValueFormatter};
 
 pub(crate) struct CodePairWriter<'a, T>
 where
@@ -13,22 +18,25 @@ where
 {
     writer: &'a mut T,
     as_text: bool,
-    text_as_ascii: bool,
+    text_encoding: &'static Encoding,
     version: AcadVersion,
+    value_formatter: &'a dyn ValueFormatter,
 }
 
 impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
     pub fn new(
         writer: &'a mut T,
         as_text: bool,
-        text_as_ascii: bool,
+        text_encoding: &'static Encoding,
         version: AcadVersion,
+        value_formatter: &'a dyn ValueFormatter,
     ) -> Self {
         CodePairWriter {
             writer,
             as_text,
-            text_as_ascii,
+            text_encoding,
             version,
+            value_formatter,
         }
     }
     pub fn write_prelude(&mut self) -> DxfResult<()> {
@@ -48,17 +56,20 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
             self.write_binary_code_pair(pair)
         }
     }
+    pub fn flush(&mut self) -> DxfResult<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
     fn write_ascii_code_pair(&mut self, pair: &CodePair) -> DxfResult<()> {
         self.writer
             .write_fmt(format_args!("{: >3}\r\n", pair.code))?;
         match pair.value {
             CodePairValue::Str(ref s) => {
-                let s = escape_control_characters(s);
-                let s = if self.text_as_ascii {
-                    escape_unicode_to_ascii(&s)
-                } else {
-                    s
-                };
+                self.write_string(s)?;
+                self.writer.write_all(b"\r\n")?;
+            }
+            CodePairValue::Double(d) => {
+                let s = self.value_formatter.format_f64(pair.code, d);
                 self.writer.write_fmt(format_args!("{}\r\n", s))?;
             }
             _ => self.writer.write_fmt(format_args!("{}\r\n", &pair.value))?,
@@ -67,7 +78,7 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
     }
     fn write_binary_code_pair(&mut self, pair: &CodePair) -> DxfResult<()> {
         // write code
-        if self.version >= AcadVersion::R13 {
+        if self.version.is_r13_or_later() {
             self.writer.write_i16::<LittleEndian>(pair.code as i16)?;
         } else if pair.code >= 255 {
             self.writer.write_u8(255)?;
@@ -79,7 +90,7 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
         // write value
         match pair.value {
             CodePairValue::Boolean(s) => {
-                if self.version >= AcadVersion::R13 {
+                if self.version.is_r13_or_later() {
                     self.writer.write_u8(s as u8)?
                 } else {
                     self.writer.write_i16::<LittleEndian>(s)?
@@ -90,10 +101,7 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
             CodePairValue::Short(s) => self.writer.write_i16::<LittleEndian>(s)?,
             CodePairValue::Double(d) => self.writer.write_f64::<LittleEndian>(d)?,
             CodePairValue::Str(ref s) => {
-                for &b in escape_control_characters(s).as_bytes() {
-                    self.writer.write_u8(b)?;
-                }
-
+                self.write_string(s)?;
                 self.writer.write_u8(0)?;
             }
             CodePairValue::Binary(ref buf) => {
@@ -104,15 +112,30 @@ impl<'a, T: Write + ?Sized> CodePairWriter<'a, T> {
             }
         }
 
+        Ok(())
+    }
+    fn write_string(&mut self, s: &str) -> DxfResult<()> {
+        let s = escape_control_characters(s);
+        if self.text_encoding == encoding_rs::UTF_8 {
+            self.writer.write_all(s.as_bytes())?;
+        } else if self.text_encoding == encoding_rs::WINDOWS_1252 {
+            // the default code page is written as plain ASCII so any reader can handle it
+            self.writer
+                .write_all(escape_unicode_to_ascii(&s).as_bytes())?;
+        } else {
+            self.writer
+                .write_all(&encode_with_code_page(&s, self.text_encoding))?;
+        }
+
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::code_pair_writer::CodePairWriter;
+    use crate::code_pair_writer::{encoding_rs, CodePairWriter};
     use crate::enums::AcadVersion;
-    use crate::CodePair;
+    use crate::{CodePair, DefaultValueFormatter};
     use std::io::{BufRead, BufReader, Cursor, Seek, SeekFrom};
 
     fn write_in_binary(pair: &CodePair) -> Vec<u8> {
@@ -120,8 +143,9 @@ mod tests {
         let mut writer = CodePairWriter {
             writer: &mut buf,
             as_text: false,
-            text_as_ascii: true,
+            text_encoding: encoding_rs::WINDOWS_1252,
             version: AcadVersion::R2004,
+            value_formatter: &DefaultValueFormatter,
         };
         writer
             .write_binary_code_pair(pair)
@@ -136,8 +160,9 @@ mod tests {
         let mut writer = CodePairWriter {
             writer: &mut buf,
             as_text: true,
-            text_as_ascii: true,
+            text_encoding: encoding_rs::WINDOWS_1252,
             version: AcadVersion::R2004,
+            value_formatter: &DefaultValueFormatter,
         };
         writer
             .write_ascii_code_pair(pair)
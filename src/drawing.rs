@@ -12,7 +12,11 @@ use crate::header::*;
 use crate::objects::*;
 use crate::tables::*;
 
-use crate::{CodePair, CodePairValue, DxfError, DxfResult, Handle};
+use crate::{
+    CodePair, CodePairValue, Color, DefaultValueFormatter, DxfError, DxfResult, DxfSection,
+    ExtensionGroup, ExtensionGroupItem, Handle, LineWeight, LoadOptions, LoadReport,
+    NormalizeOptions, Point, SaveOptions, TransformationMatrix, XData, XDataItem,
+};
 
 use crate::dxb_reader::DxbReader;
 use crate::dxb_writer::DxbWriter;
@@ -32,13 +36,26 @@ use std::fs::File;
 use std::io::{BufReader, BufWriter, Cursor, Read, Write};
 
 use itertools::put_back;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::iter::Iterator;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub(crate) const AUTO_REPLACE_HANDLE: Handle = Handle(0xFFFF_FFFF_FFFF_FFFF);
 
+const MANDATORY_APP_IDS: [&str; 4] = [
+    "ACAD",
+    "ACADANNOTATIVE",
+    "ACAD_NAV_VCDISPLAY",
+    "ACAD_MLEADERVER",
+];
+const MANDATORY_BLOCK_RECORDS: [&str; 2] = ["*MODEL_SPACE", "*PAPER_SPACE"];
+const MANDATORY_DIM_STYLES: [&str; 2] = ["STANDARD", "ANNOTATIVE"];
+const MANDATORY_LINE_TYPES: [&str; 3] = ["BYLAYER", "BYBLOCK", "CONTINUOUS"];
+const MANDATORY_TEXT_STYLES: [&str; 2] = ["STANDARD", "ANNOTATIVE"];
+const MANDATORY_VIEW_PORTS: [&str; 1] = ["*ACTIVE"];
+
 /// Represents a DXF drawing.
+#[derive(Clone)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Drawing {
     /// The drawing's header.  Contains various drawing-specific values and settings.
@@ -73,8 +90,12 @@ pub struct Drawing {
     __entities: Vec<Entity>,
     /// Internal collection of objects.
     __objects: Vec<Object>,
+    /// The position of each entity in the file it was read from, keyed by handle.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    __entity_read_order: HashMap<Handle, usize>,
 
-    /// The thumbnail image preview of the drawing.
+    /// The thumbnail image preview of the drawing.  This is written to the `THUMBNAILIMAGE` section
+    /// for R2000 and later, and to the `$PREVIEWIMAGE` header variable for earlier versions.
     #[cfg_attr(feature = "serialize", serde(skip))]
     pub thumbnail: Option<DynamicImage>,
 }
@@ -98,20 +119,35 @@ impl Drawing {
             __blocks: vec![],
             __entities: vec![],
             __objects: vec![],
+            __entity_read_order: HashMap::new(),
             thumbnail: None,
         };
         drawing.normalize();
         drawing
     }
-    /// Loads a `Drawing` from anything that implements the `Read` trait.
+    /// Loads a `Drawing` from anything that implements the `Read` trait.  Text is decoded as
+    /// Windows-1252 until a recognized `$DWGCODEPAGE` or an R2007 or later `$ACADVER` is read, then
+    /// the rest of the file is decoded accordingly.
     pub fn load<'a, T>(reader: &mut T) -> DxfResult<Drawing>
     where
         T: Read + 'a + ?Sized,
     {
-        Drawing::load_with_encoding(reader, encoding_rs::WINDOWS_1252)
+        Drawing::load_internal(reader, encoding_rs::WINDOWS_1252, true)
     }
     /// Loads a `Drawing` from anything that implements the `Read` trait using the specified text encoding.
+    /// `$DWGCODEPAGE` doesn't change the encoding, but once an R2007 or later `$ACADVER` is read, the rest of
+    /// the file is decoded as UTF-8.
     pub fn load_with_encoding<T>(reader: &mut T, encoding: &'static Encoding) -> DxfResult<Drawing>
+    where
+        T: Read + ?Sized,
+    {
+        Drawing::load_internal(reader, encoding, false)
+    }
+    fn load_internal<T>(
+        reader: &mut T,
+        encoding: &'static Encoding,
+        use_code_page: bool,
+    ) -> DxfResult<Drawing>
     where
         T: Read + ?Sized,
     {
@@ -123,15 +159,35 @@ impl Drawing {
             }
             _ => {
                 let iter = new_code_pair_iter_from_reader(reader, encoding, first_line)?;
-                Drawing::load_from_iter(iter)
+                Drawing::load_from_iter(iter, use_code_page)
             }
         }
     }
-    /// Loads a `Drawing` from the specified `CodePairIter`.
-    pub(crate) fn load_from_iter(iter: Box<dyn CodePairIter>) -> DxfResult<Drawing> {
+    /// Loads a `Drawing` from anything that implements the `Read` trait using the specified options.
+    pub fn load_with_options<T>(
+        reader: &mut T,
+        options: &LoadOptions,
+    ) -> DxfResult<(Drawing, LoadReport)>
+    where
+        T: Read + ?Sized,
+    {
+        let mut drawing = Drawing::load(reader)?;
+        let mut report = LoadReport::default();
+        if options.repair_handles {
+            report.repaired_handles = drawing.repair_handles();
+        }
+
+        Ok((drawing, report))
+    }
+    /// Loads a `Drawing` from the specified `CodePairIter`.  When `use_code_page` is `true`, the
+    /// encoding switches to match `$DWGCODEPAGE`.
+    pub(crate) fn load_from_iter(
+        iter: Box<dyn CodePairIter>,
+        use_code_page: bool,
+    ) -> DxfResult<Drawing> {
         let mut drawing = Drawing::new();
         drawing.clear();
-        let mut iter = CodePairPutBack::from_code_pair_iter(iter);
+        let mut iter = CodePairPutBack::from_code_pair_iter(iter, use_code_page);
         Drawing::read_sections(&mut drawing, &mut iter)?;
         match iter.next() {
             Some(Ok(CodePair {
@@ -149,9 +205,12 @@ impl Drawing {
     }
     /// Loads a `Drawing` from disk, using a `BufReader`.
     pub fn load_file(path: impl AsRef<Path>) -> DxfResult<Drawing> {
-        Drawing::load_file_with_encoding(path, encoding_rs::WINDOWS_1252)
+        let file = File::open(&path)?;
+        let mut buf_reader = BufReader::new(file);
+        Drawing::load(&mut buf_reader)
     }
-    /// Loads a `Drawing` from disk, using a `BufReader` with the specified text encoding.
+    /// Loads a `Drawing` from disk, using a `BufReader` with the specified text encoding.  See
+    /// `load_with_encoding`.
     pub fn load_file_with_encoding(
         path: impl AsRef<Path>,
         encoding: &'static Encoding,
@@ -160,60 +219,211 @@ impl Drawing {
         let mut buf_reader = BufReader::new(file);
         Drawing::load_with_encoding(&mut buf_reader, encoding)
     }
-    /// Writes a `Drawing` to anything that implements the `Write` trait.
+    /// Loads a `Drawing` from disk using the specified options, using a `BufReader`.
+    pub fn load_file_with_options(
+        path: impl AsRef<Path>,
+        options: &LoadOptions,
+    ) -> DxfResult<(Drawing, LoadReport)> {
+        let file = File::open(&path)?;
+        let mut buf_reader = BufReader::new(file);
+        Drawing::load_with_options(&mut buf_reader, options)
+    }
+    /// Loads a `Drawing` from an in-memory buffer.
+    pub fn load_from_bytes(bytes: &[u8]) -> DxfResult<Drawing> {
+        Drawing::load(&mut Cursor::new(bytes))
+    }
+    /// Writes a `Drawing` to anything that implements the `Write` trait.  Mandatory tables and
+    /// records are added to the written file if they're missing; see `SaveOptions::raw`.
     pub fn save<T>(&self, writer: &mut T) -> DxfResult<()>
     where
         T: Write + ?Sized,
     {
-        self.save_internal(writer, true)
+        self.save_internal(writer, true, &SaveOptions::default())
     }
     /// Writes a `Drawing` as binary to anything that implements the `Write` trait.
     pub fn save_binary<T>(&self, writer: &mut T) -> DxfResult<()>
     where
         T: Write + ?Sized,
     {
-        self.save_internal(writer, false)
+        self.save_internal(writer, false, &SaveOptions::default())
+    }
+    /// Writes a `Drawing` to anything that implements the `Write` trait using the specified options.
+    pub fn save_with_options<T>(&self, writer: &mut T, options: &SaveOptions) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        self.save_internal(writer, true, options)
+    }
+    /// Writes a `Drawing` as binary to anything that implements the `Write` trait using the
+    /// specified options.
+    pub fn save_binary_with_options<T>(
+        &self,
+        writer: &mut T,
+        options: &SaveOptions,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        self.save_internal(writer, false, options)
+    }
+    /// Returns the oldest version that can represent every entity and object in the drawing.
+    pub fn minimum_required_version(&self) -> AcadVersion {
+        let entity_versions = self
+            .entities()
+            .chain(self.blocks().flat_map(|b| b.entities.iter()))
+            .filter_map(|e| e.specific.min_version());
+        let object_versions = self.objects().filter_map(|o| o.specific.min_version());
+        entity_versions
+            .chain(object_versions)
+            .max()
+            .unwrap_or(AcadVersion::Version_1_0)
     }
     /// Gets all code pairs that will be written.
+    #[cfg(test)]
     pub(crate) fn code_pairs(&self) -> DxfResult<Vec<CodePair>> {
-        let write_handles = self.header.version >= AcadVersion::R13 || self.header.handles_enabled;
+        self.code_pairs_with_options(&SaveOptions::default())
+    }
+    fn code_pairs_with_options(&self, options: &SaveOptions) -> DxfResult<Vec<CodePair>> {
+        if options.validate {
+            self.validate()?;
+        }
+        if options.fail_on_unsupported {
+            self.check_supported()?;
+        }
+        let write_handles = self.header.supports_handles();
+        // missing mandatory records are written without being added to the drawing
+        let missing = if options.raw {
+            MissingTableRecords::default()
+        } else {
+            self.missing_table_records()
+        };
+        let handle_seed = Handle(self.header.next_available_handle.0 + missing.len() as u64);
         let mut pairs = Vec::new();
-        self.header.add_code_pairs(&mut pairs);
-        self.add_classes_pairs(&mut pairs);
-        self.add_tables_pairs(&mut pairs, write_handles);
-        self.add_blocks_pairs(&mut pairs, write_handles);
-        self.add_entities_pairs(&mut pairs, write_handles);
-        self.add_objects_pairs(&mut pairs);
-        self.add_thumbnail_pairs(&mut pairs)?;
+        for line in options.comments.iter().flat_map(|c| c.lines()) {
+            pairs.push(CodePair::new_str(999, line));
+        }
+        self.add_header_pairs(&mut pairs, handle_seed)?;
+        if options.includes_section(DxfSection::Classes) {
+            self.add_classes_pairs(&mut pairs);
+        }
+        if options.includes_section(DxfSection::Tables) {
+            self.add_tables_pairs(&mut pairs, &missing, write_handles);
+        }
+        if options.includes_section(DxfSection::Blocks) {
+            self.add_blocks_pairs(&mut pairs, write_handles);
+        }
+        if options.includes_section(DxfSection::Entities) {
+            self.add_entities_pairs(&mut pairs, write_handles, options);
+        }
+        if options.includes_section(DxfSection::Objects) {
+            self.add_objects_pairs(&mut pairs);
+        }
+        if options.includes_section(DxfSection::Thumbnail) {
+            self.add_thumbnail_pairs(&mut pairs)?;
+        }
         pairs.push(CodePair::new_str(0, "EOF"));
         Ok(pairs)
     }
-    fn save_internal<T>(&self, writer: &mut T, as_ascii: bool) -> DxfResult<()>
+    fn save_internal<T>(
+        &self,
+        writer: &mut T,
+        as_ascii: bool,
+        options: &SaveOptions,
+    ) -> DxfResult<()>
     where
         T: Write + ?Sized,
     {
-        let pairs = self.code_pairs()?;
-        let text_as_ascii = self.header.version <= AcadVersion::R2004;
-        let mut code_pair_writer =
-            CodePairWriter::new(writer, as_ascii, text_as_ascii, self.header.version);
+        if options.bump_version_to_minimum_required {
+            let required_version = self.minimum_required_version();
+            if required_version > self.header.version {
+                let mut drawing = self.clone();
+                drawing.header.version = required_version;
+                let mut options = options.clone();
+                options.bump_version_to_minimum_required = false;
+                return drawing.save_internal(writer, as_ascii, &options);
+            }
+        }
+
+        if self.uses_legacy_table_names()
+            && !self
+                .table_names()
+                .iter()
+                .all(|n| Drawing::is_legacy_table_name(n))
+        {
+            let mut drawing = self.clone();
+            drawing.sanitize_table_names();
+            return drawing.save_internal(writer, as_ascii, options);
+        }
+
+        let pairs = self.code_pairs_with_options(options)?;
+        let value_formatter = match options.value_formatter {
+            Some(ref value_formatter) => value_formatter.as_ref(),
+            None => &DefaultValueFormatter,
+        };
+        let mut code_pair_writer = CodePairWriter::new(
+            writer,
+            as_ascii,
+            self.text_encoding(),
+            self.header.version,
+            value_formatter,
+        );
         code_pair_writer.write_prelude()?;
         for pair in pairs {
             code_pair_writer.write_code_pair(&pair)?;
         }
-        Ok(())
+        code_pair_writer.flush()
     }
     /// Writes a `Drawing` to disk, using a `BufWriter`.
     pub fn save_file(&self, path: impl AsRef<Path>) -> DxfResult<()> {
-        self.save_file_internal(path, true)
+        self.save_file_internal(path, true, &SaveOptions::default())
     }
     /// Writes a `Drawing` as binary to disk, using a `BufWriter`.
     pub fn save_file_binary(&self, path: impl AsRef<Path>) -> DxfResult<()> {
-        self.save_file_internal(path, false)
+        self.save_file_internal(path, false, &SaveOptions::default())
+    }
+    /// Writes a `Drawing` to disk using the specified options, using a `BufWriter`.
+    pub fn save_file_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: &SaveOptions,
+    ) -> DxfResult<()> {
+        self.save_file_internal(path, true, options)
+    }
+    /// Writes a copy of the `Drawing` for each of `versions` to `base_path` with the release name
+    /// and a `.dxf` extension appended, e.g., `base_path.R12.dxf`.  Every version gets its own
+    /// file, even those that share an `$ACADVER` value like R11 and R12.  Returns the paths that
+    /// were written.
+    pub fn save_all_versions(
+        &self,
+        base_path: impl AsRef<Path>,
+        versions: &[AcadVersion],
+    ) -> DxfResult<Vec<PathBuf>> {
+        let mut paths = vec![];
+        for version in versions {
+            let mut file_name = base_path.as_ref().as_os_str().to_owned();
+            file_name.push(format!(".{:?}.dxf", version));
+            let path = PathBuf::from(file_name);
+            if paths.contains(&path) {
+                continue;
+            }
+
+            let mut drawing = self.clone();
+            drawing.header.version = *version;
+            drawing.save_file(&path)?;
+            paths.push(path);
+        }
+
+        Ok(paths)
     }
-    fn save_file_internal(&self, path: impl AsRef<Path>, as_ascii: bool) -> DxfResult<()> {
+    fn save_file_internal(
+        &self,
+        path: impl AsRef<Path>,
+        as_ascii: bool,
+        options: &SaveOptions,
+    ) -> DxfResult<()> {
         let file = File::create(&path)?;
         let mut writer = BufWriter::new(file);
-        self.save_internal(&mut writer, as_ascii)
+        self.save_internal(&mut writer, as_ascii, options)
     }
     /// Writes a `Drawing` as DXB to anything that implements the `Write` trait.
     pub fn save_dxb<T>(&self, writer: &mut T) -> DxfResult<()>
@@ -229,6 +439,10 @@ impl Drawing {
         let mut buf_writer = BufWriter::new(file);
         self.save_dxb(&mut buf_writer)
     }
+    /// Returns the class with the specified DXF record name, e.g., `ACDBPLACEHOLDER`.
+    pub fn class_for(&self, dxf_name: &str) -> Option<&Class> {
+        self.classes.iter().find(|c| c.record_name == dxf_name)
+    }
     /// Returns an iterator for all app ids.
     pub fn app_ids(&self) -> impl Iterator<Item = &AppId> {
         self.__app_ids.iter()
@@ -271,6 +485,13 @@ impl Drawing {
     pub fn dim_styles_mut(&mut self) -> impl Iterator<Item = &mut DimStyle> {
         self.__dim_styles.iter_mut()
     }
+    /// Returns the dimension style named by `$DIMSTYLE`, matched case-insensitively.
+    pub fn current_dim_style(&self) -> Option<&DimStyle> {
+        self.__dim_styles.iter().find(|d| {
+            d.name
+                .eq_ignore_ascii_case(&self.header.dimension_style_name)
+        })
+    }
     /// Adds a dimension style to the `Drawing`.
     pub fn add_dim_style(&mut self, mut dim_style: DimStyle) -> &DimStyle {
         dim_style.handle = self.next_handle();
@@ -297,6 +518,74 @@ impl Drawing {
     pub fn remove_layer(&mut self, index: usize) -> Option<Layer> {
         Drawing::remove_item(&mut self.__layers, index)
     }
+    /// Removes the layer named `name`.  Entities and blocks on that layer are moved to
+    /// `reassign_to`, which is created if needed; if `reassign_to` is `None` and the layer is still
+    /// in use, `DxfError::InvalidOperation` is returned and nothing is changed.  If the layer is the
+    /// current layer, `$CLAYER` is reset to `reassign_to` or `"0"`.  Layer names are matched
+    /// case-insensitively and layer `"0"` can't be removed.
+    pub fn remove_layer_by_name(&mut self, name: &str, reassign_to: Option<&str>) -> DxfResult<()> {
+        if name == "0" {
+            return Err(DxfError::InvalidOperation(String::from(
+                "layer \"0\" can't be removed",
+            )));
+        }
+        if matches!(reassign_to, Some(target) if target.eq_ignore_ascii_case(name)) {
+            return Err(DxfError::InvalidOperation(format!(
+                "layer \"{}\" can't be reassigned to itself",
+                name
+            )));
+        }
+        let index = match self
+            .__layers
+            .iter()
+            .position(|l| l.name.eq_ignore_ascii_case(name))
+        {
+            Some(index) => index,
+            None => {
+                return Err(DxfError::InvalidOperation(format!(
+                    "layer \"{}\" doesn't exist",
+                    name
+                )))
+            }
+        };
+
+        let on_layer = |layer: &str| layer.eq_ignore_ascii_case(name);
+        let in_use = self.entities().any(|e| on_layer(&e.common.layer))
+            || self.blocks().any(|b| {
+                on_layer(&b.layer) || b.entities.iter().any(|e| on_layer(&e.common.layer))
+            });
+        let is_current = on_layer(&self.header.current_layer);
+        if in_use && reassign_to.is_none() {
+            return Err(DxfError::InvalidOperation(format!(
+                "layer \"{}\" is still in use",
+                name
+            )));
+        }
+
+        let target = match reassign_to {
+            Some(target) if in_use || is_current => self.resolve_layer_name(target, true)?,
+            _ => String::from("0"),
+        };
+        if in_use {
+            for entity in self
+                .__entities
+                .iter_mut()
+                .chain(self.__blocks.iter_mut().flat_map(|b| b.entities.iter_mut()))
+                .filter(|e| on_layer(&e.common.layer))
+            {
+                entity.common.layer = target.clone();
+            }
+            for block in self.__blocks.iter_mut().filter(|b| on_layer(&b.layer)) {
+                block.layer = target.clone();
+            }
+        }
+        if is_current {
+            self.header.current_layer = target;
+        }
+
+        self.__layers.remove(index);
+        Ok(())
+    }
     /// Returns an iterator for all line types.
     pub fn line_types(&self) -> impl Iterator<Item = &LineType> {
         self.__line_types.iter()
@@ -407,38 +696,501 @@ impl Drawing {
     pub fn entities_mut(&mut self) -> impl Iterator<Item = &mut Entity> {
         self.__entities.iter_mut()
     }
-    /// Adds an entity to the `Drawing`.
-    pub fn add_entity(&mut self, mut entity: Entity) -> &Entity {
-        entity.common.handle = self.next_handle();
+    /// Consumes the drawing and returns its top-level entities.
+    pub fn into_entities(self) -> Vec<Entity> {
+        self.__entities
+    }
+    /// Consumes the drawing and returns its header, top-level entities, and objects.
+    pub fn into_parts(self) -> (Header, Vec<Entity>, Vec<Object>) {
+        (self.header, self.__entities, self.__objects)
+    }
+    /// Calls `f` on every top-level entity.  Entities inside blocks are not visited.
+    pub fn apply_to_entities<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Entity),
+    {
+        self.__entities.iter_mut().for_each(f);
+    }
+    /// Calls `f` on every top-level entity that matches `pred`.  Entities inside blocks are not
+    /// visited.
+    pub fn apply_to_entities_where<P, F>(&mut self, mut pred: P, mut f: F)
+    where
+        P: FnMut(&Entity) -> bool,
+        F: FnMut(&mut Entity),
+    {
+        for ent in self.__entities.iter_mut() {
+            if pred(ent) {
+                f(ent);
+            }
+        }
+    }
+    /// Returns the entities whose bounding box intersects the rectangle in the XY plane spanned by
+    /// `min` and `max`.  Entities without a bounding box are never returned.
+    pub fn entities_in_rect(&self, min: Point, max: Point) -> Vec<&Entity> {
+        let (min_x, max_x) = (min.x.min(max.x), min.x.max(max.x));
+        let (min_y, max_y) = (min.y.min(max.y), min.y.max(max.y));
+        self.__entities
+            .iter()
+            .filter(|e| match e.bounding_box() {
+                Some((lo, hi)) => lo.x <= max_x && hi.x >= min_x && lo.y <= max_y && hi.y >= min_y,
+                None => false,
+            })
+            .collect()
+    }
+    /// Returns the top-level `Text`, `MText`, `Attribute`, and `AttributeDefinition` entities whose
+    /// text contains `needle`, and the `Insert` entities with a matching attribute.  `MText` is
+    /// searched without its formatting codes.
+    pub fn find_text(&self, needle: &str, case_sensitive: bool) -> Vec<&Entity> {
+        let needle = if case_sensitive {
+            String::from(needle)
+        } else {
+            needle.to_lowercase()
+        };
+        let matches = |text: &str| {
+            if case_sensitive {
+                text.contains(&needle)
+            } else {
+                text.to_lowercase().contains(&needle)
+            }
+        };
+        self.__entities
+            .iter()
+            .filter(|e| match e.specific {
+                EntityType::Attribute(ref att) => matches(&att.value),
+                EntityType::AttributeDefinition(ref att) => matches(&att.value),
+                EntityType::Insert(ref ins) => ins.attributes().any(|att| matches(&att.value)),
+                EntityType::MText(ref mtext) => matches(&mtext.plain_text()),
+                EntityType::Text(ref text) => matches(&text.value),
+                _ => false,
+            })
+            .collect()
+    }
+    /// Returns the names of the blocks referenced by the top-level entities, including the blocks
+    /// those blocks reference in turn.  Inserts, dimensions, and tables are considered.  Names are
+    /// matched ignoring case and reported with the block's spelling when the block exists.
+    pub fn blocks_in_use(&self) -> HashSet<String> {
+        let blocks = self
+            .__blocks
+            .iter()
+            .map(|b| (b.name.to_ascii_uppercase(), b))
+            .collect::<HashMap<_, _>>();
+        let mut seen = HashSet::new();
+        let mut in_use = HashSet::new();
+        let mut pending = self
+            .__entities
+            .iter()
+            .filter_map(Drawing::referenced_block_name)
+            .collect::<Vec<_>>();
+        while let Some(name) = pending.pop() {
+            let key = name.to_ascii_uppercase();
+            if !seen.insert(key.clone()) {
+                continue;
+            }
 
-        // set child handles
-        match entity.specific {
-            EntityType::Insert(ref mut ins) => {
-                ins.__seqend_handle = self.next_handle();
-                for a in ins.__attributes_and_handles.iter_mut() {
-                    if a.1 == AUTO_REPLACE_HANDLE {
-                        a.1 = self.next_handle();
-                    }
+            match blocks.get(&key) {
+                Some(block) => {
+                    in_use.insert(block.name.clone());
+                    pending.extend(
+                        block
+                            .entities
+                            .iter()
+                            .filter_map(Drawing::referenced_block_name),
+                    );
                 }
-            }
-            EntityType::Polyline(ref mut poly) => {
-                poly.__seqend_handle = self.next_handle();
-                for v in poly.__vertices_and_handles.iter_mut() {
-                    if v.1 == AUTO_REPLACE_HANDLE {
-                        v.1 = self.next_handle();
-                    }
+                None => {
+                    in_use.insert(String::from(name));
                 }
             }
-            _ => (),
         }
 
+        in_use
+    }
+    /// Returns the number of top-level entities on each layer.  Layer names are compared
+    /// case-insensitively and reported with the spelling of the first matching layer in the table,
+    /// or as first seen if the layer isn't in the table.
+    pub fn entities_count_by_layer(&self) -> HashMap<String, usize> {
+        let mut layer_names = HashMap::new();
+        for layer in &self.__layers {
+            layer_names
+                .entry(layer.name.to_ascii_uppercase())
+                .or_insert(layer.name.as_str());
+        }
+        let mut counts: HashMap<String, (&str, usize)> = HashMap::new();
+        for entity in &self.__entities {
+            let key = entity.common.layer.to_ascii_uppercase();
+            let name = layer_names
+                .get(&key)
+                .copied()
+                .unwrap_or(&entity.common.layer);
+            counts.entry(key).or_insert((name, 0)).1 += 1;
+        }
+
+        counts
+            .into_values()
+            .map(|(name, count)| (String::from(name), count))
+            .collect()
+    }
+    fn referenced_block_name(entity: &Entity) -> Option<&str> {
+        let name = match entity.specific {
+            EntityType::Insert(ref ins) => &ins.name,
+            EntityType::Table(ref table) => &table.block_name,
+            _ => &entity.dimension_base()?.block_name,
+        };
+        if name.is_empty() {
+            None
+        } else {
+            Some(name.as_str())
+        }
+    }
+    /// Checks the drawing for values that would produce an invalid file.  This is also done when
+    /// the drawing is saved with `SaveOptions::validate`.
+    pub fn validate(&self) -> DxfResult<()> {
+        let block_entities = self.__blocks.iter().flat_map(|b| b.entities.iter());
+        for entity in self.__entities.iter().chain(block_entities) {
+            if let EntityType::Spline(ref spline) = entity.specific {
+                spline.validate()?;
+            }
+        }
+
+        // drawings without any dimension styles, e.g., after `clear()`, aren't checked
+        if !self.header.dimension_style_name.is_empty()
+            && !self.__dim_styles.is_empty()
+            && self.current_dim_style().is_none()
+        {
+            return Err(DxfError::ValidationError(format!(
+                "the current dimension style `{}` doesn't exist",
+                self.header.dimension_style_name
+            )));
+        }
+
+        Ok(())
+    }
+    /// Adds an entity to the `Drawing`.
+    pub fn add_entity(&mut self, mut entity: Entity) -> &Entity {
+        self.set_entity_handles(&mut entity, false);
+
         // ensure invariants
         self.add_entity_no_handle_set(entity)
     }
+    /// Adds an entity to the `Drawing` using the current settings from the header.  Entities on the default layer
+    /// `"0"` are moved to the current layer and `BYLAYER` colors and line types are replaced by the current ones;
+    /// any other explicitly set value always wins.
+    pub fn add_entity_with_current_settings(&mut self, mut entity: Entity) -> &Entity {
+        if entity.common.layer == "0" {
+            entity.common.layer = self.header.current_layer.clone();
+        }
+        if entity.common.color == Color::by_layer() {
+            entity.common.color = self.header.current_entity_color.clone();
+        }
+        if entity.common.line_type_name.eq_ignore_ascii_case("BYLAYER") {
+            entity.common.line_type_name = self.header.current_entity_line_type.clone();
+        }
+
+        self.add_entity(entity)
+    }
+    /// Appends a copy of the entity with the specified handle, including any vertices or
+    /// attributes, to the same entity list as the original.  The copy and its children get new
+    /// handles.  Returns the handle of the copy, or `None` if no entity has that handle.
+    pub fn copy_entity(&mut self, handle: Handle) -> Option<Handle> {
+        if let Some(entity) = self.__entities.iter().find(|e| e.common.handle == handle) {
+            let mut copy = entity.clone();
+            self.set_entity_handles(&mut copy, true);
+            let new_handle = copy.common.handle;
+            self.__entities.push(copy);
+            return Some(new_handle);
+        }
+
+        for block_index in 0..self.__blocks.len() {
+            let entities = &self.__blocks[block_index].entities;
+            if let Some(entity) = entities.iter().find(|e| e.common.handle == handle) {
+                let mut copy = entity.clone();
+                self.set_entity_handles(&mut copy, true);
+                let new_handle = copy.common.handle;
+                self.__blocks[block_index].entities.push(copy);
+                return Some(new_handle);
+            }
+        }
+
+        None
+    }
+    /// Moves the entity with the specified handle to `layer`, matched case-insensitively.  If no
+    /// such layer exists it's created when `create_layer` is `true`, otherwise
+    /// `DxfError::InvalidOperation` is returned.
+    pub fn move_entity_to_layer(
+        &mut self,
+        handle: Handle,
+        layer: &str,
+        create_layer: bool,
+    ) -> DxfResult<()> {
+        if !self
+            .entities_and_block_entities()
+            .any(|e| e.common.handle == handle)
+        {
+            return Err(DxfError::InvalidOperation(format!(
+                "no entity has handle {}",
+                handle.as_string()
+            )));
+        }
+
+        let layer = self.resolve_layer_name(layer, create_layer)?;
+        for entity in self
+            .__entities
+            .iter_mut()
+            .chain(self.__blocks.iter_mut().flat_map(|b| b.entities.iter_mut()))
+            .filter(|e| e.common.handle == handle)
+        {
+            entity.common.layer = layer.clone();
+        }
+
+        Ok(())
+    }
+    /// Like `move_entity_to_layer`, but moves a copy of the entity made with `copy_entity` and
+    /// returns the handle of the copy.
+    pub fn copy_entity_to_layer(
+        &mut self,
+        handle: Handle,
+        layer: &str,
+        create_layer: bool,
+    ) -> DxfResult<Handle> {
+        if !self
+            .entities_and_block_entities()
+            .any(|e| e.common.handle == handle)
+        {
+            return Err(DxfError::InvalidOperation(format!(
+                "no entity has handle {}",
+                handle.as_string()
+            )));
+        }
+
+        // resolve the layer first so nothing is copied on failure
+        let layer = self.resolve_layer_name(layer, create_layer)?;
+        let copy = self.copy_entity(handle).unwrap();
+        self.move_entity_to_layer(copy, &layer, false)?;
+        Ok(copy)
+    }
+    /// Sets the layer applied to entities added with `add_entity_with_current_settings`.  The layer is created if it doesn't exist.
+    pub fn set_current_layer(&mut self, layer_name: &str) {
+        self.header.current_layer = String::from(layer_name);
+        self.ensure_layer_is_present(layer_name);
+    }
+    /// Sets the color applied to entities added with `add_entity_with_current_settings`.
+    pub fn set_current_color(&mut self, color: Color) {
+        self.header.current_entity_color = color;
+    }
+    /// Sets the line type applied to entities added with `add_entity_with_current_settings`.  The line type is created if it doesn't
+    /// exist.
+    pub fn set_current_line_type(&mut self, line_type_name: &str) {
+        self.header.current_entity_line_type = String::from(line_type_name);
+        self.ensure_line_type_is_present(line_type_name);
+    }
+    /// Sets the `$CELWEIGHT` line weight used for new objects.
+    pub fn set_current_line_weight(&mut self, line_weight: LineWeight) {
+        self.header.new_object_line_weight = line_weight;
+    }
     /// Removes the specified `Entity` from the `Drawing`.
     pub fn remove_entity(&mut self, index: usize) -> Option<Entity> {
         Drawing::remove_item(&mut self.__entities, index)
     }
+    /// Replaces the `Insert` at the specified index with transformed copies of its block's entities, one copy for
+    /// each cell of the insert's row/column array.  The insert's attributes are converted to `Text` entities.
+    /// Returns `false` if the entity isn't an `Insert` or its block can't be found.
+    pub fn explode_insert(&mut self, insert_index: usize) -> bool {
+        let insert_entity = match self.__entities.get(insert_index) {
+            Some(e) => e,
+            None => return false,
+        };
+        let insert = match insert_entity.specific {
+            EntityType::Insert(ref ins) => ins,
+            _ => return false,
+        };
+        let block = match self
+            .__blocks
+            .iter()
+            .find(|b| b.name.eq_ignore_ascii_case(&insert.name))
+        {
+            Some(b) => b,
+            None => return false,
+        };
+
+        let base_point = TransformationMatrix::translation(
+            -block.base_point.x,
+            -block.base_point.y,
+            -block.base_point.z,
+        );
+        let mut exploded = vec![];
+        for row in 0..insert.row_count.max(1) {
+            for column in 0..insert.column_count.max(1) {
+                let m = insert.to_matrix_for_cell(row, column) * base_point;
+                for block_entity in &block.entities {
+                    if let EntityType::AttributeDefinition(_) = block_entity.specific {
+                        // attribute values are carried by the insert
+                        continue;
+                    }
+
+                    let mut entity = block_entity.clone();
+                    entity.transform(&m);
+                    exploded.push(entity);
+                }
+            }
+        }
+        for att in insert.attributes() {
+            let mut common = insert_entity.common.clone();
+            common.x_data.clear();
+            common.extension_data_groups.clear();
+            exploded.push(Entity {
+                common,
+                specific: EntityType::Text(Text {
+                    thickness: att.thickness,
+                    location: att.location.clone(),
+                    text_height: att.text_height,
+                    value: att.value.clone(),
+                    rotation: att.rotation,
+                    relative_x_scale_factor: att.relative_x_scale_factor,
+                    oblique_angle: att.oblique_angle,
+                    text_style_name: att.text_style_name.clone(),
+                    text_generation_flags: att.text_generation_flags,
+                    horizontal_text_justification: att.horizontal_text_justification,
+                    second_alignment_point: att.second_alignment_point.clone(),
+                    normal: att.normal.clone(),
+                    vertical_text_justification: att.vertical_text_justification,
+                }),
+            });
+        }
+
+        self.__entities.remove(insert_index);
+        for (i, mut entity) in exploded.into_iter().enumerate() {
+            self.set_entity_handles(&mut entity, true);
+            self.ensure_entity_dependencies_are_present(&entity);
+            self.__entities.insert(insert_index + i, entity);
+        }
+
+        true
+    }
+    /// Sets `$EXTMIN`/`$EXTMAX` to the bounds of the model space entities and `$PEXTMIN`/`$PEXTMAX`
+    /// to the bounds of the paper space entities.  A space without any bounded entities keeps its
+    /// previous extents.
+    pub fn update_extents(&mut self) {
+        let mut model_space: Option<(Point, Point)> = None;
+        let mut paper_space = None;
+        for entity in &self.__entities {
+            if let Some((min, max)) = entity.bounding_box() {
+                let extents = if entity.common.is_in_paper_space {
+                    &mut paper_space
+                } else {
+                    &mut model_space
+                };
+                *extents = Some(match extents.take() {
+                    Some((lo, hi)) => (
+                        Point::new(lo.x.min(min.x), lo.y.min(min.y), lo.z.min(min.z)),
+                        Point::new(hi.x.max(max.x), hi.y.max(max.y), hi.z.max(max.z)),
+                    ),
+                    None => (min, max),
+                });
+            }
+        }
+
+        let header = &mut self.header;
+        if let Some((min, max)) = model_space {
+            header.minimum_drawing_extents = min;
+            header.maximum_drawing_extents = max;
+        }
+        if let Some((min, max)) = paper_space {
+            header.paperspace_minimum_drawing_extents = min;
+            header.paperspace_maximum_drawing_extents = max;
+        }
+    }
+    /// Uniformly scales the drawing about the origin.  Entity coordinates and sizes such as radii,
+    /// text heights, and thicknesses, block base points, and the size-related header variables are
+    /// all multiplied by `factor`.  Each entity's own line type scale is left alone since `$LTSCALE`
+    /// already scales every line type pattern.
+    pub fn scale(&mut self, factor: f64) {
+        let m = TransformationMatrix::scale(factor, factor, factor);
+        for entity in self
+            .__entities
+            .iter_mut()
+            .chain(self.__blocks.iter_mut().flat_map(|b| b.entities.iter_mut()))
+        {
+            Drawing::scale_entity(entity, &m, factor);
+        }
+        for block in self.__blocks.iter_mut() {
+            block.base_point = m.transform_point(&block.base_point);
+        }
+
+        let header = &mut self.header;
+        for p in [
+            &mut header.insertion_base,
+            &mut header.minimum_drawing_extents,
+            &mut header.maximum_drawing_extents,
+            &mut header.minimum_drawing_limits,
+            &mut header.maximum_drawing_limits,
+        ] {
+            *p = m.transform_point(p);
+        }
+        header.line_type_scale *= factor;
+        header.default_text_height *= factor;
+        header.trace_width *= factor;
+        header.default_polyline_width *= factor;
+        header.elevation *= factor;
+        header.thickness *= factor;
+    }
+    /// Projects all top-level and block entities onto the world XY plane.  See
+    /// `Entity::flatten_to_2d` for details.  Entities that can't be flattened, such as 3D solids
+    /// and meshes, are removed when `remove_unflattenable` is `true` and left unchanged otherwise.
+    /// Returns the number of entities that couldn't be flattened.
+    pub fn flatten_to_2d(&mut self, remove_unflattenable: bool) -> usize {
+        let mut unflattenable =
+            Drawing::flatten_entities(&mut self.__entities, remove_unflattenable);
+        for block in self.__blocks.iter_mut() {
+            unflattenable += Drawing::flatten_entities(&mut block.entities, remove_unflattenable);
+        }
+
+        unflattenable
+    }
+    fn flatten_entities(entities: &mut Vec<Entity>, remove_unflattenable: bool) -> usize {
+        let mut unflattenable = 0;
+        entities.retain_mut(|entity| {
+            let flattened = entity.flatten_to_2d();
+            if !flattened {
+                unflattenable += 1;
+            }
+
+            flattened || !remove_unflattenable
+        });
+        unflattenable
+    }
+    /// Scales the drawing from `from` units to `to` units and sets `$INSUNITS` to `to`.  If either
+    /// unit is `Unitless` only `$INSUNITS` is updated.
+    pub fn convert_units(&mut self, from: Units, to: Units) {
+        if let (Some(from_meters), Some(to_meters)) = (meters_per_unit(from), meters_per_unit(to)) {
+            self.scale(from_meters / to_meters);
+        }
+
+        self.header.default_drawing_units = to;
+    }
+    /// Replaces BYLAYER colors, line types, and line weights on top-level entities with the values
+    /// from each entity's layer so they render the same without that layer.
+    pub fn clone_layer_properties_to_entities(&mut self) {
+        let layers = &self.__layers;
+        for entity in self.__entities.iter_mut() {
+            let layer = match layers
+                .iter()
+                .find(|l| l.name.eq_ignore_ascii_case(&entity.common.layer))
+            {
+                Some(layer) => layer,
+                None => continue,
+            };
+            let common = &mut entity.common;
+            if common.color.is_by_layer() {
+                common.color = layer.color.clone();
+            }
+            if common.line_type_name.eq_ignore_ascii_case("BYLAYER") {
+                common.line_type_name = layer.line_type_name.clone();
+            }
+            if common.lineweight_enum_value == LineWeight::by_layer().raw_value() {
+                common.lineweight_enum_value = layer.line_weight.raw_value();
+            }
+        }
+    }
     /// Returns an iterator for all contained objects.
     pub fn objects(&self) -> impl Iterator<Item = &Object> {
         self.__objects.iter()
@@ -447,6 +1199,12 @@ impl Drawing {
     pub fn objects_mut(&mut self) -> impl Iterator<Item = &mut Object> {
         self.__objects.iter_mut()
     }
+    /// Returns an iterator for all objects of the specified kind.
+    pub fn objects_of_type(&self, kind: ObjectTypeKind) -> impl Iterator<Item = &Object> {
+        self.__objects
+            .iter()
+            .filter(move |o| o.specific.kind() == kind)
+    }
     /// Adds an object to the `Drawing`.
     pub fn add_object(&mut self, mut obj: Object) -> &Object {
         obj.common.handle = self.next_handle();
@@ -460,6 +1218,40 @@ impl Drawing {
     pub fn remove_object(&mut self, index: usize) -> Option<Object> {
         Drawing::remove_item(&mut self.__objects, index)
     }
+    /// Returns the object holding the root (named object) dictionary.  This is the first object in
+    /// the `OBJECTS` section when that's a dictionary, otherwise the first dictionary without an
+    /// owner.
+    pub fn root_dictionary(&self) -> Option<&Object> {
+        match self.__objects.first() {
+            Some(obj) if obj.as_dictionary().is_some() => Some(obj),
+            _ => self
+                .__objects
+                .iter()
+                .find(|o| o.as_dictionary().is_some() && o.common.__owner_handle.is_empty()),
+        }
+    }
+    /// Returns an iterator for all `Group` objects and their names from the `ACAD_GROUP`
+    /// dictionary.  Groups not found in a dictionary have an empty name.
+    pub fn groups(&self) -> impl Iterator<Item = (&str, &Group)> {
+        self.__objects.iter().filter_map(move |o| match o.specific {
+            ObjectType::Group(ref group) => Some((self.dictionary_key_for(o.common.handle), group)),
+            _ => None,
+        })
+    }
+    /// Adds a `Group` to the `ACAD_GROUP` dictionary under the specified name, creating the
+    /// dictionary if necessary.
+    pub fn add_group(&mut self, name: &str, group: Group) -> &Object {
+        let group_dictionary_handle = self.ensure_group_dictionary();
+        let mut obj = Object::new(ObjectType::Group(group));
+        obj.common.handle = self.next_handle();
+        obj.common.__owner_handle = group_dictionary_handle;
+        let group_handle = obj.common.handle;
+        if let Some(dict) = self.dictionary_mut(group_dictionary_handle) {
+            dict.value_handles.insert(String::from(name), group_handle);
+        }
+
+        self.add_object_no_handle_set(obj)
+    }
     /// Clears all items from the `Drawing`.
     pub fn clear(&mut self) {
         self.classes.clear();
@@ -475,12 +1267,62 @@ impl Drawing {
         self.__blocks.clear();
         self.__entities.clear();
         self.__objects.clear();
+        self.__entity_read_order.clear();
         self.thumbnail = None;
 
         self.header.next_available_handle = Handle(1);
     }
+    /// Renames table entries whose names can't be written when `$EXTNAMES` is off or the version
+    /// is before R2000.  Those names are limited to 31 characters of letters, digits, `$`, `-`,
+    /// and `_`; other characters become `_` and collisions get a numeric suffix.  References from
+    /// entities, blocks, layers, and the header are updated.  Returns the renamed entries as
+    /// `old name -> new name`.  This is done automatically when the drawing is saved.
+    pub fn sanitize_table_names(&mut self) -> HashMap<String, String> {
+        let mut renames = HashMap::new();
+        if !self.uses_legacy_table_names() {
+            return renames;
+        }
+
+        let names = self.table_names();
+        let mut used = names
+            .iter()
+            .filter(|n| Drawing::is_legacy_table_name(n))
+            .cloned()
+            .collect::<HashSet<_>>();
+        for name in names {
+            if Drawing::is_legacy_table_name(&name) || renames.contains_key(&name) {
+                continue;
+            }
+
+            let base = Drawing::legacy_table_name(&name);
+            let mut candidate = base.clone();
+            let mut suffix = 1;
+            while used.contains(&candidate) {
+                let suffix_text = format!("_{}", suffix);
+                candidate = base
+                    .chars()
+                    .take(31 - suffix_text.len())
+                    .chain(suffix_text.chars())
+                    .collect();
+                suffix += 1;
+            }
+
+            used.insert(candidate.clone());
+            renames.insert(name, candidate);
+        }
+
+        if !renames.is_empty() {
+            self.apply_table_renames(&renames);
+        }
+
+        renames
+    }
     /// Normalizes the `Drawing` by ensuring expected items are present.
     pub fn normalize(&mut self) {
+        self.normalize_with_options(&NormalizeOptions::default());
+    }
+    /// Normalizes the `Drawing` by ensuring expected items are present, as specified by `options`.
+    pub fn normalize_with_options(&mut self, options: &NormalizeOptions) {
         // TODO: check for duplicates
         self.header.normalize();
         self.normalize_blocks();
@@ -498,6 +1340,9 @@ impl Drawing {
         self.ensure_text_styles();
         self.ensure_view_ports();
         self.ensure_ucs();
+        if options.link_block_records {
+            self.link_block_records();
+        }
 
         self.__app_ids.sort_by(|a, b| a.name.cmp(&b.name));
         self.__block_records.sort_by(|a, b| a.name.cmp(&b.name));
@@ -509,6 +1354,38 @@ impl Drawing {
         self.__views.sort_by(|a, b| a.name.cmp(&b.name));
         self.__view_ports.sort_by(|a, b| a.name.cmp(&b.name));
     }
+    /// Ensures every block has a block record with the same name and vice versa, creating any that
+    /// are missing.  Each block's owner is set to its block record.  This isn't part of `normalize`
+    /// by default because it adds an empty block for every unused block record, e.g.,
+    /// `*MODEL_SPACE`; see `NormalizeOptions::link_block_records`.
+    pub fn link_block_records(&mut self) {
+        let mut blocks = std::mem::take(&mut self.__blocks);
+        for block in &mut blocks {
+            self.ensure_block_record_is_present_for_block(block);
+        }
+        self.__blocks = blocks;
+
+        let missing = self
+            .__block_records
+            .iter()
+            .filter(|r| {
+                !self
+                    .__blocks
+                    .iter()
+                    .any(|b| b.name.eq_ignore_ascii_case(&r.name))
+            })
+            .map(|r| (r.name.clone(), r.handle))
+            .collect::<Vec<_>>();
+        for (name, handle) in missing {
+            let block = Block {
+                name,
+                handle: self.next_handle(),
+                __owner_handle: handle,
+                ..Default::default()
+            };
+            self.__blocks.push(block);
+        }
+    }
     /// Gets a `DrawingItem` with the appropriate handle or `None`.
     pub fn item_by_handle(&'_ self, handle: Handle) -> Option<DrawingItem<'_>> {
         for item in &self.__app_ids {
@@ -639,48 +1516,624 @@ impl Drawing {
 
         None
     }
-    pub(crate) fn assign_and_get_handle(&mut self, item: &mut DrawingItemMut) -> Handle {
-        if item.handle().is_empty() {
-            item.set_handle(self.header.next_available_handle);
-            self.header.next_available_handle =
-                self.header.next_available_handle.next_handle_value();
+    /// Gives every item that shares a non-empty handle with an earlier item a new handle; the first
+    /// item keeps the original, so references to it are unchanged.  Objects that a renumbered
+    /// dictionary owns are moved to the dictionary's new handle.  Returns the `(old, new)` handle
+    /// of each renumbered item.
+    pub fn repair_handles(&mut self) -> Vec<(Handle, Handle)> {
+        let entity_handles = self
+            .__entities
+            .iter()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        let handles = self.item_handles_mut();
+        let max_handle = handles
+            .iter()
+            .map(|h| **h)
+            .filter(|h| Drawing::is_real_handle(*h))
+            .map(|h| h.0)
+            .max()
+            .unwrap_or(0);
+        let mut next_handle = Handle(max_handle + 1);
+        let mut seen = HashSet::new();
+        let mut repairs = vec![];
+        for handle in handles {
+            if Drawing::is_real_handle(*handle) && !seen.insert(handle.0) {
+                repairs.push((*handle, next_handle));
+                *handle = next_handle;
+                next_handle = next_handle.next_handle_value();
+            }
+        }
+        if next_handle.0 > self.header.next_available_handle.0 {
+            self.header.next_available_handle = next_handle;
         }
 
-        item.handle()
-    }
-}
+        // objects owned by a renumbered dictionary follow it
+        let mut new_owners = HashMap::new();
+        for (old_handle, new_handle) in &repairs {
+            let dictionary = self
+                .__objects
+                .iter()
+                .find(|o| o.common.handle == *new_handle);
+            let children = match dictionary.map(|o| &o.specific) {
+                Some(ObjectType::Dictionary(ref dict)) => Some(&dict.value_handles),
+                Some(ObjectType::DictionaryWithDefault(ref dict)) => Some(&dict.value_handles),
+                _ => None,
+            };
+            for child in children.into_iter().flat_map(|c| c.values()) {
+                new_owners.insert(*child, (*old_handle, *new_handle));
+            }
+        }
+        for obj in self.__objects.iter_mut() {
+            if let Some((old_owner, new_owner)) = new_owners.get(&obj.common.handle) {
+                if obj.common.__owner_handle == *old_owner {
+                    obj.common.__owner_handle = *new_owner;
+                }
+            }
+        }
 
-// private implementation
-impl Drawing {
-    pub(crate) fn next_handle(&mut self) -> Handle {
-        let result = self.header.next_available_handle;
-        self.header.next_available_handle = self.header.next_available_handle.next_handle_value();
-        result
-    }
-    fn remove_item<T>(collection: &mut Vec<T>, index: usize) -> Option<T> {
-        if index < collection.len() {
-            Some(collection.remove(index))
-        } else {
-            None
+        // an entity that lost its handle to an earlier item keeps its place in the file order
+        let kept = self
+            .__entities
+            .iter()
+            .map(|e| e.common.handle)
+            .collect::<HashSet<_>>();
+        for (entity, old_handle) in self.__entities.iter().zip(entity_handles) {
+            if entity.common.handle != old_handle && !kept.contains(&old_handle) {
+                if let Some(index) = self.__entity_read_order.remove(&old_handle) {
+                    self.__entity_read_order.insert(entity.common.handle, index);
+                }
+            }
         }
-    }
-    pub(crate) fn add_block_no_handle_set(&mut self, mut block: Block) -> &Block {
-        self.ensure_layer_is_present_for_block(&block);
-        self.ensure_line_type_is_present_for_block(&block);
-        self.ensure_block_record_is_present_for_block(&mut block);
+
+        repairs
+    }
+    /// Reassigns every handle to a compact sequential range starting at `1` and rewrites all
+    /// references to them, including owners, pointers, dictionary entries, reactors, and handles
+    /// in extension data and XData.  Duplicate handles are first fixed with `repair_handles` and
+    /// references to handles that aren't in the drawing are cleared.  Returns a map from each old
+    /// handle to its new one.
+    pub fn renumber_handles(&mut self) -> HashMap<Handle, Handle> {
+        self.repair_handles();
+        let mut renumbered = HashMap::new();
+        let mut next_handle = Handle(1);
+        for handle in self.item_handles_mut() {
+            if Drawing::is_real_handle(*handle) {
+                renumbered.insert(*handle, next_handle);
+                *handle = next_handle;
+                next_handle = next_handle.next_handle_value();
+            }
+        }
+        self.header.next_available_handle = next_handle;
+
+        // a dangling reference could otherwise alias one of the new handles
+        let remap = |handle: Handle| {
+            if Drawing::is_real_handle(handle) {
+                renumbered
+                    .get(&handle)
+                    .copied()
+                    .unwrap_or_else(Handle::empty)
+            } else {
+                handle
+            }
+        };
+        self.remap_handle_references(&remap);
+        self.__entity_read_order = std::mem::take(&mut self.__entity_read_order)
+            .into_iter()
+            .filter_map(|(handle, index)| renumbered.get(&handle).map(|h| (*h, index)))
+            .collect();
+
+        renumbered
+    }
+    fn is_real_handle(handle: Handle) -> bool {
+        !handle.is_empty() && handle != AUTO_REPLACE_HANDLE
+    }
+    fn item_handles_mut(&mut self) -> Vec<&mut Handle> {
+        let mut handles: Vec<&mut Handle> = vec![];
+        handles.extend(self.__app_ids.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__block_records.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__dim_styles.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__layers.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__line_types.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__styles.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__ucss.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__views.iter_mut().map(|i| &mut i.handle));
+        handles.extend(self.__view_ports.iter_mut().map(|i| &mut i.handle));
+        for block in self.__blocks.iter_mut() {
+            handles.push(&mut block.handle);
+            for entity in block.entities.iter_mut() {
+                Drawing::add_entity_handles(entity, &mut handles);
+            }
+        }
+        for entity in self.__entities.iter_mut() {
+            Drawing::add_entity_handles(entity, &mut handles);
+        }
+        handles.extend(self.__objects.iter_mut().map(|o| &mut o.common.handle));
+        handles
+    }
+    fn remap_handle_references(&mut self, remap: &dyn Fn(Handle) -> Handle) {
+        let header = &mut self.header;
+        for handle in [
+            &mut header.solid_visual_style_pointer,
+            &mut header.new_object_plot_style_handle,
+            &mut header.current_material_handle,
+            &mut header.interference_object_visual_style_pointer,
+            &mut header.interference_view_port_visual_style_pointer,
+        ] {
+            *handle = remap(*handle);
+        }
+
+        for t in self.__app_ids.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__block_records.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__dim_styles.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__layers.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__line_types.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__styles.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__ucss.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__views.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+        for t in self.__view_ports.iter_mut() {
+            Drawing::remap_all(t.handle_references_mut(), remap);
+            Drawing::remap_extension_data(&mut t.extension_data_groups, &mut t.x_data, remap);
+        }
+
+        for block in self.__blocks.iter_mut() {
+            block.__owner_handle = remap(block.__owner_handle);
+            Drawing::remap_extension_data(
+                &mut block.extension_data_groups,
+                &mut block.x_data,
+                remap,
+            );
+            for entity in block.entities.iter_mut() {
+                Drawing::remap_entity_handle_references(entity, remap);
+            }
+        }
+        for entity in self.__entities.iter_mut() {
+            Drawing::remap_entity_handle_references(entity, remap);
+        }
+        for obj in self.__objects.iter_mut() {
+            Drawing::remap_all(obj.common.handle_references_mut(), remap);
+            Drawing::remap_extension_data(
+                &mut obj.common.extension_data_groups,
+                &mut obj.common.x_data,
+                remap,
+            );
+            Drawing::remap_all(obj.specific.handle_references_mut(), remap);
+            match obj.specific {
+                ObjectType::SectionSettings(ref mut settings) => {
+                    for s in settings.geometry_settings.iter_mut() {
+                        Drawing::remap_all(s.source_object_handles.iter_mut().collect(), remap);
+                        s.destination_object_handle = remap(s.destination_object_handle);
+                    }
+                }
+                ObjectType::AcadProxyObject(ref mut proxy) => {
+                    for (_, handle) in proxy.object_ids.iter_mut() {
+                        Drawing::remap_handle_string(handle, remap);
+                    }
+                }
+                ObjectType::XRecordObject(ref mut xrecord) => {
+                    for pair in xrecord.data_pairs.iter_mut() {
+                        Drawing::remap_code_pair(pair, remap);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    fn remap_entity_handle_references(entity: &mut Entity, remap: &dyn Fn(Handle) -> Handle) {
+        Drawing::remap_all(entity.common.handle_references_mut(), remap);
+        Drawing::remap_extension_data(
+            &mut entity.common.extension_data_groups,
+            &mut entity.common.x_data,
+            remap,
+        );
+        Drawing::remap_all(entity.specific.handle_references_mut(), remap);
+        match entity.specific {
+            EntityType::Image(ref mut image) => {
+                Drawing::remap_handle_string(&mut image.image_def_reference, remap);
+                Drawing::remap_handle_string(&mut image.image_def_reactor_reference, remap);
+            }
+            EntityType::Insert(ref mut ins) => {
+                for (att, _) in ins.__attributes_and_handles.iter_mut() {
+                    Drawing::remap_all(att.handle_references_mut(), remap);
+                }
+            }
+            EntityType::Leader(ref mut leader) => {
+                Drawing::remap_handle_string(&mut leader.associated_annotation_reference, remap);
+            }
+            EntityType::ProxyEntity(ref mut proxy) => {
+                for (_, handle) in proxy.object_ids.iter_mut() {
+                    Drawing::remap_handle_string(handle, remap);
+                }
+            }
+            EntityType::Table(ref mut table) => {
+                for cell in table.cells.iter_mut().filter(|c| c.cell_type == 2) {
+                    cell.block_handle = remap(cell.block_handle);
+                }
+            }
+            EntityType::Wipeout(ref mut wipeout) => {
+                Drawing::remap_handle_string(&mut wipeout.image_def_reference, remap);
+                Drawing::remap_handle_string(&mut wipeout.image_def_reactor_reference, remap);
+            }
+            _ => (),
+        }
+    }
+    fn remap_all(handles: Vec<&mut Handle>, remap: &dyn Fn(Handle) -> Handle) {
+        for handle in handles {
+            *handle = remap(*handle);
+        }
+    }
+    fn remap_handle_string(handle: &mut String, remap: &dyn Fn(Handle) -> Handle) {
+        if let Ok(value) = u64::from_str_radix(handle.trim(), 16) {
+            let new_handle = remap(Handle(value));
+            *handle = if new_handle.is_empty() {
+                String::new()
+            } else {
+                new_handle.as_string()
+            };
+        }
+    }
+    fn remap_code_pair(pair: &mut CodePair, remap: &dyn Fn(Handle) -> Handle) {
+        let is_handle_code = matches!(pair.code, 320..=369 | 390..=399 | 480..=481);
+        if is_handle_code {
+            if let Ok(handle) = pair.as_handle() {
+                pair.value = CodePairValue::Str(remap(handle).as_string());
+            }
+        }
+    }
+    fn remap_extension_data(
+        groups: &mut [ExtensionGroup],
+        x_data: &mut [XData],
+        remap: &dyn Fn(Handle) -> Handle,
+    ) {
+        fn remap_group(group: &mut ExtensionGroup, remap: &dyn Fn(Handle) -> Handle) {
+            for item in group.items.iter_mut() {
+                match item {
+                    ExtensionGroupItem::CodePair(ref mut pair) => {
+                        Drawing::remap_code_pair(pair, remap)
+                    }
+                    ExtensionGroupItem::Group(ref mut group) => remap_group(group, remap),
+                }
+            }
+        }
+        fn remap_items(items: &mut [XDataItem], remap: &dyn Fn(Handle) -> Handle) {
+            for item in items.iter_mut() {
+                match item {
+                    XDataItem::Handle(ref mut handle) => *handle = remap(*handle),
+                    XDataItem::ControlGroup(ref mut items) => remap_items(items, remap),
+                    _ => (),
+                }
+            }
+        }
+        for group in groups.iter_mut() {
+            remap_group(group, remap);
+        }
+        for x in x_data.iter_mut() {
+            remap_items(&mut x.items, remap);
+        }
+    }
+    fn add_entity_handles<'a>(entity: &'a mut Entity, handles: &mut Vec<&'a mut Handle>) {
+        handles.push(&mut entity.common.handle);
+        match entity.specific {
+            EntityType::Insert(ref mut ins) => {
+                handles.extend(ins.__attributes_and_handles.iter_mut().map(|a| &mut a.1));
+                handles.push(&mut ins.__seqend_handle);
+            }
+            EntityType::Polyline(ref mut poly) => {
+                handles.extend(poly.__vertices_and_handles.iter_mut().map(|v| &mut v.1));
+                handles.push(&mut poly.__seqend_handle);
+            }
+            _ => (),
+        }
+    }
+    pub(crate) fn assign_and_get_handle(&mut self, item: &mut DrawingItemMut) -> Handle {
+        if item.handle().is_empty() {
+            item.set_handle(self.header.next_available_handle);
+            self.header.next_available_handle =
+                self.header.next_available_handle.next_handle_value();
+        }
+
+        item.handle()
+    }
+}
+
+/// Iterating a `&Drawing` yields its top-level entities; blocks, tables, and objects have their own
+/// accessors.
+impl<'a> IntoIterator for &'a Drawing {
+    type Item = &'a Entity;
+    type IntoIter = std::slice::Iter<'a, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.__entities.iter()
+    }
+}
+
+// private implementation
+impl Drawing {
+    fn scale_entity(entity: &mut Entity, m: &TransformationMatrix, factor: f64) {
+        // block contents are scaled separately, so an insert only needs to move
+        let insert_scale = match entity.specific {
+            EntityType::Insert(ref ins) => {
+                Some((ins.x_scale_factor, ins.y_scale_factor, ins.z_scale_factor))
+            }
+            _ => None,
+        };
+        entity.transform(m);
+        if let (EntityType::Insert(ref mut ins), Some((x, y, z))) =
+            (&mut entity.specific, insert_scale)
+        {
+            ins.x_scale_factor = x;
+            ins.y_scale_factor = y;
+            ins.z_scale_factor = z;
+        }
+        if let Some(thickness) = entity.specific.thickness_mut() {
+            *thickness *= factor;
+        }
+        if let EntityType::Insert(ref mut ins) = entity.specific {
+            for att in ins.attributes_mut() {
+                att.thickness *= factor;
+            }
+        }
+    }
+    fn uses_legacy_table_names(&self) -> bool {
+        self.header.version < AcadVersion::R2000 || !self.header.use_acad2000_symbol_table_naming
+    }
+    fn table_names(&self) -> Vec<String> {
+        let mut names = vec![];
+        names.extend(self.__app_ids.iter().map(|t| t.name.clone()));
+        names.extend(self.__block_records.iter().map(|t| t.name.clone()));
+        names.extend(self.__dim_styles.iter().map(|t| t.name.clone()));
+        names.extend(self.__layers.iter().map(|t| t.name.clone()));
+        names.extend(self.__line_types.iter().map(|t| t.name.clone()));
+        names.extend(self.__styles.iter().map(|t| t.name.clone()));
+        names.extend(self.__ucss.iter().map(|t| t.name.clone()));
+        names.extend(self.__views.iter().map(|t| t.name.clone()));
+        names.extend(self.__view_ports.iter().map(|t| t.name.clone()));
+        names.extend(self.__blocks.iter().map(|b| b.name.clone()));
+        names
+    }
+    fn is_legacy_table_name_char(index: usize, c: char) -> bool {
+        // anonymous block names start with `*`
+        c.is_ascii_alphanumeric() || c == '$' || c == '-' || c == '_' || (index == 0 && c == '*')
+    }
+    fn is_legacy_table_name(name: &str) -> bool {
+        name.chars().count() <= 31
+            && name
+                .chars()
+                .enumerate()
+                .all(|(i, c)| Drawing::is_legacy_table_name_char(i, c))
+    }
+    fn legacy_table_name(name: &str) -> String {
+        name.chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if Drawing::is_legacy_table_name_char(i, c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .take(31)
+            .collect()
+    }
+    fn apply_table_renames(&mut self, renames: &HashMap<String, String>) {
+        let rename = |name: &mut String| {
+            if let Some(new_name) = renames.get(name) {
+                *name = new_name.clone();
+            }
+        };
+        for t in self.__app_ids.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__block_records.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__dim_styles.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__layers.iter_mut() {
+            rename(&mut t.name);
+            rename(&mut t.line_type_name);
+        }
+        for t in self.__line_types.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__styles.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__ucss.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__views.iter_mut() {
+            rename(&mut t.name);
+        }
+        for t in self.__view_ports.iter_mut() {
+            rename(&mut t.name);
+        }
+        for block in self.__blocks.iter_mut() {
+            rename(&mut block.name);
+            rename(&mut block.layer);
+        }
+
+        for entity in self
+            .__entities
+            .iter_mut()
+            .chain(self.__blocks.iter_mut().flat_map(|b| b.entities.iter_mut()))
+        {
+            rename(&mut entity.common.layer);
+            rename(&mut entity.common.line_type_name);
+            match entity.specific {
+                EntityType::ArcAlignedText(ref mut e) => rename(&mut e.text_style_name),
+                EntityType::Attribute(ref mut e) => rename(&mut e.text_style_name),
+                EntityType::AttributeDefinition(ref mut e) => rename(&mut e.text_style_name),
+                EntityType::Insert(ref mut e) => {
+                    rename(&mut e.name);
+                    for att in e.attributes_mut() {
+                        rename(&mut att.text_style_name);
+                    }
+                }
+                EntityType::Leader(ref mut e) => rename(&mut e.dimension_style_name),
+                EntityType::MText(ref mut e) => rename(&mut e.text_style_name),
+                EntityType::Table(ref mut e) => rename(&mut e.block_name),
+                EntityType::Text(ref mut e) => rename(&mut e.text_style_name),
+                EntityType::Tolerance(ref mut e) => rename(&mut e.dimension_style_name),
+                _ => (),
+            }
+            if let Some(dimension_base) = entity.dimension_base_mut() {
+                rename(&mut dimension_base.dimension_style_name);
+                rename(&mut dimension_base.block_name);
+            }
+        }
+
+        let header = &mut self.header;
+        rename(&mut header.current_layer);
+        rename(&mut header.current_entity_line_type);
+        rename(&mut header.text_style);
+        rename(&mut header.dimension_style_name);
+    }
+    pub(crate) fn next_handle(&mut self) -> Handle {
+        let result = self.header.next_available_handle;
+        self.header.next_available_handle = self.header.next_available_handle.next_handle_value();
+        result
+    }
+    fn remove_item<T>(collection: &mut Vec<T>, index: usize) -> Option<T> {
+        if index < collection.len() {
+            Some(collection.remove(index))
+        } else {
+            None
+        }
+    }
+    pub(crate) fn add_block_no_handle_set(&mut self, mut block: Block) -> &Block {
+        self.ensure_layer_is_present_for_block(&block);
+        self.ensure_line_type_is_present_for_block(&block);
+        self.ensure_block_record_is_present_for_block(&mut block);
         self.ensure_block_entity_handles_are_set(&mut block);
         self.__blocks.push(block);
         self.__blocks.last().unwrap()
     }
-    fn add_entity_no_handle_set(&mut self, entity: Entity) -> &Entity {
-        self.ensure_mline_style_is_present_for_entity(&entity);
-        self.ensure_dimension_style_is_present_for_entity(&entity);
+    pub(crate) fn text_encoding(&self) -> &'static Encoding {
+        if self.header.version.is_utf8() {
+            encoding_rs::UTF_8
+        } else {
+            self.header
+                .code_page()
+                .map_or(encoding_rs::WINDOWS_1252, |c| c.encoding())
+        }
+    }
+    pub(crate) fn set_entity_handles(&mut self, entity: &mut Entity, replace_child_handles: bool) {
+        entity.common.handle = self.next_handle();
+
+        // set child handles
+        match entity.specific {
+            EntityType::Insert(ref mut ins) => {
+                ins.__seqend_handle = self.next_handle();
+                for a in ins.__attributes_and_handles.iter_mut() {
+                    if replace_child_handles || a.1 == AUTO_REPLACE_HANDLE {
+                        a.1 = self.next_handle();
+                    }
+                }
+            }
+            EntityType::Polyline(ref mut poly) => {
+                poly.__seqend_handle = self.next_handle();
+                for v in poly.__vertices_and_handles.iter_mut() {
+                    if replace_child_handles || v.1 == AUTO_REPLACE_HANDLE {
+                        v.1 = self.next_handle();
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    fn ensure_entity_dependencies_are_present(&mut self, entity: &Entity) {
+        self.ensure_mline_style_is_present_for_entity(entity);
+        self.ensure_dimension_style_is_present_for_entity(entity);
         self.ensure_layer_is_present(&entity.common.layer);
         self.ensure_line_type_is_present(&entity.common.line_type_name);
-        self.ensure_text_style_is_present_for_entity(&entity);
+        self.ensure_text_style_is_present_for_entity(entity);
+    }
+    fn add_entity_no_handle_set(&mut self, entity: Entity) -> &Entity {
+        self.ensure_entity_dependencies_are_present(&entity);
         self.__entities.push(entity);
         self.__entities.last().unwrap()
     }
+    fn dictionary_key_for(&self, handle: Handle) -> &str {
+        self.__objects
+            .iter()
+            .filter_map(|o| match o.specific {
+                ObjectType::Dictionary(ref dict) => dict
+                    .value_handles
+                    .iter()
+                    .find(|(_, &h)| h == handle)
+                    .map(|(key, _)| key.as_str()),
+                _ => None,
+            })
+            .next()
+            .unwrap_or("")
+    }
+    fn dictionary_mut(&mut self, handle: Handle) -> Option<&mut Dictionary> {
+        self.__objects
+            .iter_mut()
+            .filter(|o| o.common.handle == handle)
+            .find_map(|o| match o.specific {
+                ObjectType::Dictionary(ref mut dict) => Some(dict),
+                _ => None,
+            })
+    }
+    /// Returns the handle of the `ACAD_GROUP` dictionary, creating it and the root dictionary if
+    /// they aren't present.  The root dictionary is the first one in the `OBJECTS` section.
+    fn ensure_group_dictionary(&mut self) -> Handle {
+        let root_handle = match self.root_dictionary() {
+            Some(root) => root.common.handle,
+            None => {
+                let mut root = Object::new(ObjectType::Dictionary(Default::default()));
+                root.common.handle = self.next_handle();
+                let root_handle = root.common.handle;
+                self.__objects.insert(0, root);
+                root_handle
+            }
+        };
+        if let Some(&handle) = self
+            .dictionary_mut(root_handle)
+            .and_then(|root| root.value_handles.get("ACAD_GROUP"))
+        {
+            return handle;
+        }
+
+        let mut group_dictionary = Object::new(ObjectType::Dictionary(Default::default()));
+        group_dictionary.common.handle = self.next_handle();
+        group_dictionary.common.__owner_handle = root_handle;
+        let group_dictionary_handle = group_dictionary.common.handle;
+        if let Some(root) = self.dictionary_mut(root_handle) {
+            root.value_handles
+                .insert(String::from("ACAD_GROUP"), group_dictionary_handle);
+        }
+
+        self.add_object_no_handle_set(group_dictionary);
+        group_dictionary_handle
+    }
     fn add_object_no_handle_set(&mut self, obj: Object) -> &Object {
         self.ensure_layer_is_present_for_object(&obj);
         self.ensure_line_type_is_present_for_object(&obj);
@@ -738,7 +2191,7 @@ impl Drawing {
         self.__view_ports.last().unwrap()
     }
     fn ensure_app_id_is_present(&mut self, name: &str) {
-        if !self.app_ids().any(|a| a.name == *name) {
+        if !self.app_ids().any(|a| a.name.eq_ignore_ascii_case(name)) {
             self.add_app_id(AppId {
                 name: String::from(name),
                 ..Default::default()
@@ -746,7 +2199,10 @@ impl Drawing {
         }
     }
     fn ensure_block_record_is_present(&mut self, name: &str) {
-        if !self.block_records().any(|b| b.name == name) {
+        if !self
+            .block_records()
+            .any(|b| b.name.eq_ignore_ascii_case(name))
+        {
             self.add_block_record(BlockRecord {
                 name: String::from(name),
                 ..Default::default()
@@ -769,23 +2225,21 @@ impl Drawing {
     fn ensure_dimension_style_is_present_for_entity(&mut self, entity: &Entity) {
         // ensure corresponding dimension style is present
         let dim_style_name = match &entity.specific {
-            EntityType::RotatedDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
-            EntityType::RadialDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
-            EntityType::DiameterDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
-            EntityType::AngularThreePointDimension(ref d) => {
-                Some(&d.dimension_base.dimension_style_name)
-            }
-            EntityType::OrdinateDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
             EntityType::Leader(ref l) => Some(&l.dimension_style_name),
             EntityType::Tolerance(ref t) => Some(&t.dimension_style_name),
-            _ => None,
+            _ => entity
+                .dimension_base()
+                .map(|dimension_base| &dimension_base.dimension_style_name),
         };
         if let Some(dim_style_name) = dim_style_name {
             self.ensure_dimension_style_is_present(dim_style_name);
         }
     }
     fn ensure_dimension_style_is_present(&mut self, dim_style_name: &str) {
-        if !self.dim_styles().any(|d| d.name == dim_style_name) {
+        if !self
+            .dim_styles()
+            .any(|d| d.name.eq_ignore_ascii_case(dim_style_name))
+        {
             self.add_dim_style(DimStyle {
                 name: String::from(dim_style_name),
                 ..Default::default()
@@ -813,8 +2267,33 @@ impl Drawing {
             _ => (),
         }
     }
+    fn entities_and_block_entities(&self) -> impl Iterator<Item = &Entity> {
+        self.__entities
+            .iter()
+            .chain(self.__blocks.iter().flat_map(|b| b.entities.iter()))
+    }
+    fn resolve_layer_name(&mut self, layer: &str, create_layer: bool) -> DxfResult<String> {
+        match self
+            .__layers
+            .iter()
+            .find(|l| l.name.eq_ignore_ascii_case(layer))
+        {
+            Some(existing) => Ok(existing.name.clone()),
+            None if create_layer => {
+                self.ensure_layer_is_present(layer);
+                Ok(String::from(layer))
+            }
+            None => Err(DxfError::InvalidOperation(format!(
+                "layer \"{}\" doesn't exist",
+                layer
+            ))),
+        }
+    }
     fn ensure_layer_is_present(&mut self, layer_name: &str) {
-        if !self.layers().any(|l| l.name == *layer_name) {
+        if !self
+            .layers()
+            .any(|l| l.name.eq_ignore_ascii_case(layer_name))
+        {
             self.add_layer(Layer {
                 name: String::from(layer_name),
                 ..Default::default()
@@ -827,10 +2306,20 @@ impl Drawing {
         }
     }
     fn ensure_block_record_is_present_for_block(&mut self, block: &mut Block) {
-        self.add_block_record(BlockRecord {
-            name: String::from(&block.name),
-            ..Default::default()
-        });
+        let existing = self
+            .block_records()
+            .find(|r| r.name.eq_ignore_ascii_case(&block.name))
+            .map(|r| r.handle);
+        block.__owner_handle = match existing {
+            Some(handle) => handle,
+            None => {
+                self.add_block_record(BlockRecord {
+                    name: String::from(&block.name),
+                    ..Default::default()
+                })
+                .handle
+            }
+        };
     }
     fn ensure_block_entity_handles_are_set(&mut self, block: &mut Block) {
         for ent in &mut block.entities {
@@ -843,7 +2332,10 @@ impl Drawing {
         }
     }
     fn ensure_line_type_is_present(&mut self, line_type_name: &str) {
-        if !self.line_types().any(|lt| lt.name == *line_type_name) {
+        if !self
+            .line_types()
+            .any(|lt| lt.name.eq_ignore_ascii_case(line_type_name))
+        {
             self.add_line_type(LineType {
                 name: String::from(line_type_name),
                 ..Default::default()
@@ -869,7 +2361,10 @@ impl Drawing {
         }
     }
     fn ensure_text_style_is_present(&mut self, text_style_name: &str) {
-        if !self.styles().any(|s| s.name == text_style_name) {
+        if !self
+            .styles()
+            .any(|s| s.name.eq_ignore_ascii_case(text_style_name))
+        {
             self.add_style(Style {
                 name: String::from(text_style_name),
                 ..Default::default()
@@ -877,7 +2372,7 @@ impl Drawing {
         }
     }
     fn ensure_ucs_is_present(&mut self, ucs_name: &str) {
-        if !self.ucss().any(|u| u.name == ucs_name) {
+        if !self.ucss().any(|u| u.name.eq_ignore_ascii_case(ucs_name)) {
             self.add_ucs(Ucs {
                 name: String::from(ucs_name),
                 ..Default::default()
@@ -886,7 +2381,10 @@ impl Drawing {
     }
     fn ensure_view_is_present(&mut self, obj: &Object) {
         if let ObjectType::PlotSettings(ref ps) = &obj.specific {
-            if !self.views().any(|v| v.name == ps.plot_view_name) {
+            if !self
+                .views()
+                .any(|v| v.name.eq_ignore_ascii_case(&ps.plot_view_name))
+            {
                 self.add_view(View {
                     name: ps.plot_view_name.clone(),
                     ..Default::default()
@@ -895,7 +2393,7 @@ impl Drawing {
         }
     }
     fn ensure_view_port_is_present(&mut self, name: &str) {
-        if !self.view_ports().any(|v| v.name == name) {
+        if !self.view_ports().any(|v| v.name.eq_ignore_ascii_case(name)) {
             self.add_view_port(ViewPort {
                 name: String::from(name),
                 ..Default::default()
@@ -903,7 +2401,8 @@ impl Drawing {
         }
     }
     pub(crate) fn add_classes_pairs(&self, pairs: &mut Vec<CodePair>) {
-        if self.classes.is_empty() {
+        // the CLASSES section was introduced in R13
+        if self.classes.is_empty() || !self.header.version.is_r13_or_later() {
             return;
         }
 
@@ -915,10 +2414,15 @@ impl Drawing {
 
         pairs.push(CodePair::new_str(0, "ENDSEC"));
     }
-    pub(crate) fn add_tables_pairs(&self, pairs: &mut Vec<CodePair>, write_handles: bool) {
+    pub(crate) fn add_tables_pairs(
+        &self,
+        pairs: &mut Vec<CodePair>,
+        missing: &MissingTableRecords,
+        write_handles: bool,
+    ) {
         pairs.push(CodePair::new_str(0, "SECTION"));
         pairs.push(CodePair::new_str(2, "TABLES"));
-        add_table_code_pairs(self, pairs, write_handles);
+        add_table_code_pairs(self, missing, pairs, write_handles);
         pairs.push(CodePair::new_str(0, "ENDSEC"));
     }
     pub(crate) fn add_blocks_pairs(&self, pairs: &mut Vec<CodePair>, write_handles: bool) {
@@ -934,17 +2438,36 @@ impl Drawing {
 
         pairs.push(CodePair::new_str(0, "ENDSEC"));
     }
-    pub(crate) fn add_entities_pairs(&self, pairs: &mut Vec<CodePair>, write_handles: bool) {
+    pub(crate) fn add_entities_pairs(
+        &self,
+        pairs: &mut Vec<CodePair>,
+        write_handles: bool,
+        options: &SaveOptions,
+    ) {
         pairs.push(CodePair::new_str(0, "SECTION"));
         pairs.push(CodePair::new_str(2, "ENTITIES"));
-        for e in &self.__entities {
+        let mut entities = self.__entities.iter().collect::<Vec<_>>();
+        if options.preserve_order {
+            // entities that weren't read from a file keep their relative order at the end
+            entities.sort_by_key(|e| {
+                self.__entity_read_order
+                    .get(&e.common.handle)
+                    .cloned()
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        if options.sort_entities_by_layer {
+            // stable, so entities keep their relative order within a layer
+            entities.sort_by_cached_key(|e| e.common.layer.to_ascii_uppercase());
+        }
+        for e in entities {
             e.add_code_pairs(pairs, self.header.version, write_handles);
         }
 
         pairs.push(CodePair::new_str(0, "ENDSEC"));
     }
     pub(crate) fn add_objects_pairs(&self, pairs: &mut Vec<CodePair>) {
-        if self.header.version >= AcadVersion::R13 {
+        if self.header.version.is_r13_or_later() {
             pairs.push(CodePair::new_str(0, "SECTION"));
             pairs.push(CodePair::new_str(2, "OBJECTS"));
             for o in &self.__objects {
@@ -954,16 +2477,59 @@ impl Drawing {
             pairs.push(CodePair::new_str(0, "ENDSEC"));
         }
     }
+    pub(crate) fn check_supported(&self) -> DxfResult<()> {
+        let version = self.header.version;
+        let unsupported = |feature: &str| DxfError::Unsupported {
+            feature: String::from(feature),
+            version: Some(version),
+        };
+        let block_entities = self.__blocks.iter().flat_map(|b| b.entities.iter());
+        for entity in self.__entities.iter().chain(block_entities) {
+            if !entity.specific.is_supported_on_version(version) {
+                return Err(unsupported(entity.specific.to_type_string()));
+            }
+        }
+        if version.is_r13_or_later() {
+            for obj in &self.__objects {
+                if !obj.specific.is_supported_on_version(version) {
+                    return Err(unsupported(obj.specific.to_type_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+    /// Writes the header with `handle_seed` as `$HANDSEED`.
+    pub(crate) fn add_header_pairs(
+        &self,
+        pairs: &mut Vec<CodePair>,
+        handle_seed: Handle,
+    ) -> DxfResult<()> {
+        match self.thumbnail {
+            // versions without a THUMBNAILIMAGE section keep the preview in the header
+            Some(ref img) if self.header.version < AcadVersion::R2000 => {
+                let mut header = self.header.clone();
+                header.__preview_image_data = thumbnail::thumbnail_bitmap_data(img)?;
+                header.next_available_handle = handle_seed;
+                header.add_code_pairs(pairs);
+            }
+            _ if handle_seed != self.header.next_available_handle => {
+                let mut header = self.header.clone();
+                header.next_available_handle = handle_seed;
+                header.add_code_pairs(pairs);
+            }
+            _ => self.header.add_code_pairs(pairs),
+        }
+        Ok(())
+    }
     pub(crate) fn add_thumbnail_pairs(&self, pairs: &mut Vec<CodePair>) -> DxfResult<()> {
         if self.header.version >= AcadVersion::R2000 {
             if let Some(ref img) = self.thumbnail {
                 pairs.push(CodePair::new_str(0, "SECTION"));
                 pairs.push(CodePair::new_str(2, "THUMBNAILIMAGE"));
-                let mut data = vec![];
-                img.write_to(&mut Cursor::new(&mut data), image::ImageFormat::Bmp)?;
-                let length = data.len() - 14; // skip 14 byte bmp header
-                pairs.push(CodePair::new_i32(90, length as i32));
-                for s in data[14..].chunks(128) {
+                let data = thumbnail::thumbnail_bitmap_data(img)?;
+                pairs.push(CodePair::new_i32(90, data.len() as i32));
+                for s in data.chunks(128) {
                     let pair = CodePair::new_binary(310, s.to_vec());
                     pairs.push(pair);
                 }
@@ -987,7 +2553,15 @@ impl Drawing {
                             ..
                         })) => {
                             match &*s {
-                                "HEADER" => drawing.header = Header::read(iter)?,
+                                "HEADER" => {
+                                    drawing.header = Header::read(iter)?;
+                                    let data =
+                                        std::mem::take(&mut drawing.header.__preview_image_data);
+                                    if !data.is_empty() {
+                                        drawing.thumbnail =
+                                            thumbnail::thumbnail_from_bitmap_data(&data)?;
+                                    }
+                                }
                                 "CLASSES" => Class::read_classes(drawing, iter)?,
                                 "TABLES" => {
                                     drawing.read_section_item(iter, "TABLE", read_specific_table)?
@@ -1068,18 +2642,30 @@ impl Drawing {
         let mut iter = EntityIter { iter };
         let mut entities = vec![];
         iter.read_entities_into_vec(&mut entities)?;
-        for e in entities {
+        for (index, mut e) in entities.into_iter().enumerate() {
             if e.common.handle.is_empty() {
-                self.add_entity(e);
-            } else {
-                self.add_entity_no_handle_set(e);
+                self.set_entity_handles(&mut e, false);
             }
+            self.__entity_read_order
+                .entry(e.common.handle)
+                .or_insert(index);
+            self.add_entity_no_handle_set(e);
         }
         Ok(())
     }
     fn read_objects(&mut self, iter: &mut CodePairPutBack) -> DxfResult<()> {
+        // any objects present at this point were created as placeholders while reading entities
+        let mut placeholder_count = self.__objects.len();
         let iter = put_back(ObjectIter { iter });
         for o in iter {
+            if let ObjectType::MLineStyle(ref style) = o.specific {
+                if let Some(index) = self.__objects[..placeholder_count].iter().position(|p| {
+                    matches!(p.specific, ObjectType::MLineStyle(ref s) if s.style_name == style.style_name)
+                }) {
+                    self.__objects.remove(index);
+                    placeholder_count -= 1;
+                }
+            }
             if o.common.handle.is_empty() {
                 self.add_object(o);
             } else {
@@ -1164,15 +2750,15 @@ impl Drawing {
     }
     fn normalize_app_ids(&mut self) {
         // ensure all app ids that should exist do
-        self.ensure_app_id_is_present("ACAD");
-        self.ensure_app_id_is_present("ACADANNOTATIVE");
-        self.ensure_app_id_is_present("ACAD_NAV_VCDISPLAY");
-        self.ensure_app_id_is_present("ACAD_MLEADERVER");
+        for name in MANDATORY_APP_IDS {
+            self.ensure_app_id_is_present(name);
+        }
     }
     fn normalize_block_records(&mut self) {
         // ensure all block records that should exist do
-        self.ensure_block_record_is_present("*MODEL_SPACE");
-        self.ensure_block_record_is_present("*PAPER_SPACE");
+        for name in MANDATORY_BLOCK_RECORDS {
+            self.ensure_block_record_is_present(name);
+        }
     }
     fn normalize_layers(&mut self) {
         self.ensure_layer_is_present(&self.header.current_layer.clone());
@@ -1197,8 +2783,9 @@ impl Drawing {
     }
     fn ensure_dimension_styles(&mut self) {
         // ensure all dimension styles that should exist do
-        self.ensure_dimension_style_is_present("STANDARD");
-        self.ensure_dimension_style_is_present("ANNOTATIVE");
+        for name in MANDATORY_DIM_STYLES {
+            self.ensure_dimension_style_is_present(name);
+        }
     }
     fn ensure_layers(&mut self) {
         // ensure all layers that should exist do
@@ -1206,44 +2793,202 @@ impl Drawing {
     }
     fn ensure_line_types(&mut self) {
         // ensure all line_types that should exist do
-        self.ensure_line_type_is_present("BYLAYER");
-        self.ensure_line_type_is_present("BYBLOCK");
-        self.ensure_line_type_is_present("CONTINUOUS");
+        for name in MANDATORY_LINE_TYPES {
+            self.ensure_line_type_is_present(name);
+        }
     }
     fn ensure_text_styles(&mut self) {
         // ensure all styles that should exist do
-        self.ensure_text_style_is_present("STANDARD");
-        self.ensure_text_style_is_present("ANNOTATIVE");
+        for name in MANDATORY_TEXT_STYLES {
+            self.ensure_text_style_is_present(name);
+        }
     }
     fn ensure_view_ports(&mut self) {
         // ensure all view ports that should exist do
-        self.ensure_view_port_is_present("*ACTIVE");
+        for name in MANDATORY_VIEW_PORTS {
+            self.ensure_view_port_is_present(name);
+        }
     }
     fn ensure_ucs(&mut self) {
         // ensure all ucs that should exist do
-        let mut should_exist = HashSet::new();
-        should_exist.insert(self.header.ucs_definition_name.clone());
-        should_exist.insert(self.header.ucs_name.clone());
-        should_exist.insert(self.header.ortho_ucs_reference.clone());
-        should_exist.insert(self.header.paperspace_ucs_definition_name.clone());
-        should_exist.insert(self.header.paperspace_ucs_name.clone());
-        should_exist.insert(self.header.paperspace_ortho_ucs_reference.clone());
-
+        let should_exist = self
+            .header_ucs_names()
+            .iter()
+            .map(|n| String::from(*n))
+            .collect::<Vec<_>>();
         for name in &should_exist {
-            if !name.is_empty() {
-                self.ensure_ucs_is_present(name);
+            self.ensure_ucs_is_present(name);
+        }
+    }
+    /// Returns the non-empty UCS names referenced by the header, without duplicates ignoring case.
+    fn header_ucs_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        for name in [
+            &self.header.ucs_definition_name,
+            &self.header.ucs_name,
+            &self.header.ortho_ucs_reference,
+            &self.header.paperspace_ucs_definition_name,
+            &self.header.paperspace_ucs_name,
+            &self.header.paperspace_ortho_ucs_reference,
+        ] {
+            if !name.is_empty() && !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                names.push(name);
+            }
+        }
+        names
+    }
+    /// Returns the mandatory table records the drawing doesn't have, with handles starting at
+    /// `$HANDSEED`.  Names are matched case-insensitively, e.g., `Continuous` satisfies
+    /// `CONTINUOUS`.
+    pub(crate) fn missing_table_records(&self) -> MissingTableRecords {
+        fn missing<'a>(
+            existing: impl Iterator<Item = &'a String> + Clone,
+            mandatory: &[&'a str],
+        ) -> Vec<String> {
+            let mut names: Vec<String> = vec![];
+            for name in mandatory {
+                if !name.is_empty()
+                    && !existing.clone().any(|e| e.eq_ignore_ascii_case(name))
+                    && !names.iter().any(|n| n.eq_ignore_ascii_case(name))
+                {
+                    names.push(String::from(*name));
+                }
             }
+            names
+        }
+
+        let mut handle = self.header.next_available_handle;
+        let mut next_handle = || {
+            let result = handle;
+            handle = handle.next_handle_value();
+            result
+        };
+        let layers = ["0", self.header.current_layer.as_str()];
+        MissingTableRecords {
+            app_ids: missing(self.__app_ids.iter().map(|i| &i.name), &MANDATORY_APP_IDS)
+                .into_iter()
+                .map(|name| AppId {
+                    name,
+                    handle: next_handle(),
+                    ..Default::default()
+                })
+                .collect(),
+            block_records: missing(
+                self.__block_records.iter().map(|i| &i.name),
+                &MANDATORY_BLOCK_RECORDS,
+            )
+            .into_iter()
+            .map(|name| BlockRecord {
+                name,
+                handle: next_handle(),
+                ..Default::default()
+            })
+            .collect(),
+            dim_styles: missing(
+                self.__dim_styles.iter().map(|i| &i.name),
+                &MANDATORY_DIM_STYLES,
+            )
+            .into_iter()
+            .map(|name| DimStyle {
+                name,
+                handle: next_handle(),
+                ..Default::default()
+            })
+            .collect(),
+            layers: missing(self.__layers.iter().map(|i| &i.name), &layers)
+                .into_iter()
+                .map(|name| Layer {
+                    name,
+                    handle: next_handle(),
+                    ..Default::default()
+                })
+                .collect(),
+            line_types: missing(
+                self.__line_types.iter().map(|i| &i.name),
+                &MANDATORY_LINE_TYPES,
+            )
+            .into_iter()
+            .map(|name| LineType {
+                name,
+                handle: next_handle(),
+                ..Default::default()
+            })
+            .collect(),
+            styles: missing(
+                self.__styles.iter().map(|i| &i.name),
+                &MANDATORY_TEXT_STYLES,
+            )
+            .into_iter()
+            .map(|name| Style {
+                name,
+                handle: next_handle(),
+                ..Default::default()
+            })
+            .collect(),
+            ucss: missing(
+                self.__ucss.iter().map(|i| &i.name),
+                &self.header_ucs_names(),
+            )
+            .into_iter()
+            .map(|name| Ucs {
+                name,
+                handle: next_handle(),
+                ..Default::default()
+            })
+            .collect(),
+            views: vec![],
+            view_ports: missing(
+                self.__view_ports.iter().map(|i| &i.name),
+                &MANDATORY_VIEW_PORTS,
+            )
+            .into_iter()
+            .map(|name| ViewPort {
+                name,
+                handle: next_handle(),
+                ..Default::default()
+            })
+            .collect(),
         }
     }
 }
 
+/// Mandatory table records that a `Drawing` doesn't have.  These are written when the drawing
+/// is saved without being added to it.
+#[derive(Default)]
+pub(crate) struct MissingTableRecords {
+    pub app_ids: Vec<AppId>,
+    pub block_records: Vec<BlockRecord>,
+    pub dim_styles: Vec<DimStyle>,
+    pub layers: Vec<Layer>,
+    pub line_types: Vec<LineType>,
+    pub styles: Vec<Style>,
+    pub ucss: Vec<Ucs>,
+    pub views: Vec<View>,
+    pub view_ports: Vec<ViewPort>,
+}
+
+impl MissingTableRecords {
+    pub(crate) fn len(&self) -> usize {
+        self.app_ids.len()
+            + self.block_records.len()
+            + self.dim_styles.len()
+            + self.layers.len()
+            + self.line_types.len()
+            + self.styles.len()
+            + self.ucss.len()
+            + self.views.len()
+            + self.view_ports.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::entities::*;
-    use crate::enums::AcadVersion;
+    use crate::enums::{AcadVersion, Units};
     use crate::helper_functions::tests::*;
     use crate::objects::*;
     use crate::tables::*;
+    use crate::DxfSection;
     use crate::*;
 
     #[test]
@@ -2097,4 +3842,1774 @@ mod tests {
         let views = drawing.views().filter(|&v| v.name == "some-view");
         assert_eq!(1, views.count());
     }
+
+    #[test]
+    fn explode_insert_array() {
+        let mut drawing = Drawing::new();
+        let mut block = Block::new("block-name", Point::new(1.0, 0.0, 0.0));
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(2.0, 0.0, 0.0),
+        ))));
+        drawing.add_block(block);
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert {
+            name: String::from("block-name"),
+            location: Point::new(10.0, 0.0, 0.0),
+            column_count: 2,
+            column_spacing: 5.0,
+            row_count: 2,
+            row_spacing: 3.0,
+            ..Default::default()
+        })));
+
+        assert!(drawing.explode_insert(0));
+        let lines = drawing
+            .entities()
+            .map(|e| match e.specific {
+                EntityType::Line(ref line) => (line.p1.clone(), line.p2.clone()),
+                _ => panic!("expected a line"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Point::new(10.0, 0.0, 0.0), Point::new(11.0, 0.0, 0.0)),
+                (Point::new(15.0, 0.0, 0.0), Point::new(16.0, 0.0, 0.0)),
+                (Point::new(10.0, 3.0, 0.0), Point::new(11.0, 3.0, 0.0)),
+                (Point::new(15.0, 3.0, 0.0), Point::new(16.0, 3.0, 0.0)),
+            ],
+            lines
+        );
+
+        let handles = drawing
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(4, handles.len());
+    }
+
+    #[test]
+    fn explode_insert_with_rotation_and_scale() {
+        let mut drawing = Drawing::new();
+        let mut block = Block::new("block-name", Point::origin());
+        block
+            .entities
+            .push(Entity::new(EntityType::Circle(Circle::new(
+                Point::new(1.0, 0.0, 0.0),
+                1.0,
+            ))));
+        drawing.add_block(block);
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert {
+            name: String::from("block-name"),
+            rotation: 90.0,
+            x_scale_factor: 2.0,
+            y_scale_factor: 2.0,
+            z_scale_factor: 2.0,
+            ..Default::default()
+        })));
+
+        assert!(drawing.explode_insert(1));
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(2, entities.len());
+        match entities[1].specific {
+            EntityType::Circle(ref circle) => {
+                assert!(approx_eq!(f64, 0.0, circle.center.x, epsilon = 1e-10));
+                assert!(approx_eq!(f64, 2.0, circle.center.y, epsilon = 1e-10));
+                assert!(approx_eq!(f64, 2.0, circle.radius));
+            }
+            _ => panic!("expected a circle"),
+        }
+    }
+
+    #[test]
+    fn explode_insert_with_extrusion_direction() {
+        let mut drawing = Drawing::new();
+        let mut block = Block::new("block-name", Point::origin());
+        block.entities.push(Entity::new(EntityType::Line(Line::new(
+            Point::origin(),
+            Point::new(1.0, 0.0, 0.0),
+        ))));
+        drawing.add_block(block);
+        // the block name is matched ignoring case and the insert's OCS X axis is the world -X axis
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert {
+            name: String::from("Block-Name"),
+            location: Point::new(2.0, 0.0, 0.0),
+            extrusion_direction: Vector::new(0.0, 0.0, -1.0),
+            ..Default::default()
+        })));
+
+        assert!(drawing.explode_insert(0));
+        let lines = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, lines.len());
+        match lines[0].specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(-2.0, 0.0, 0.0), line.p1);
+                assert_eq!(Point::new(-3.0, 0.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn explode_non_insert_does_nothing() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        assert!(!drawing.explode_insert(0));
+        assert!(!drawing.explode_insert(1));
+        assert_eq!(1, drawing.entities().count());
+    }
+
+    #[test]
+    fn add_entity_with_current_settings_applies_current_layer_and_color() {
+        let mut drawing = Drawing::new();
+        drawing.set_current_layer("current-layer");
+        drawing.set_current_color(Color::from_index(3));
+        assert!(drawing.layers().any(|l| l.name == "current-layer"));
+
+        let defaulted = drawing
+            .add_entity_with_current_settings(Entity::new(EntityType::Line(Line::default())));
+        assert_eq!("current-layer", defaulted.common.layer);
+        assert_eq!(Color::from_index(3), defaulted.common.color);
+
+        let mut explicit = Entity::new(EntityType::Line(Line::default()));
+        explicit.common.layer = String::from("explicit-layer");
+        explicit.common.color = Color::from_index(5);
+        let explicit = drawing.add_entity_with_current_settings(explicit);
+        assert_eq!("explicit-layer", explicit.common.layer);
+        assert_eq!(Color::from_index(5), explicit.common.color);
+    }
+
+    #[test]
+    fn add_entity_does_not_apply_current_settings() {
+        let mut drawing = Drawing::new();
+        drawing.set_current_layer("current-layer");
+        drawing.set_current_color(Color::from_index(3));
+        let entity = drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        assert_eq!("0", entity.common.layer);
+        assert_eq!(Color::by_layer(), entity.common.color);
+    }
+
+    #[test]
+    fn current_layer_is_not_applied_when_reading() {
+        let drawing = drawing_from_pairs(vec![
+            CodePair::new_str(0, "SECTION"),
+            CodePair::new_str(2, "HEADER"),
+            CodePair::new_str(9, "$CLAYER"),
+            CodePair::new_str(8, "current-layer"),
+            CodePair::new_str(0, "ENDSEC"),
+            CodePair::new_str(0, "SECTION"),
+            CodePair::new_str(2, "ENTITIES"),
+            CodePair::new_str(0, "LINE"),
+            CodePair::new_str(8, "0"),
+            CodePair::new_str(0, "ENDSEC"),
+            CodePair::new_str(0, "EOF"),
+        ]);
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        assert_eq!("0", entities[0].common.layer);
+        assert!(!entities[0].common.handle.is_empty());
+    }
+
+    fn save_to_string(drawing: &Drawing, options: &SaveOptions) -> String {
+        let mut buf = std::io::Cursor::new(vec![]);
+        drawing.save_with_options(&mut buf, options).unwrap();
+        String::from_utf8(buf.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn minimum_required_version_comes_from_entities() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        assert!(drawing.minimum_required_version() < AcadVersion::R14);
+        drawing.add_entity(Entity::new(EntityType::Wipeout(Wipeout::default())));
+        assert_eq!(AcadVersion::R2000, drawing.minimum_required_version());
+    }
+
+    #[test]
+    fn version_is_not_bumped_by_default() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        drawing.add_entity(Entity::new(EntityType::Wipeout(Wipeout::default())));
+        let contents = save_to_string(&drawing, &SaveOptions::default());
+        assert!(contents.contains("$ACADVER\r\n  1\r\nAC1009\r\n"));
+        assert!(!contents.contains("WIPEOUT"));
+    }
+
+    #[test]
+    fn version_is_bumped_to_minimum_required() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        drawing.add_entity(Entity::new(EntityType::Wipeout(Wipeout::default())));
+        let mut options = SaveOptions::new();
+        options.bump_version_to_minimum_required = true;
+        let contents = save_to_string(&drawing, &options);
+        assert!(contents.contains("$ACADVER\r\n  1\r\nAC1015\r\n"));
+        assert!(contents.contains("WIPEOUT"));
+        assert_eq!(AcadVersion::R12, drawing.header.version);
+    }
+
+    #[test]
+    fn version_is_never_lowered_when_bumping() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        drawing.add_entity(Entity::new(EntityType::Wipeout(Wipeout::default())));
+        let mut options = SaveOptions::new();
+        options.bump_version_to_minimum_required = true;
+        let contents = save_to_string(&drawing, &options);
+        assert!(contents.contains("$ACADVER\r\n  1\r\nAC1032\r\n"));
+    }
+
+    #[test]
+    fn iterate_drawing_reference_yields_entities() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::default())));
+        let mut type_names = vec![];
+        for entity in &drawing {
+            type_names.push(match entity.specific {
+                EntityType::Line(_) => "LINE",
+                EntityType::Circle(_) => "CIRCLE",
+                _ => "OTHER",
+            });
+        }
+        assert_eq!(vec!["LINE", "CIRCLE"], type_names);
+    }
+
+    #[test]
+    fn scale_drawing_scales_radii_and_text_heights() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::new(1.0, 2.0, 0.0),
+            3.0,
+        ))));
+        let text = Text {
+            text_height: 0.5,
+            thickness: 0.25,
+            ..Default::default()
+        };
+        let mut text = Entity::new(EntityType::Text(text));
+        text.common.line_type_scale = 3.0;
+        drawing.add_entity(text);
+        drawing.scale(2.0);
+
+        let entities = drawing.entities().collect::<Vec<_>>();
+        match entities[0].specific {
+            EntityType::Circle(ref circle) => {
+                assert_eq!(Point::new(2.0, 4.0, 0.0), circle.center);
+                assert!(approx_eq!(f64, 6.0, circle.radius));
+            }
+            _ => panic!("expected a circle"),
+        }
+        match entities[1].specific {
+            EntityType::Text(ref text) => {
+                assert!(approx_eq!(f64, 1.0, text.text_height));
+                assert!(approx_eq!(f64, 0.5, text.thickness));
+            }
+            _ => panic!("expected text"),
+        }
+        assert!(approx_eq!(f64, 3.0, entities[1].common.line_type_scale));
+        assert!(approx_eq!(f64, 2.0, drawing.header.line_type_scale));
+    }
+
+    #[test]
+    fn flatten_drawing_to_2d() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 2.0, 3.0),
+            Point::new(4.0, 5.0, 6.0),
+        ))));
+        drawing.add_entity(Entity::new(EntityType::Solid3D(Solid3D::default())));
+        assert_eq!(1, drawing.flatten_to_2d(false));
+        assert_eq!(2, drawing.entities().count());
+
+        assert_eq!(1, drawing.flatten_to_2d(true));
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(1.0, 2.0, 0.0), line.p1);
+                assert_eq!(Point::new(4.0, 5.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn scale_drawing_moves_inserts_without_rescaling_them() {
+        let mut drawing = Drawing::new();
+        let mut block = Block::new("b", Point::new(1.0, 0.0, 0.0));
+        block
+            .entities
+            .push(Entity::new(EntityType::Circle(Circle::new(
+                Point::origin(),
+                1.0,
+            ))));
+        drawing.add_block(block);
+        let insert = Insert {
+            name: String::from("b"),
+            location: Point::new(5.0, 0.0, 0.0),
+            x_scale_factor: 3.0,
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Insert(insert)));
+        drawing.scale(10.0);
+
+        let block = drawing.blocks().find(|b| b.name == "b").unwrap();
+        assert_eq!(Point::new(10.0, 0.0, 0.0), block.base_point);
+        match block.entities[0].specific {
+            EntityType::Circle(ref circle) => assert!(approx_eq!(f64, 10.0, circle.radius)),
+            _ => panic!("expected a circle"),
+        }
+        let entity = drawing.entities().next().unwrap();
+        match entity.specific {
+            EntityType::Insert(ref insert) => {
+                assert_eq!(Point::new(50.0, 0.0, 0.0), insert.location);
+                assert!(approx_eq!(f64, 3.0, insert.x_scale_factor));
+            }
+            _ => panic!("expected an insert"),
+        }
+    }
+
+    #[test]
+    fn convert_units_from_millimeters_to_inches() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::origin(),
+            25.4,
+        ))));
+        drawing.convert_units(Units::Millimeters, Units::Inches);
+        assert_eq!(Units::Inches, drawing.header.default_drawing_units);
+        let entity = drawing.entities().next().unwrap();
+        match entity.specific {
+            EntityType::Circle(ref circle) => {
+                assert!(approx_eq!(f64, 1.0, circle.radius, epsilon = 1e-12))
+            }
+            _ => panic!("expected a circle"),
+        }
+    }
+
+    #[test]
+    fn clone_layer_properties_to_by_layer_entities() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("red-layer"),
+            color: Color::from_index(1),
+            line_type_name: String::from("DASHED"),
+            ..Default::default()
+        });
+        // layer names are matched ignoring case
+        let mut by_layer = Entity::new(EntityType::Line(Line::default()));
+        by_layer.common.layer = String::from("Red-Layer");
+        drawing.add_entity(by_layer);
+        let mut explicit = Entity::new(EntityType::Line(Line::default()));
+        explicit.common.layer = String::from("red-layer");
+        explicit.common.color = Color::from_index(3);
+        explicit.common.line_type_name = String::from("CONTINUOUS");
+        drawing.add_entity(explicit);
+
+        drawing.clone_layer_properties_to_entities();
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(Color::from_index(1), entities[0].common.color);
+        assert_eq!("DASHED", entities[0].common.line_type_name);
+        assert_eq!(Color::from_index(3), entities[1].common.color);
+        assert_eq!("CONTINUOUS", entities[1].common.line_type_name);
+    }
+
+    #[test]
+    fn round_trip_named_group() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let line = drawing
+            .add_entity(Entity::new(EntityType::Line(Line::default())))
+            .clone();
+        let circle = drawing
+            .add_entity(Entity::new(EntityType::Circle(Circle::default())))
+            .clone();
+        let mut group = Group {
+            description: String::from("two entities"),
+            is_selectable: false,
+            ..Default::default()
+        };
+        group.add_entities(&line);
+        group.add_entities(&circle);
+        drawing.add_group("my-group", group);
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let groups = reparsed.groups().collect::<Vec<_>>();
+        assert_eq!(1, groups.len());
+        let (name, group) = groups[0];
+        assert_eq!("my-group", name);
+        assert_eq!("two entities", group.description);
+        assert!(!group.is_selectable);
+        let entities = group.entities(&reparsed);
+        assert_eq!(2, entities.len());
+        assert_eq!(line.common.handle, entities[0].common.handle);
+        assert_eq!(circle.common.handle, entities[1].common.handle);
+    }
+
+    #[test]
+    fn add_group_reuses_group_dictionary() {
+        let mut drawing = Drawing::new();
+        drawing.add_group("first", Group::default());
+        drawing.add_group("second", Group::default());
+        let dictionary_count = drawing
+            .objects()
+            .filter(|o| matches!(o.specific, ObjectType::Dictionary(_)))
+            .count();
+        assert_eq!(2, dictionary_count); // root and ACAD_GROUP
+        let names = drawing.groups().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(vec!["first", "second"], names);
+    }
+
+    #[test]
+    fn add_group_uses_root_dictionary() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        let xrecord = drawing
+            .add_object(Object::new(ObjectType::XRecordObject(Default::default())))
+            .handle();
+        let mut owned = Object::new(ObjectType::Dictionary(Default::default()));
+        owned.set_owner_handle(xrecord);
+        drawing.add_object(owned);
+        let root = drawing
+            .add_object(Object::new(ObjectType::Dictionary(Default::default())))
+            .handle();
+        drawing.add_group("first", Group::default());
+        let root_dictionary = drawing.root_dictionary().unwrap();
+        assert_eq!(root, root_dictionary.handle());
+        let group_dictionary = root_dictionary
+            .as_dictionary()
+            .unwrap()
+            .get("ACAD_GROUP")
+            .unwrap();
+        let group_dictionary = drawing
+            .objects()
+            .find(|o| o.handle() == group_dictionary)
+            .unwrap();
+        assert_eq!(root, group_dictionary.owner_handle());
+    }
+
+    #[test]
+    fn load_from_bytes() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(1, reparsed.entities().count());
+    }
+
+    #[test]
+    fn save_only_entities_section() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let options = SaveOptions {
+            sections: Some(vec![DxfSection::Entities]),
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let contents = String::from_utf8(buf).unwrap();
+        assert!(contents.contains("HEADER"));
+        assert!(contents.contains("ENTITIES"));
+        assert!(!contents.contains("TABLES"));
+        assert!(!contents.contains("OBJECTS"));
+        assert!(contents.trim_end().ends_with("EOF"));
+
+        let reparsed = Drawing::load_from_bytes(contents.as_bytes()).unwrap();
+        assert_eq!(1, reparsed.entities().count());
+    }
+
+    #[test]
+    fn save_with_preserve_order_restores_file_order() {
+        let mut drawing = Drawing::new();
+        for x in 1..=3 {
+            drawing.add_entity(Entity::new_line(
+                Point::new(x as f64, 0.0, 0.0),
+                Point::new(x as f64, 1.0, 0.0),
+            ));
+        }
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let mut drawing = Drawing::load_from_bytes(&buf).unwrap();
+        {
+            let mut entities = drawing.entities_mut().collect::<Vec<_>>();
+            let (first, rest) = entities.split_at_mut(1);
+            std::mem::swap(first[0], rest[1]);
+        }
+        let line_xs = |d: &Drawing| {
+            d.entities()
+                .map(|e| match e.specific {
+                    EntityType::Line(ref line) => line.p1.x,
+                    _ => panic!("expected a line"),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![3.0, 2.0, 1.0], line_xs(&drawing));
+
+        let options = SaveOptions {
+            preserve_order: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(vec![1.0, 2.0, 3.0], line_xs(&reparsed));
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(vec![3.0, 2.0, 1.0], line_xs(&reparsed));
+    }
+
+    #[test]
+    fn entities_in_rect() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new_line(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 1.0, 0.0),
+        ));
+        drawing.add_entity(Entity::new_circle(Point::new(10.0, 10.0, 0.0), 2.0));
+        drawing.add_entity(Entity::new_line(
+            Point::new(20.0, 0.0, 0.0),
+            Point::new(30.0, 0.0, 0.0),
+        ));
+        let found = drawing.entities_in_rect(Point::new(8.5, 8.5, 0.0), Point::new(0.5, 0.5, 0.0));
+        assert_eq!(2, found.len());
+        assert!(matches!(found[0].specific, EntityType::Line(_)));
+        assert!(matches!(found[1].specific, EntityType::Circle(_)));
+        assert!(drawing
+            .entities_in_rect(Point::new(13.0, 3.0, 0.0), Point::new(19.0, 5.0, 0.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn long_layer_name_is_truncated_for_r12() {
+        let long_name = "a-very-long-layer-name-that-is-40-chars!";
+        assert_eq!(40, long_name.len());
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        drawing.add_layer(Layer {
+            name: String::from(long_name),
+            ..Default::default()
+        });
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.layer = String::from(long_name);
+        drawing.add_entity(line);
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        let expected = "a-very-long-layer-name-that-is-";
+        assert!(reparsed.layers().any(|l| l.name == expected));
+        assert_eq!(expected, reparsed.entities().next().unwrap().common.layer);
+
+        // the drawing itself is only changed when asked
+        assert!(drawing.layers().any(|l| l.name == long_name));
+        let renames = drawing.sanitize_table_names();
+        assert_eq!(1, renames.len());
+        assert_eq!(expected, renames[long_name]);
+        assert_eq!(expected, drawing.entities().next().unwrap().common.layer);
+    }
+
+    #[test]
+    fn sanitized_table_names_stay_unique() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        drawing.header.use_acad2000_symbol_table_naming = false;
+        drawing.add_layer(Layer {
+            name: String::from("layer one"),
+            ..Default::default()
+        });
+        drawing.add_layer(Layer {
+            name: String::from("layer_one"),
+            ..Default::default()
+        });
+        let renames = drawing.sanitize_table_names();
+        assert_eq!("layer_one_1", renames["layer one"]);
+
+        drawing.header.use_acad2000_symbol_table_naming = true;
+        drawing.add_layer(Layer {
+            name: String::from("layer two"),
+            ..Default::default()
+        });
+        assert!(drawing.sanitize_table_names().is_empty());
+    }
+
+    #[test]
+    fn copy_entity_assigns_new_handles() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline::default();
+        poly.add_vertex(&mut drawing, Vertex::new(Point::new(1.0, 0.0, 0.0)));
+        poly.add_vertex(&mut drawing, Vertex::new(Point::new(2.0, 0.0, 0.0)));
+        let original = drawing
+            .add_entity(Entity::new(EntityType::Polyline(poly)))
+            .common
+            .handle;
+
+        let copy = drawing.copy_entity(original).unwrap();
+        assert_ne!(original, copy);
+        assert_eq!(2, drawing.entities().count());
+        let mut handles = std::collections::HashSet::new();
+        for entity in drawing.entities() {
+            assert!(handles.insert(entity.common.handle));
+            if let EntityType::Polyline(ref poly) = entity.specific {
+                assert_eq!(2, poly.vertices().count());
+                for (_, vertex_handle) in poly.__vertices_and_handles.iter() {
+                    assert!(handles.insert(*vertex_handle));
+                }
+                assert!(handles.insert(poly.__seqend_handle));
+            }
+        }
+
+        assert_eq!(None, drawing.copy_entity(Handle(0xFFFF)));
+    }
+
+    #[test]
+    fn round_trip_group_referencing_rtext() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2013;
+        drawing.classes.push(Class {
+            record_name: String::from("RTEXT"),
+            class_name: String::from("RText"),
+            application_name: String::from("AcadExpressTools"),
+            is_entity: true,
+            ..Default::default()
+        });
+        let rtext = drawing
+            .add_entity(Entity::new(EntityType::RText(RText::default())))
+            .clone();
+        let mut group = Group::default();
+        group.add_entities(&rtext);
+        drawing.add_group("express", group);
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert!(reparsed.class_for("RTEXT").is_some());
+        let entity = reparsed.entities().next().unwrap();
+        assert!(matches!(entity.specific, EntityType::RText(_)));
+        assert_eq!(rtext.common.handle, entity.common.handle);
+        assert_eq!(rtext.common.__owner_handle, entity.common.__owner_handle);
+        let (_, group) = reparsed.groups().next().unwrap();
+        let entities = group.entities(&reparsed);
+        assert_eq!(1, entities.len());
+        assert_eq!(rtext.common.handle, entities[0].common.handle);
+    }
+
+    struct FlushTrackingWriter {
+        buf: Vec<u8>,
+        flush_count: usize,
+        fail_flush: bool,
+    }
+
+    impl std::io::Write for FlushTrackingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.buf.write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flush_count += 1;
+            if self.fail_flush {
+                Err(std::io::Error::other("flush failed"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn save_flushes_writer() {
+        let drawing = Drawing::new();
+        let mut writer = FlushTrackingWriter {
+            buf: vec![],
+            flush_count: 0,
+            fail_flush: false,
+        };
+        drawing.save(&mut writer).unwrap();
+        assert_eq!(1, writer.flush_count);
+        drawing.save_binary(&mut writer).unwrap();
+        assert_eq!(2, writer.flush_count);
+        drawing.save_dxb(&mut writer).unwrap();
+        assert_eq!(3, writer.flush_count);
+    }
+
+    #[test]
+    fn save_reports_flush_error() {
+        let drawing = Drawing::new();
+        let mut writer = FlushTrackingWriter {
+            buf: vec![],
+            flush_count: 0,
+            fail_flush: true,
+        };
+        match drawing.save(&mut writer) {
+            Err(DxfError::IoError(_)) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn load_headerless_file() {
+        let contents = vec![
+            "  0", "SECTION", "  2", "ENTITIES", "  0", "LINE", "  8", "0", " 10", "1.0", " 20",
+            "2.0", " 30", "0.0", " 11", "3.0", " 21", "4.0", " 31", "0.0", "  0", "ENDSEC", "  0",
+            "EOF",
+        ]
+        .join("\r\n");
+        let drawing = Drawing::load_from_bytes(contents.as_bytes()).unwrap();
+        assert_eq!(AcadVersion::R12, drawing.header.version);
+        let line = drawing.entities().next().unwrap();
+        assert!(!line.common.handle.is_empty());
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(AcadVersion::R12, reparsed.header.version);
+        let reparsed_line = reparsed.entities().next().unwrap();
+        assert_eq!(line.common.handle, reparsed_line.common.handle);
+        assert!(reparsed_line.common.handle.0 < reparsed.header.next_available_handle.0);
+        assert!(reparsed.layers().any(|l| l.name == "0"));
+    }
+
+    #[test]
+    fn find_text_in_text_entities() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new_text(Point::origin(), 1.0, "Pump Station"));
+        drawing.add_entity(Entity::new(EntityType::MText(MText {
+            text: String::from(r"{\C1;PUMP} room\Pnorth"),
+            ..Default::default()
+        })));
+        let mut insert = Insert::default();
+        insert.add_attribute(
+            &mut drawing,
+            Attribute {
+                value: String::from("pump-01"),
+                ..Default::default()
+            },
+        );
+        drawing.add_entity(Entity::new(EntityType::Insert(insert)));
+        drawing.add_entity(Entity::new_text(Point::origin(), 1.0, "Valve"));
+
+        assert_eq!(3, drawing.find_text("pump", false).len());
+        let exact = drawing.find_text("PUMP room", true);
+        assert_eq!(1, exact.len());
+        assert!(matches!(exact[0].specific, EntityType::MText(_)));
+        assert_eq!(1, drawing.find_text("Station", true).len());
+        assert!(drawing.find_text("station", true).is_empty());
+    }
+
+    #[test]
+    fn current_properties_are_written_to_header() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.set_current_layer("walls");
+        drawing.set_current_color(Color::from_index(1));
+        drawing.set_current_line_type("DASHED");
+        drawing.set_current_line_weight(LineWeight::by_layer());
+        assert!(drawing.line_types().any(|lt| lt.name == "DASHED"));
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$CLAYER"),
+                CodePair::new_str(8, "walls"),
+                CodePair::new_str(9, "$CELTYPE"),
+                CodePair::new_str(6, "DASHED"),
+                CodePair::new_str(9, "$CECOLOR"),
+                CodePair::new_i16(62, 1),
+            ],
+        );
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$CELWEIGHT"),
+                CodePair::new_i16(370, LineWeight::by_layer().raw_value()),
+            ],
+        );
+
+        let line = drawing
+            .add_entity_with_current_settings(Entity::new(EntityType::Line(Line::default())));
+        assert_eq!("DASHED", line.common.line_type_name);
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!("walls", reparsed.header.current_layer);
+        assert_eq!("DASHED", reparsed.header.current_entity_line_type);
+    }
+
+    #[test]
+    fn apply_to_entities_where_layer_matches() {
+        let mut drawing = Drawing::new();
+        let mut on_x = Entity::new(EntityType::Line(Line::default()));
+        on_x.common.layer = String::from("x");
+        drawing.add_entity(on_x);
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let mut block = Block::default();
+        let mut in_block = Entity::new(EntityType::Line(Line::default()));
+        in_block.common.layer = String::from("x");
+        block.entities.push(in_block);
+        drawing.add_block(block);
+
+        drawing.apply_to_entities_where(
+            |e| e.common.layer == "x",
+            |e| e.common.color = Color::from_index(1),
+        );
+        let colors = drawing
+            .entities()
+            .map(|e| e.common.color.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Color::from_index(1), Color::by_layer()], colors);
+        assert_eq!(
+            Color::by_layer(),
+            drawing.blocks().next().unwrap().entities[0].common.color
+        );
+
+        let mut count = 0;
+        drawing.apply_to_entities(|e| {
+            e.common.line_type_scale = 2.0;
+            count += 1;
+        });
+        assert_eq!(2, count);
+        assert!(drawing.entities().all(|e| e.common.line_type_scale == 2.0));
+    }
+
+    #[test]
+    fn blocks_in_use_follows_nested_inserts() {
+        let insert_of = |name: &str| {
+            Entity::new(EntityType::Insert(Insert {
+                name: String::from(name),
+                ..Default::default()
+            }))
+        };
+        let mut drawing = Drawing::new();
+        // references are matched ignoring case
+        for (name, child) in &[("A", Some("b")), ("B", Some("C")), ("C", None), ("D", None)] {
+            let mut block = Block {
+                name: String::from(*name),
+                ..Default::default()
+            };
+            if let Some(child) = child {
+                block.entities.push(insert_of(child));
+            }
+            drawing.add_block(block);
+        }
+        drawing.add_entity(insert_of("A"));
+        drawing.add_entity(insert_of("a"));
+
+        let mut in_use = drawing.blocks_in_use().into_iter().collect::<Vec<_>>();
+        in_use.sort();
+        assert_eq!(vec!["A", "B", "C"], in_use);
+    }
+
+    #[test]
+    fn save_all_versions_writes_one_file_per_version() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new_line(Point::origin(), Point::new(1.0, 1.0, 0.0)));
+        let base_path = std::env::temp_dir().join(format!("dxf-save-all-{}", std::process::id()));
+        let paths = drawing
+            .save_all_versions(
+                &base_path,
+                &[AcadVersion::R11, AcadVersion::R12, AcadVersion::R2000],
+            )
+            .unwrap();
+        assert_eq!(3, paths.len());
+        assert!(paths[0].to_string_lossy().ends_with(".R11.dxf"));
+        assert!(paths[1].to_string_lossy().ends_with(".R12.dxf"));
+        assert!(paths[2].to_string_lossy().ends_with(".R2000.dxf"));
+
+        let versions = paths
+            .iter()
+            .map(|p| {
+                let reloaded = Drawing::load_file(p).unwrap();
+                std::fs::remove_file(p).unwrap();
+                assert_eq!(1, reloaded.entities().count());
+                reloaded.header.version
+            })
+            .collect::<Vec<_>>();
+        // R11 and R12 are both written as `AC1009`
+        assert_eq!(
+            vec![AcadVersion::R12, AcadVersion::R12, AcadVersion::R2000],
+            versions
+        );
+        assert_eq!(AcadVersion::R12, drawing.header.version);
+    }
+
+    #[test]
+    fn repair_duplicate_handles_on_load() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new_line(Point::origin(), Point::new(1.0, 0.0, 0.0)));
+        drawing.add_entity(Entity::new_line(Point::origin(), Point::new(2.0, 0.0, 0.0)));
+        let first = {
+            let mut ents = drawing.entities_mut();
+            let first = ents.next().unwrap().common.handle;
+            ents.next().unwrap().common.handle = first;
+            first
+        };
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+
+        // off by default
+        let (loaded, report) =
+            Drawing::load_with_options(&mut buf.as_slice(), &LoadOptions::new()).unwrap();
+        assert!(report.repaired_handles.is_empty());
+        let handles = loaded
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        assert_eq!(handles[0], handles[1]);
+
+        let options = LoadOptions {
+            repair_handles: true,
+        };
+        let (loaded, report) = Drawing::load_with_options(&mut buf.as_slice(), &options).unwrap();
+        let handles = loaded
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        assert_eq!(first, handles[0]);
+        assert_ne!(first, handles[1]);
+        assert_eq!(vec![(first, handles[1])], report.repaired_handles);
+        assert!(loaded.header.next_available_handle.0 > handles[1].0);
+        match loaded.item_by_handle(first) {
+            Some(DrawingItem::Entity(e)) => match e.specific {
+                EntityType::Line(ref line) => assert_eq!(1.0, line.p2.x),
+                _ => panic!("expected a line"),
+            },
+            _ => panic!("expected an entity"),
+        }
+    }
+
+    #[test]
+    fn repair_handles_moves_objects_owned_by_a_renumbered_dictionary() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        let mut first = Dictionary::default();
+        first.value_handles.insert(String::from("a"), Handle(0x20));
+        let mut second = Dictionary::default();
+        second.value_handles.insert(String::from("b"), Handle(0x21));
+        for (handle, owner, specific) in [
+            (0x10, 0x0, ObjectType::Dictionary(first)),
+            (0x10, 0x0, ObjectType::Dictionary(second)),
+            (0x20, 0x10, ObjectType::XRecordObject(Default::default())),
+            (0x21, 0x10, ObjectType::XRecordObject(Default::default())),
+        ] {
+            let mut obj = Object::new(specific);
+            obj.common.handle = Handle(handle);
+            obj.common.__owner_handle = Handle(owner);
+            drawing.__objects.push(obj);
+        }
+
+        let repairs = drawing.repair_handles();
+        assert_eq!(vec![(Handle(0x10), Handle(0x22))], repairs);
+        let owners = drawing
+            .objects()
+            .map(|o| o.common.__owner_handle)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![Handle(0), Handle(0), Handle(0x10), Handle(0x22)],
+            owners
+        );
+    }
+
+    #[test]
+    fn unsupported_entity_fails_save_when_requested() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        drawing.add_entity(Entity::new(EntityType::MText(MText::default())));
+
+        // skipped by default
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+
+        let options = SaveOptions {
+            fail_on_unsupported: true,
+            ..Default::default()
+        };
+        let err = drawing
+            .save_with_options(&mut vec![], &options)
+            .unwrap_err();
+        assert_eq!("MTEXT is not supported in R12", err.to_string());
+        match err {
+            DxfError::Unsupported { feature, version } => {
+                assert_eq!("MTEXT", feature);
+                assert_eq!(Some(AcadVersion::R12), version);
+            }
+            _ => panic!("expected an unsupported error"),
+        }
+
+        drawing.header.version = AcadVersion::R2000;
+        drawing.save_with_options(&mut vec![], &options).unwrap();
+    }
+
+    #[test]
+    fn update_extents_separates_model_and_paper_space() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new_line(
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(5.0, 3.0, 0.0),
+        ));
+        drawing.add_entity(Entity::new_circle(Point::new(-1.0, 0.0, 0.0), 1.0));
+        let mut paper = Entity::new_line(Point::new(0.0, 0.0, 0.0), Point::new(11.0, 8.5, 0.0));
+        paper.common.is_in_paper_space = true;
+        drawing.add_entity(paper);
+
+        drawing.update_extents();
+        assert_eq!(
+            Point::new(-2.0, -1.0, 0.0),
+            drawing.header.minimum_drawing_extents
+        );
+        assert_eq!(
+            Point::new(5.0, 3.0, 0.0),
+            drawing.header.maximum_drawing_extents
+        );
+        assert_eq!(
+            Point::origin(),
+            drawing.header.paperspace_minimum_drawing_extents
+        );
+        assert_eq!(
+            Point::new(11.0, 8.5, 0.0),
+            drawing.header.paperspace_maximum_drawing_extents
+        );
+    }
+
+    #[test]
+    fn into_parts_moves_entities_and_objects() {
+        let mut drawing = Drawing::new();
+        drawing.header.current_layer = String::from("walls");
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        drawing.add_object(Object::new(ObjectType::PlaceHolder(Default::default())));
+        let object_count = drawing.objects().count();
+
+        let (header, entities, objects) = drawing.clone().into_parts();
+        assert_eq!("walls", header.current_layer);
+        assert_eq!(1, entities.len());
+        assert_eq!(object_count, objects.len());
+
+        let entities = drawing.into_entities();
+        assert_eq!(1, entities.len());
+        assert!(matches!(entities[0].specific, EntityType::Line(_)));
+    }
+
+    #[test]
+    fn save_with_entities_sorted_by_layer() {
+        let mut drawing = Drawing::new();
+        for (x, layer) in [(1.0, "b"), (2.0, "a"), (3.0, "B"), (4.0, "A")] {
+            let mut line = Entity::new_line(Point::new(x, 0.0, 0.0), Point::new(x, 1.0, 0.0));
+            line.common.layer = String::from(layer);
+            drawing.add_entity(line);
+        }
+        let line_xs = |d: &Drawing| {
+            d.entities()
+                .map(|e| e.as_line().unwrap().p1.x)
+                .collect::<Vec<_>>()
+        };
+
+        let options = SaveOptions {
+            sort_entities_by_layer: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(vec![2.0, 4.0, 1.0, 3.0], line_xs(&reparsed));
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], line_xs(&drawing));
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], line_xs(&reparsed));
+    }
+
+    #[test]
+    fn objects_of_type_filters_dictionaries() {
+        let mut drawing = Drawing::new();
+        drawing.add_object(Object::new(ObjectType::PlaceHolder(Default::default())));
+        drawing.add_object(Object::new(ObjectType::Dictionary(Default::default())));
+        let dictionaries = drawing
+            .objects_of_type(ObjectTypeKind::Dictionary)
+            .collect::<Vec<_>>();
+        assert!(!dictionaries.is_empty());
+        assert_eq!(
+            drawing
+                .objects()
+                .filter(|o| o.specific.to_type_string() == "DICTIONARY")
+                .count(),
+            dictionaries.len()
+        );
+        assert!(dictionaries.iter().all(|o| o.as_dictionary().is_some()));
+        assert!(dictionaries.iter().all(|o| o.as_place_holder().is_none()));
+        assert_eq!(
+            1,
+            drawing.objects_of_type(ObjectTypeKind::PlaceHolder).count()
+        );
+    }
+
+    #[test]
+    fn link_block_records_creates_missing_items() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(Block {
+            name: String::from("bolt"),
+            ..Default::default()
+        });
+        let index = drawing
+            .block_records()
+            .position(|r| r.name == "bolt")
+            .unwrap();
+        drawing.remove_block_record(index);
+        drawing.add_block_record(BlockRecord {
+            name: String::from("nut"),
+            ..Default::default()
+        });
+        assert!(!drawing.block_records().any(|r| r.name == "bolt"));
+        assert!(!drawing.blocks().any(|b| b.name == "nut"));
+
+        drawing.link_block_records();
+        let record_count = drawing.block_records().count();
+        assert_eq!(record_count, drawing.blocks().count());
+        for block in drawing.blocks() {
+            let record = drawing
+                .block_records()
+                .find(|r| r.name == block.name)
+                .unwrap();
+            assert_eq!(record.handle, block.__owner_handle);
+            assert!(!block.handle.is_empty());
+        }
+        assert!(drawing.block_records().any(|r| r.name == "bolt"));
+        assert!(drawing.blocks().any(|b| b.name == "nut"));
+
+        // linking again doesn't duplicate anything
+        drawing.link_block_records();
+        assert_eq!(record_count, drawing.block_records().count());
+        assert_eq!(record_count, drawing.blocks().count());
+    }
+
+    #[test]
+    fn normalize_links_block_records_when_requested() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(Block {
+            name: String::from("bolt"),
+            ..Default::default()
+        });
+        let index = drawing
+            .block_records()
+            .position(|r| r.name == "bolt")
+            .unwrap();
+        drawing.remove_block_record(index);
+
+        drawing.normalize();
+        assert!(!drawing.block_records().any(|r| r.name == "bolt"));
+
+        let options = NormalizeOptions {
+            link_block_records: true,
+        };
+        drawing.normalize_with_options(&options);
+        let record = drawing.block_records().find(|r| r.name == "bolt").unwrap();
+        let block = drawing.blocks().find(|b| b.name == "bolt").unwrap();
+        assert_eq!(record.handle, block.__owner_handle);
+    }
+
+    #[test]
+    fn link_block_records_ignores_case() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(Block {
+            name: String::from("Bolt"),
+            ..Default::default()
+        });
+        let index = drawing
+            .block_records()
+            .position(|r| r.name == "Bolt")
+            .unwrap();
+        drawing.remove_block_record(index);
+        let record_handle = drawing
+            .add_block_record(BlockRecord {
+                name: String::from("BOLT"),
+                ..Default::default()
+            })
+            .handle;
+        let record_count = drawing.block_records().count();
+
+        drawing.link_block_records();
+        assert_eq!(record_count, drawing.block_records().count());
+        assert_eq!(
+            1,
+            drawing
+                .blocks()
+                .filter(|b| b.name.eq_ignore_ascii_case("bolt"))
+                .count()
+        );
+        let block = drawing.blocks().find(|b| b.name == "Bolt").unwrap();
+        assert_eq!(record_handle, block.__owner_handle);
+    }
+
+    #[test]
+    fn add_block_reuses_existing_block_record() {
+        let mut drawing = Drawing::new();
+        let handle = drawing
+            .add_block_record(BlockRecord {
+                name: String::from("bolt"),
+                ..Default::default()
+            })
+            .handle;
+        drawing.add_block(Block {
+            name: String::from("bolt"),
+            ..Default::default()
+        });
+        assert_eq!(
+            1,
+            drawing.block_records().filter(|r| r.name == "bolt").count()
+        );
+        assert_eq!(handle, drawing.blocks().next().unwrap().__owner_handle);
+    }
+
+    #[test]
+    fn remove_layer_by_name_reassigns_entities() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("old"),
+            ..Default::default()
+        });
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.layer = String::from("old");
+        drawing.add_entity(line);
+
+        match drawing.remove_layer_by_name("old", None) {
+            Err(DxfError::InvalidOperation(ref s)) => assert!(s.contains("in use")),
+            _ => panic!("expected an error"),
+        }
+        assert!(drawing.layers().any(|l| l.name == "old"));
+        assert!(drawing.remove_layer_by_name("0", Some("old")).is_err());
+
+        drawing.remove_layer_by_name("old", Some("0")).unwrap();
+        assert!(!drawing.layers().any(|l| l.name == "old"));
+        assert_eq!("0", drawing.entities().next().unwrap().common.layer);
+    }
+
+    #[test]
+    fn remove_layer_by_name_resets_current_layer() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("Walls"),
+            ..Default::default()
+        });
+        drawing.add_layer(Layer {
+            name: String::from("Doors"),
+            ..Default::default()
+        });
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.layer = String::from("WALLS");
+        drawing.add_entity(line);
+        drawing.header.current_layer = String::from("walls");
+
+        drawing
+            .remove_layer_by_name("Walls", Some("doors"))
+            .unwrap();
+        assert!(!drawing.layers().any(|l| l.name == "Walls"));
+        assert_eq!("Doors", drawing.entities().next().unwrap().common.layer);
+        assert_eq!("Doors", drawing.header.current_layer);
+
+        drawing.header.current_layer = String::from("Doors");
+        assert!(drawing.remove_layer_by_name("DOORS", None).is_err());
+        drawing.remove_entity(0);
+        drawing.remove_layer_by_name("DOORS", None).unwrap();
+        assert_eq!("0", drawing.header.current_layer);
+    }
+
+    #[test]
+    fn move_and_copy_entity_to_layer() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("Walls"),
+            ..Default::default()
+        });
+        let handle = drawing
+            .add_entity(Entity::new(EntityType::Line(Line::default())))
+            .common
+            .handle;
+
+        drawing
+            .move_entity_to_layer(handle, "WALLS", false)
+            .unwrap();
+        assert_eq!("Walls", drawing.entities().next().unwrap().common.layer);
+
+        assert!(drawing
+            .move_entity_to_layer(handle, "doors", false)
+            .is_err());
+        assert!(!drawing.layers().any(|l| l.name == "doors"));
+
+        let copy = drawing.copy_entity_to_layer(handle, "doors", true).unwrap();
+        assert_ne!(handle, copy);
+        assert!(drawing.layers().any(|l| l.name == "doors"));
+        let layers = drawing
+            .entities()
+            .map(|e| e.common.layer.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Walls", "doors"], layers);
+    }
+
+    #[test]
+    fn entities_count_by_layer_ignores_case() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("Walls"),
+            ..Default::default()
+        });
+        for layer in &["Walls", "WALLS", "0", "walls", "doors"] {
+            let mut line = Entity::new(EntityType::Line(Line::default()));
+            line.common.layer = String::from(*layer);
+            drawing.add_entity(line);
+        }
+        let counts = drawing.entities_count_by_layer();
+        assert_eq!(3, counts.len());
+        assert_eq!(Some(&3), counts.get("Walls"));
+        assert_eq!(Some(&1), counts.get("0"));
+        assert_eq!(Some(&1), counts.get("doors"));
+    }
+
+    #[test]
+    fn write_assigned_handles_at_default_version() {
+        let mut drawing = Drawing::new();
+        assert!(!drawing.header.version.is_r13_or_later());
+        assert!(drawing.header.handles_enabled);
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.handle = Handle(0x42);
+        drawing.add_entity_no_handle_set(line);
+        assert_contains_pairs(
+            &drawing,
+            vec![CodePair::new_str(0, "LINE"), CodePair::new_str(5, "42")],
+        );
+
+        drawing.header.handles_enabled = false;
+        assert_not_contains_pairs(
+            &drawing,
+            vec![CodePair::new_str(0, "LINE"), CodePair::new_str(5, "42")],
+        );
+    }
+
+    #[test]
+    fn round_trip_current_dim_style() {
+        let mut drawing = Drawing::new();
+        drawing.add_dim_style(DimStyle {
+            name: String::from("Architectural"),
+            ..Default::default()
+        });
+        drawing.header.dimension_style_name = String::from("Architectural");
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$DIMSTYLE"),
+                CodePair::new_str(2, "Architectural"),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!("Architectural", reparsed.header.dimension_style_name);
+        assert_eq!("Architectural", reparsed.current_dim_style().unwrap().name);
+    }
+
+    #[test]
+    fn validate_dangling_current_dim_style() {
+        let mut drawing = Drawing::new();
+        drawing.header.dimension_style_name = String::from("missing");
+        match drawing.validate() {
+            Err(DxfError::ValidationError(ref s)) => assert!(s.contains("`missing`")),
+            other => panic!("{:?}", other),
+        }
+        let options = SaveOptions {
+            validate: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        assert!(drawing.save_with_options(&mut buf, &options).is_err());
+
+        drawing.header.dimension_style_name = String::from("standard");
+        drawing.validate().unwrap();
+    }
+
+    #[test]
+    fn save_loaded_drawing_with_dangling_current_dim_style() {
+        let drawing = drawing_from_pairs(vec![
+            CodePair::new_str(0, "SECTION"),
+            CodePair::new_str(2, "HEADER"),
+            CodePair::new_str(9, "$DIMSTYLE"),
+            CodePair::new_str(2, "missing"),
+            CodePair::new_str(0, "ENDSEC"),
+            CodePair::new_str(0, "SECTION"),
+            CodePair::new_str(2, "TABLES"),
+            CodePair::new_str(0, "TABLE"),
+            CodePair::new_str(2, "DIMSTYLE"),
+            CodePair::new_str(0, "DIMSTYLE"),
+            CodePair::new_str(2, "STANDARD"),
+            CodePair::new_str(0, "ENDTAB"),
+            CodePair::new_str(0, "ENDSEC"),
+            CodePair::new_str(0, "EOF"),
+        ]);
+        assert_eq!("missing", drawing.header.dimension_style_name);
+        assert!(drawing.validate().is_err());
+
+        // validation is opt-in so the file still saves
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+    }
+
+    #[test]
+    fn renumber_handles_keeps_references_consistent() {
+        let mut drawing = Drawing::new();
+        drawing.header.next_available_handle = Handle(0x1000);
+        let mut block = Block::new("b", Point::origin());
+        block
+            .entities
+            .push(Entity::new(EntityType::Line(Default::default())));
+        drawing.add_block(block);
+        let image_def = drawing
+            .add_object(Object::new(ObjectType::ImageDefinition(Default::default())))
+            .common
+            .handle;
+        let mut image = Entity::new(EntityType::Image(Image {
+            image_def_reference: image_def.as_string(),
+            ..Default::default()
+        }));
+        image.common.extension_data_groups.push(ExtensionGroup {
+            application_name: String::from("ACAD_REACTORS"),
+            items: vec![ExtensionGroupItem::CodePair(CodePair::new_string(
+                330,
+                &image_def.as_string(),
+            ))],
+        });
+        image.common.x_data.push(XData {
+            application_name: String::from("APP"),
+            items: vec![XDataItem::Handle(Handle(0xDEAD))],
+        });
+        let image = drawing.add_entity(image).common.handle;
+        let group = Group {
+            __entities_handle: vec![image],
+            ..Default::default()
+        };
+        drawing.add_group("g", group);
+
+        let renumbered = drawing.renumber_handles();
+
+        let mut handles = drawing
+            .entities()
+            .map(|e| e.common.handle)
+            .chain(drawing.objects().map(|o| o.common.handle))
+            .chain(drawing.blocks().map(|b| b.handle))
+            .chain(drawing.layers().map(|l| l.handle))
+            .collect::<Vec<_>>();
+        handles.sort_by_key(|h| h.0);
+        handles.dedup();
+        let max_handle = drawing.header.next_available_handle.0 - 1;
+        assert!(handles.iter().all(|h| !h.is_empty() && h.0 <= max_handle));
+        assert_eq!(Handle(max_handle + 1), drawing.header.next_available_handle);
+        assert!(max_handle < 0x1000);
+
+        let image_def = renumbered[&image_def];
+        let image = drawing.item_by_handle(renumbered[&image]).unwrap();
+        let image = match image {
+            DrawingItem::Entity(e) => e,
+            _ => panic!("expected an entity"),
+        };
+        assert_eq!(
+            image_def.as_string(),
+            image.as_image().unwrap().image_def_reference
+        );
+        match image.common.extension_data_groups[0].items[0] {
+            ExtensionGroupItem::CodePair(ref pair) => {
+                assert_eq!(image_def, pair.as_handle().unwrap())
+            }
+            _ => panic!("expected a code pair"),
+        }
+        // dangling references are cleared
+        assert_eq!(
+            XDataItem::Handle(Handle::empty()),
+            image.common.x_data[0].items[0]
+        );
+
+        let (_, group) = drawing.groups().next().unwrap();
+        assert_eq!(
+            vec![image.common.handle],
+            group
+                .entities(&drawing)
+                .iter()
+                .map(|e| e.common.handle)
+                .collect::<Vec<_>>()
+        );
+        let block = drawing.blocks().find(|b| b.name == "b").unwrap();
+        match block.owner(&drawing) {
+            Some(DrawingItem::BlockRecord(br)) => assert_eq!("b", br.name),
+            _ => panic!("expected the block record"),
+        }
+    }
+
+    #[test]
+    fn renumber_handles_remaps_proxy_object_ids() {
+        let mut drawing = Drawing::new();
+        drawing.header.next_available_handle = Handle(0x1000);
+        let layer = drawing
+            .add_layer(Layer {
+                name: String::from("proxies"),
+                ..Default::default()
+            })
+            .handle;
+        drawing.add_entity(Entity::new(EntityType::ProxyEntity(ProxyEntity {
+            object_ids: vec![(330, layer.as_string()), (360, String::from("DEAD"))],
+            ..Default::default()
+        })));
+        drawing.add_object(Object::new(ObjectType::AcadProxyObject(AcadProxyObject {
+            object_ids: vec![(340, layer.as_string())],
+            ..Default::default()
+        })));
+
+        let renumbered = drawing.renumber_handles();
+        let layer = renumbered[&layer].as_string();
+        match drawing.entities().next().unwrap().specific {
+            EntityType::ProxyEntity(ref proxy) => {
+                assert_eq!(
+                    vec![(330, layer.clone()), (360, String::new())],
+                    proxy.object_ids
+                );
+            }
+            _ => panic!("expected a proxy entity"),
+        }
+        match drawing.objects().last().unwrap().specific {
+            ObjectType::AcadProxyObject(ref proxy) => {
+                assert_eq!(vec![(340, layer)], proxy.object_ids)
+            }
+            _ => panic!("expected a proxy object"),
+        }
+    }
+
+    #[test]
+    fn renumber_handles_with_preserve_order() {
+        let mut drawing = Drawing::new();
+        for x in 1..=3 {
+            drawing.add_entity(Entity::new_line(
+                Point::new(x as f64, 0.0, 0.0),
+                Point::new(x as f64, 1.0, 0.0),
+            ));
+        }
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let mut drawing = Drawing::load_from_bytes(&buf).unwrap();
+        {
+            let mut entities = drawing.entities_mut().collect::<Vec<_>>();
+            let (first, rest) = entities.split_at_mut(1);
+            std::mem::swap(first[0], rest[1]);
+        }
+        // the layer comes first so the line with the same handle is the one repaired
+        let duplicate = drawing.entities().nth(1).unwrap().common.handle;
+        drawing.layers_mut().next().unwrap().handle = duplicate;
+
+        drawing.renumber_handles();
+        assert_ne!(
+            drawing.layers().next().unwrap().handle,
+            drawing.entities().nth(1).unwrap().common.handle
+        );
+        let options = SaveOptions {
+            preserve_order: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        let line_xs = reparsed
+            .entities()
+            .map(|e| e.as_line().unwrap().p1.x)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1.0, 2.0, 3.0], line_xs);
+    }
+
+    #[test]
+    fn save_writes_mandatory_table_records() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        drawing.header.version = AcadVersion::R2000;
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let saved = Drawing::load_from_bytes(&buf).unwrap();
+        assert!(saved.app_ids().any(|a| a.name == "ACAD"));
+        for name in ["BYBLOCK", "BYLAYER", "CONTINUOUS"] {
+            assert!(saved.line_types().any(|l| l.name == name));
+        }
+        assert!(saved.layers().any(|l| l.name == "0"));
+        assert!(saved.styles().any(|s| s.name == "STANDARD"));
+        // the drawing itself isn't modified
+        assert_eq!(0, drawing.layers().count());
+        let mut handles = saved
+            .layers()
+            .map(|l| l.handle)
+            .chain(saved.line_types().map(|l| l.handle))
+            .chain(saved.styles().map(|s| s.handle))
+            .collect::<Vec<_>>();
+        let count = handles.len();
+        handles.sort_by_key(|h| h.0);
+        handles.dedup();
+        assert_eq!(count, handles.len());
+        assert!(handles
+            .iter()
+            .all(|h| h.0 < saved.header.next_available_handle.0));
+
+        let options = SaveOptions {
+            raw: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        drawing.save_with_options(&mut buf, &options).unwrap();
+        let saved = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(0, saved.app_ids().count());
+        assert_eq!(0, saved.line_types().count());
+        assert_eq!(0, saved.layers().count());
+        assert_eq!(0, saved.styles().count());
+    }
+
+    #[test]
+    fn save_only_adds_missing_table_records() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        drawing.header.version = AcadVersion::R2000;
+        for name in ["Z", "A"] {
+            drawing.add_layer(Layer {
+                name: String::from(name),
+                line_type_name: String::new(),
+                ..Default::default()
+            });
+        }
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let saved = Drawing::load_from_bytes(&buf).unwrap();
+        let layers = saved.layers().collect::<Vec<_>>();
+        assert_eq!(
+            vec!["Z", "A", "0"],
+            layers.iter().map(|l| l.name.as_str()).collect::<Vec<_>>()
+        );
+        // existing records aren't normalized
+        assert_eq!("", layers[0].line_type_name);
+    }
+
+    #[test]
+    fn save_matches_mandatory_table_records_ignoring_case() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.header.current_layer = String::from("walls");
+        drawing.add_line_type(LineType {
+            name: String::from("Continuous"),
+            ..Default::default()
+        });
+        drawing.add_layer(Layer {
+            name: String::from("Walls"),
+            line_type_name: String::from("Continuous"),
+            ..Default::default()
+        });
+        drawing.add_style(Style {
+            name: String::from("Standard"),
+            ..Default::default()
+        });
+        drawing.add_block_record(BlockRecord {
+            name: String::from("*Model_Space"),
+            ..Default::default()
+        });
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let loaded = Drawing::load_from_bytes(&buf).unwrap();
+
+        let mut buf = vec![];
+        loaded.save(&mut buf).unwrap();
+        let saved = Drawing::load_from_bytes(&buf).unwrap();
+        let count = |names: Vec<&str>, name: &str| {
+            names
+                .iter()
+                .filter(|n| n.eq_ignore_ascii_case(name))
+                .count()
+        };
+        let line_types = saved.line_types().map(|l| l.name.as_str()).collect();
+        assert_eq!(1, count(line_types, "CONTINUOUS"));
+        let styles = saved.styles().map(|s| s.name.as_str()).collect();
+        assert_eq!(1, count(styles, "STANDARD"));
+        let block_records = saved.block_records().map(|b| b.name.as_str()).collect();
+        assert_eq!(1, count(block_records, "*MODEL_SPACE"));
+        let layers = saved.layers().map(|l| l.name.as_str()).collect();
+        assert_eq!(1, count(layers, "WALLS"));
+        assert!(saved.line_types().any(|l| l.name == "Continuous"));
+    }
+
+    #[test]
+    fn navigate_from_root_dictionary_to_layout() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        assert!(drawing.root_dictionary().is_none());
+        let root = drawing
+            .add_object(Object::new(ObjectType::Dictionary(Default::default())))
+            .handle();
+        let layouts = drawing
+            .add_object(Object::new(ObjectType::Dictionary(Default::default())))
+            .handle();
+        let mut layout = Object::new(ObjectType::Layout(Layout {
+            layout_name: String::from("Layout1"),
+            ..Default::default()
+        }));
+        layout.set_owner_handle(layouts);
+        let layout = drawing.add_object(layout).handle();
+        for obj in drawing.objects_mut() {
+            let handle = obj.handle();
+            if let ObjectType::Dictionary(ref mut dict) = obj.specific {
+                if handle == root {
+                    dict.value_handles
+                        .insert(String::from("ACAD_LAYOUT"), layouts);
+                } else {
+                    dict.value_handles.insert(String::from("Layout1"), layout);
+                }
+            }
+        }
+
+        let layouts = drawing
+            .root_dictionary()
+            .and_then(|root| root.as_dictionary())
+            .and_then(|root| root.get("ACAD_LAYOUT"))
+            .unwrap();
+        let root_dictionary = drawing.root_dictionary().unwrap();
+        assert_eq!(root, root_dictionary.handle());
+        assert!(root_dictionary
+            .as_dictionary()
+            .unwrap()
+            .get("missing")
+            .is_none());
+        let layout = match drawing.item_by_handle(layouts) {
+            Some(DrawingItem::Object(Object {
+                specific: ObjectType::Dictionary(ref dict),
+                ..
+            })) => dict.get("Layout1").unwrap(),
+            _ => panic!("expected a dictionary"),
+        };
+        match drawing.item_by_handle(layout) {
+            Some(DrawingItem::Object(obj)) => {
+                assert_eq!("Layout1", obj.as_layout().unwrap().layout_name);
+                assert_eq!(layouts, obj.owner_handle());
+            }
+            _ => panic!("expected a layout"),
+        }
+    }
+
+    #[test]
+    fn root_dictionary_is_the_dictionary_without_an_owner() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        let xrecord = drawing
+            .add_object(Object::new(ObjectType::XRecordObject(Default::default())))
+            .handle();
+        let mut owned = Object::new(ObjectType::Dictionary(Default::default()));
+        owned.set_owner_handle(xrecord);
+        drawing.add_object(owned);
+        assert!(drawing.root_dictionary().is_none());
+
+        let mut root = Dictionary::default();
+        root.value_handles.insert(String::from("XRECORD"), xrecord);
+        let root = drawing
+            .add_object(Object::new(ObjectType::Dictionary(root)))
+            .handle();
+        let root_dictionary = drawing.root_dictionary().unwrap();
+        assert_eq!(root, root_dictionary.handle());
+        assert_eq!(
+            Some(xrecord),
+            root_dictionary.as_dictionary().unwrap().get("XRECORD")
+        );
+    }
 }
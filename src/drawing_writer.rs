@@ -0,0 +1,310 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use crate::code_pair_writer::CodePairWriter;
+use crate::drawing::MissingTableRecords;
+use crate::entities::*;
+use crate::{
+    CodePair, DefaultValueFormatter, Drawing, DxfError, DxfResult, Handle, Header, SaveOptions,
+    ValueFormatter,
+};
+
+/// Writes an ASCII DXF file one entity at a time so the entities never have to be held in memory.
+///
+/// The header, classes, tables, and blocks are written by `begin`, each call to `write_entity`
+/// writes a single entity, and `finish` writes the objects and the end of the file.  Because the
+/// header is written first, `begin` reserves a fixed number of handles for the streamed entities
+/// and `$HANDSEED` is written past the end of that range.
+///
+/// For the same reason the table records that streamed entities refer to, e.g., their layers and
+/// line types, aren't added to the file; they have to be in the drawing passed to `begin` or
+/// `begin_with_drawing`.
+pub struct DrawingWriter<'a, T>
+where
+    T: Write + ?Sized + 'a,
+{
+    writer: &'a mut T,
+    value_formatter: Option<Arc<dyn ValueFormatter>>,
+    drawing: Drawing,
+    write_handles: bool,
+    handle_limit: u64,
+    validate: bool,
+    fail_on_unsupported: bool,
+}
+
+impl<'a, T: Write + ?Sized> DrawingWriter<'a, T> {
+    /// Writes the start of a new drawing with the specified header and opens the `ENTITIES`
+    /// section.  `handle_budget` handles are reserved for streamed entities, including their
+    /// vertices and attributes.
+    pub fn begin(writer: &'a mut T, header: &Header, handle_budget: u64) -> DxfResult<Self> {
+        let mut drawing = Drawing::new();
+        let next_handle = drawing
+            .header
+            .next_available_handle
+            .0
+            .max(header.next_available_handle.0);
+        drawing.header = header.clone();
+        drawing.header.next_available_handle = Handle(next_handle);
+        DrawingWriter::begin_with_drawing(writer, drawing, handle_budget)
+    }
+    /// Writes the start of `drawing`, including its existing entities, and opens the `ENTITIES`
+    /// section.  The objects of `drawing` are written by `finish`.  Like `Drawing::save`, any
+    /// missing mandatory table records are written without being added to `drawing`.
+    /// `handle_budget` handles are reserved for streamed entities, including their vertices and
+    /// attributes.
+    pub fn begin_with_drawing(
+        writer: &'a mut T,
+        drawing: Drawing,
+        handle_budget: u64,
+    ) -> DxfResult<Self> {
+        DrawingWriter::begin_with_options(writer, drawing, handle_budget, &SaveOptions::default())
+    }
+    /// Writes the start of `drawing` like `begin_with_drawing` using the specified options.  Only
+    /// `value_formatter`, `comments`, `validate`, `fail_on_unsupported`, and `raw` are honored;
+    /// `validate` and `fail_on_unsupported` also apply to each streamed entity.
+    pub fn begin_with_options(
+        writer: &'a mut T,
+        mut drawing: Drawing,
+        handle_budget: u64,
+        options: &SaveOptions,
+    ) -> DxfResult<Self> {
+        if options.validate {
+            drawing.validate()?;
+        }
+        if options.fail_on_unsupported {
+            drawing.check_supported()?;
+        }
+        let write_handles = drawing.header.supports_handles();
+        let missing = if options.raw {
+            MissingTableRecords::default()
+        } else {
+            drawing.missing_table_records()
+        };
+        drawing.header.next_available_handle =
+            Handle(drawing.header.next_available_handle.0 + missing.len() as u64);
+        let first_handle = drawing.header.next_available_handle;
+        let handle_limit = first_handle.0.saturating_add(handle_budget);
+        let mut pairs = vec![];
+        for line in options.comments.iter().flat_map(|c| c.lines()) {
+            pairs.push(CodePair::new_str(999, line));
+        }
+        drawing.add_header_pairs(&mut pairs, Handle(handle_limit))?;
+        drawing.add_classes_pairs(&mut pairs);
+        drawing.add_tables_pairs(&mut pairs, &missing, write_handles);
+        drawing.add_blocks_pairs(&mut pairs, write_handles);
+        pairs.push(CodePair::new_str(0, "SECTION"));
+        pairs.push(CodePair::new_str(2, "ENTITIES"));
+        for entity in drawing.entities() {
+            entity.add_code_pairs(&mut pairs, drawing.header.version, write_handles);
+        }
+
+        let mut drawing_writer = DrawingWriter {
+            writer,
+            value_formatter: options.value_formatter.clone(),
+            drawing,
+            write_handles,
+            handle_limit,
+            validate: options.validate,
+            fail_on_unsupported: options.fail_on_unsupported,
+        };
+        let mut code_pair_writer = drawing_writer.code_pair_writer();
+        code_pair_writer.write_prelude()?;
+        for pair in pairs {
+            code_pair_writer.write_code_pair(&pair)?;
+        }
+
+        Ok(drawing_writer)
+    }
+    /// Writes a single entity.  Entities without a handle, and their vertices and attributes, are
+    /// given new handles from the range reserved by `begin`; `DxfError::InvalidOperation` is
+    /// returned and nothing is written once that range is used up.
+    pub fn write_entity(&mut self, entity: &Entity) -> DxfResult<()> {
+        if self.validate {
+            if let EntityType::Spline(ref spline) = entity.specific {
+                spline.validate()?;
+            }
+        }
+        if self.fail_on_unsupported
+            && !entity
+                .specific
+                .is_supported_on_version(self.drawing.header.version)
+        {
+            return Err(DxfError::Unsupported {
+                feature: String::from(entity.specific.to_type_string()),
+                version: Some(self.drawing.header.version),
+            });
+        }
+
+        let mut pairs = vec![];
+        if self.write_handles && entity.common.handle.is_empty() {
+            let mut entity = entity.clone();
+            let next_handle = self.drawing.header.next_available_handle;
+            self.drawing.set_entity_handles(&mut entity, false);
+            if self.drawing.header.next_available_handle.0 > self.handle_limit {
+                self.drawing.header.next_available_handle = next_handle;
+                return Err(DxfError::InvalidOperation(String::from(
+                    "the handles reserved for streamed entities are used up",
+                )));
+            }
+            entity.add_code_pairs(&mut pairs, self.drawing.header.version, self.write_handles);
+        } else {
+            entity.add_code_pairs(&mut pairs, self.drawing.header.version, self.write_handles);
+        }
+
+        let mut code_pair_writer = self.code_pair_writer();
+        for pair in pairs {
+            code_pair_writer.write_code_pair(&pair)?;
+        }
+
+        Ok(())
+    }
+    /// Closes the `ENTITIES` section, writes the objects and the end of the file, and flushes the
+    /// writer.
+    pub fn finish(mut self) -> DxfResult<()> {
+        let mut pairs = vec![CodePair::new_str(0, "ENDSEC")];
+        self.drawing.add_objects_pairs(&mut pairs);
+        pairs.push(CodePair::new_str(0, "EOF"));
+        let mut code_pair_writer = self.code_pair_writer();
+        for pair in pairs {
+            code_pair_writer.write_code_pair(&pair)?;
+        }
+
+        code_pair_writer.flush()
+    }
+    fn code_pair_writer(&mut self) -> CodePairWriter<'_, T> {
+        CodePairWriter::new(
+            self.writer,
+            true,
+            self.drawing.text_encoding(),
+            self.drawing.header.version,
+            match self.value_formatter {
+                Some(ref value_formatter) => value_formatter.as_ref(),
+                None => &DefaultValueFormatter,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::*;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    #[test]
+    fn write_entities_incrementally() {
+        let header = Header::for_version(AcadVersion::R2000);
+        let mut buf = vec![];
+        let mut writer = DrawingWriter::begin(&mut buf, &header, 3).unwrap();
+        for i in 0..3 {
+            let x = f64::from(i);
+            writer
+                .write_entity(&Entity::new_line(
+                    Point::new(x, 0.0, 0.0),
+                    Point::new(x, 1.0, 0.0),
+                ))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        let drawing = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(AcadVersion::R2000, drawing.header.version);
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(3, entities.len());
+        let handles = entities
+            .iter()
+            .map(|e| e.common.handle.0)
+            .collect::<HashSet<_>>();
+        assert_eq!(3, handles.len());
+        assert!(handles.iter().all(|h| *h != 0));
+
+        // `$HANDSEED` is past every streamed handle
+        let handle_seed = drawing.header.next_available_handle.0;
+        assert!(handles.iter().all(|h| *h < handle_seed));
+        match entities[2].specific {
+            EntityType::Line(ref line) => assert_eq!(Point::new(2.0, 0.0, 0.0), line.p1),
+            _ => panic!("expected a line"),
+        }
+        assert!(drawing.layers().any(|l| l.name == "0"));
+    }
+
+    #[test]
+    fn write_entity_past_handle_budget() {
+        let header = Header::for_version(AcadVersion::R2000);
+        let mut buf = vec![];
+        let mut writer = DrawingWriter::begin(&mut buf, &header, 1).unwrap();
+        let line = Entity::new_line(Point::origin(), Point::new(1.0, 1.0, 0.0));
+        writer.write_entity(&line).unwrap();
+        match writer.write_entity(&line) {
+            Err(DxfError::InvalidOperation(_)) => (),
+            other => panic!("expected an invalid operation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn write_missing_table_records_when_streaming() {
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        drawing.header.version = AcadVersion::R2000;
+        let mut buf = vec![];
+        let mut writer = DrawingWriter::begin_with_drawing(&mut buf, drawing, 1).unwrap();
+        writer
+            .write_entity(&Entity::new_line(
+                Point::origin(),
+                Point::new(1.0, 1.0, 0.0),
+            ))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let drawing = Drawing::load_from_bytes(&buf).unwrap();
+        assert!(drawing.line_types().any(|l| l.name == "CONTINUOUS"));
+        assert!(drawing.styles().any(|s| s.name == "STANDARD"));
+
+        // the streamed entity doesn't reuse a handle given to a missing table record
+        let line_handle = drawing.entities().next().unwrap().common.handle;
+        assert!(drawing.line_types().all(|l| l.handle != line_handle));
+        assert!(drawing.app_ids().all(|a| a.handle != line_handle));
+    }
+
+    #[test]
+    fn write_entities_with_options() {
+        struct FixedFormatter;
+        impl ValueFormatter for FixedFormatter {
+            fn format_f64(&self, _code: i32, value: f64) -> String {
+                format!("{:.3}", value)
+            }
+        }
+
+        let mut spline = Spline {
+            degree_of_curve: 1,
+            control_points: vec![Point::origin(); 2],
+            knot_values: vec![0.0, 0.0, 1.0, 1.0],
+            ..Default::default()
+        };
+        spline.set_is_rational(true);
+        let spline = Entity::new(EntityType::Spline(spline));
+        let line = Entity::new_line(Point::origin(), Point::new(1.5, 0.0, 0.0));
+
+        // invalid splines are only rejected when asked
+        let mut buf = vec![];
+        let mut writer = DrawingWriter::begin(&mut buf, &Header::default(), 2).unwrap();
+        writer.write_entity(&spline).unwrap();
+        writer.finish().unwrap();
+
+        let options = SaveOptions {
+            validate: true,
+            value_formatter: Some(Arc::new(FixedFormatter)),
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        let mut writer =
+            DrawingWriter::begin_with_options(&mut buf, Drawing::new(), 2, &options).unwrap();
+        assert!(writer.write_entity(&spline).is_err());
+        writer.write_entity(&line).unwrap();
+        writer.finish().unwrap();
+        let contents = String::from_utf8(buf).unwrap();
+        assert!(contents.contains("\r\n 11\r\n1.500\r\n"));
+    }
+}
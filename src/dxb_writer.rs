@@ -65,6 +65,7 @@ impl<T: Write> DxbWriter<T> {
 
         // write null terminator
         self.writer.write_u8(0)?;
+        self.writer.flush()?;
         Ok(())
     }
     fn write_entities(&mut self, entities: &[Entity]) -> DxfResult<()> {
@@ -3,14 +3,23 @@ use std::fmt;
 use std::io;
 use std::num;
 
+use crate::enums::AcadVersion;
 use crate::CodePair;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DxfError {
     IoError(io::Error),
     ImageError(image::ImageError),
     ParseFloatError(num::ParseFloatError, usize),
     ParseIntError(num::ParseIntError, usize),
+    InvalidValue {
+        code: i32,
+        value: String,
+        expected_type: &'static str,
+        offset: usize,
+        cause: Box<DxfError>,
+    },
     ParseError(usize),
     UnexpectedCode(i32, usize),
     UnexpectedCodePair(CodePair, String),
@@ -23,6 +32,12 @@ pub enum DxfError {
     InvalidBinaryFile,
     MalformedString,
     WrongItemType,
+    ValidationError(String),
+    InvalidOperation(String),
+    Unsupported {
+        feature: String,
+        version: Option<AcadVersion>,
+    },
 }
 
 impl From<io::Error> for DxfError {
@@ -44,6 +59,17 @@ impl fmt::Display for DxfError {
             DxfError::ImageError(ref e) => write!(formatter, "{}", e),
             DxfError::ParseFloatError(ref e, o) => write!(formatter, "{} at line/offset {}", e, o),
             DxfError::ParseIntError(ref e, o) => write!(formatter, "{} at line/offset {}", e, o),
+            DxfError::InvalidValue {
+                code,
+                ref value,
+                expected_type,
+                offset,
+                ..
+            } => write!(
+                formatter,
+                "\"{}\" is not a valid {} for code {} at line/offset {}",
+                value, expected_type, code, offset
+            ),
             DxfError::ParseError(o) => write!(
                 formatter,
                 "there was a general parsing error at line/offset {}",
@@ -89,6 +115,18 @@ impl fmt::Display for DxfError {
             DxfError::InvalidBinaryFile => write!(formatter, "the binary file is invalid"),
             DxfError::MalformedString => write!(formatter, "the string is malformed"),
             DxfError::WrongItemType => write!(formatter, "the specified item type is not correct"),
+            DxfError::ValidationError(ref s) => write!(formatter, "the drawing is invalid: {}", s),
+            DxfError::InvalidOperation(ref s) => {
+                write!(formatter, "the operation is invalid: {}", s)
+            }
+            DxfError::Unsupported {
+                ref feature,
+                version: Some(version),
+            } => write!(formatter, "{} is not supported in {:?}", feature, version),
+            DxfError::Unsupported {
+                ref feature,
+                version: None,
+            } => write!(formatter, "{} is not supported", feature),
         }
     }
 }
@@ -100,6 +138,7 @@ impl error::Error for DxfError {
             DxfError::ImageError(ref e) => Some(e),
             DxfError::ParseFloatError(ref e, _) => Some(e),
             DxfError::ParseIntError(ref e, _) => Some(e),
+            DxfError::InvalidValue { ref cause, .. } => Some(cause.as_ref()),
             _ => None,
         }
     }
@@ -2,14 +2,28 @@
 
 use enum_primitive::FromPrimitive;
 
-use crate::{CodePair, Color, DxfError, DxfResult, Handle, Point, Vector};
+use crate::{
+    ocs_point_to_wcs, CodePair, Color, DrawingItem, DxfError, DxfResult, Handle, Point,
+    TransformationMatrix, Vector,
+};
 
 use crate::code_pair_put_back::CodePairPutBack;
+use crate::drawing::AUTO_REPLACE_HANDLE;
 use crate::entities::*;
 use crate::enums::*;
 use crate::helper_functions::*;
 use crate::Drawing;
 
+//------------------------------------------------------------------------------
+//                                                    AngularThreePointDimension
+//------------------------------------------------------------------------------
+impl AngularThreePointDimension {
+    /// Angles aren't computed yet, so this always returns `None`.
+    pub fn measurement(&self) -> Option<f64> {
+        None
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                           Arc
 //------------------------------------------------------------------------------
@@ -23,6 +37,26 @@ impl Arc {
             ..Default::default()
         }
     }
+    /// Returns the center of the arc in world coordinates.
+    pub fn center_wcs(&self) -> Point {
+        self.center.to_wcs_with(&self.normal)
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                           AttributeDefinition
+//------------------------------------------------------------------------------
+impl AttributeDefinition {
+    /// Creates a new `AttributeDefinition` with the specified tag, prompt, and default value.
+    pub fn new(text_tag: &str, prompt: &str, default_value: &str, location: Point) -> Self {
+        AttributeDefinition {
+            text_tag: String::from(text_tag),
+            prompt: String::from(prompt),
+            value: String::from(default_value),
+            location,
+            ..Default::default()
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -36,6 +70,38 @@ impl Circle {
             ..Default::default()
         }
     }
+    /// Creates a new `Circle` with the specified diameter.
+    pub fn from_diameter(center: Point, diameter: f64) -> Self {
+        Circle::new(center, diameter / 2.0)
+    }
+    /// Creates a new `Circle` with `p1` and `p2` at opposite ends of a diameter.
+    pub fn from_two_points_diameter(p1: Point, p2: Point) -> Self {
+        let center = Point::new(
+            (p1.x + p2.x) / 2.0,
+            (p1.y + p2.y) / 2.0,
+            (p1.z + p2.z) / 2.0,
+        );
+        let diameter =
+            ((p2.x - p1.x).powi(2) + (p2.y - p1.y).powi(2) + (p2.z - p1.z).powi(2)).sqrt();
+        Circle::from_diameter(center, diameter)
+    }
+    /// Returns the center of the circle in world coordinates.
+    pub fn center_wcs(&self) -> Point {
+        self.center.to_wcs_with(&self.normal)
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                             DiameterDimension
+//------------------------------------------------------------------------------
+impl DiameterDimension {
+    /// Returns the diameter measured between the two definition points on the circle.
+    pub fn measurement(&self) -> Option<f64> {
+        Some(DimensionBase::distance(
+            &self.dimension_base.definition_point_1,
+            &self.definition_point_2,
+        ))
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -62,6 +128,68 @@ impl DimensionBase {
         }
         val
     }
+    fn distance(p1: &Point, p2: &Point) -> f64 {
+        let (dx, dy, dz) = (p2.x - p1.x, p2.y - p1.y, p2.z - p1.z);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                       Ellipse
+//------------------------------------------------------------------------------
+impl Ellipse {
+    /// Creates a new full `Ellipse` in the XY plane.  `major_axis` is the vector from the center to
+    /// the end of the major axis and `minor_over_major` is the length of the minor axis relative to
+    /// it.  If `minor_over_major` is greater than 1, the axes are swapped so the stored ratio is at
+    /// most 1 as DXF requires.
+    pub fn from_axes(center: Point, major_axis: Vector, minor_over_major: f64) -> Self {
+        let (major_axis, minor_axis_ratio) = if minor_over_major > 1.0 {
+            let minor = Vector::z_axis().cross(&major_axis);
+            (
+                Vector::new(
+                    minor.x * minor_over_major,
+                    minor.y * minor_over_major,
+                    minor.z * minor_over_major,
+                ),
+                1.0 / minor_over_major,
+            )
+        } else {
+            (major_axis, minor_over_major)
+        };
+        Ellipse {
+            center,
+            major_axis,
+            minor_axis_ratio,
+            ..Default::default()
+        }
+    }
+    /// Creates a new full `Ellipse` inscribed in the axis-aligned rectangle from `min` to `max`.
+    pub fn from_bounding_box(min: Point, max: Point) -> Self {
+        let center = Point::new(
+            (min.x + max.x) / 2.0,
+            (min.y + max.y) / 2.0,
+            (min.z + max.z) / 2.0,
+        );
+        let half_width = (max.x - min.x).abs() / 2.0;
+        let half_height = (max.y - min.y).abs() / 2.0;
+        if half_width >= half_height {
+            Ellipse::from_axes(
+                center,
+                Vector::new(half_width, 0.0, 0.0),
+                if half_width == 0.0 {
+                    1.0
+                } else {
+                    half_height / half_width
+                },
+            )
+        } else {
+            Ellipse::from_axes(
+                center,
+                Vector::new(0.0, half_height, 0.0),
+                half_width / half_height,
+            )
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -84,6 +212,58 @@ impl Face3D {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                         Helix
+//------------------------------------------------------------------------------
+impl Helix {
+    /// Returns `segments + 1` points along the helix, from the start point to the end of the last
+    /// turn.  When `radius` is set it takes precedence over the start point's distance from the
+    /// axis.
+    pub fn tessellate(&self, segments: usize) -> Vec<Point> {
+        let axis = self.axis_vector.normalize();
+        let base = &self.axis_base_point;
+        // the start point relative to the axis, with any component along the axis removed
+        let (sx, sy, sz) = (
+            self.start_point.x - base.x,
+            self.start_point.y - base.y,
+            self.start_point.z - base.z,
+        );
+        let along = sx * axis.x + sy * axis.y + sz * axis.z;
+        let mut radial = Vector::new(
+            sx - along * axis.x,
+            sy - along * axis.y,
+            sz - along * axis.z,
+        );
+        if self.radius > 0.0 {
+            // the start point only gives the direction; `radius` is the distance from the axis
+            let direction = if radial.length() > 0.0 {
+                radial.normalize()
+            } else {
+                Entity::ocs_axes(&axis).0
+            };
+            radial = Entity::scaled(&direction, self.radius);
+        }
+        let tangent = axis.cross(&radial);
+        let handedness = if self.is_right_handed { 1.0 } else { -1.0 };
+        let total_angle = self.number_of_turns * 2.0 * std::f64::consts::PI * handedness;
+        let total_height = self.number_of_turns * self.turn_height;
+
+        let segments = segments.max(1);
+        (0..=segments)
+            .map(|i| {
+                let t = i as f64 / segments as f64;
+                let (sin, cos) = (t * total_angle).sin_cos();
+                let height = along + t * total_height;
+                Point::new(
+                    base.x + radial.x * cos + tangent.x * sin + axis.x * height,
+                    base.y + radial.y * cos + tangent.y * sin + axis.y * height,
+                    base.z + radial.z * cos + tangent.z * sin + axis.z * height,
+                )
+            })
+            .collect()
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                        Insert
 //------------------------------------------------------------------------------
@@ -98,6 +278,43 @@ impl Insert {
         let att_handle = drawing.next_handle();
         self.__attributes_and_handles.push((att, att_handle));
     }
+    /// Returns the transformation from block coordinates to world coordinates for this insert.  The block's base
+    /// point is not included and must be subtracted first.
+    pub fn to_matrix(&self) -> TransformationMatrix {
+        self.to_matrix_for_cell(0, 0)
+    }
+    /// Returns the insertion point of each element of the insert's rectangular array, row by row,
+    /// in world coordinates.  The spacing is rotated with the insert; an insert without an array
+    /// returns its location.
+    pub fn array_positions(&self) -> Vec<Point> {
+        let mut positions = vec![];
+        for row in 0..self.row_count.max(1) {
+            for column in 0..self.column_count.max(1) {
+                positions.push(
+                    self.to_matrix_for_cell(row, column)
+                        .transform_point(&Point::origin()),
+                );
+            }
+        }
+
+        positions
+    }
+    pub(crate) fn to_matrix_for_cell(&self, row: i16, column: i16) -> TransformationMatrix {
+        // the location and rotation are in the insert's object coordinate system
+        TransformationMatrix::ocs_to_wcs(&self.extrusion_direction)
+            * TransformationMatrix::translation(self.location.x, self.location.y, self.location.z)
+            * TransformationMatrix::rotation_about_z(self.rotation)
+            * TransformationMatrix::translation(
+                f64::from(column) * self.column_spacing,
+                f64::from(row) * self.row_spacing,
+                0.0,
+            )
+            * TransformationMatrix::scale(
+                self.x_scale_factor,
+                self.y_scale_factor,
+                self.z_scale_factor,
+            )
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -113,6 +330,102 @@ impl Line {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                    LwPolyline
+//------------------------------------------------------------------------------
+impl LwPolyline {
+    /// Returns the vertex locations in the polyline's OCS.  When `closed_wrap` is `true` and the
+    /// polyline is closed, the first location is repeated at the end.
+    pub fn points(&self, closed_wrap: bool) -> Vec<Point> {
+        let mut points = self
+            .vertices
+            .iter()
+            .map(|v| Point::new(v.x, v.y, 0.0))
+            .collect::<Vec<_>>();
+        if closed_wrap && self.is_closed() && !points.is_empty() {
+            points.push(points[0].clone());
+        }
+
+        points
+    }
+    /// Returns each segment as `(start, end, bulge)`, including the segment from the last vertex
+    /// back to the first when the polyline is closed.
+    pub fn segments(&self) -> Vec<(Point, Point, f64)> {
+        let bulges = self.vertices.iter().map(|v| v.bulge).collect::<Vec<_>>();
+        polyline_segments(&self.points(true), &bulges)
+    }
+    /// Returns the effective `(starting, ending)` width of the segment that starts at vertex `i`.
+    /// Vertices without their own widths use the polyline's `constant_width`.  Panics if `i` is
+    /// out of bounds.
+    pub fn width_at_vertex(&self, i: usize) -> (f64, f64) {
+        let v = &self.vertices[i];
+        if v.starting_width == 0.0 && v.ending_width == 0.0 {
+            (self.constant_width, self.constant_width)
+        } else {
+            (v.starting_width, v.ending_width)
+        }
+    }
+    /// Removes vertices using the Douglas-Peucker algorithm: each run of straight segments is
+    /// reduced to the fewest vertices that keep the original within `tolerance`.  Returns the
+    /// number of vertices removed.  Vertices that start or end an arc segment are always kept.
+    pub fn simplify(&mut self, tolerance: f64) -> usize {
+        let original_count = self.vertices.len();
+        if original_count < 3 {
+            return 0;
+        }
+
+        let last = original_count - 1;
+        let mut keep = (0..original_count)
+            .map(|i| {
+                i == 0
+                    || i == last
+                    || self.vertices[i - 1].bulge != 0.0
+                    || self.vertices[i].bulge != 0.0
+            })
+            .collect::<Vec<_>>();
+        let anchors = (0..original_count).filter(|&i| keep[i]).collect::<Vec<_>>();
+        for run in anchors.windows(2) {
+            let mut pending = vec![(run[0], run[1])];
+            while let Some((start, end)) = pending.pop() {
+                let farthest = (start + 1..end)
+                    .map(|i| {
+                        let distance = distance_to_line(
+                            &self.vertices[i],
+                            &self.vertices[start],
+                            &self.vertices[end],
+                        );
+                        (i, distance)
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((i, distance)) = farthest {
+                    if distance > tolerance {
+                        keep[i] = true;
+                        pending.push((start, i));
+                        pending.push((i, end));
+                    }
+                }
+            }
+        }
+
+        let mut i = 0;
+        self.vertices.retain(|_| {
+            i += 1;
+            keep[i - 1]
+        });
+        original_count - self.vertices.len()
+    }
+}
+
+fn distance_to_line(v: &LwPolylineVertex, start: &LwPolylineVertex, end: &LwPolylineVertex) -> f64 {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return ((v.x - start.x).powi(2) + (v.y - start.y).powi(2)).sqrt();
+    }
+
+    ((v.x - start.x) * dy - (v.y - start.y) * dx).abs() / length
+}
+
 //------------------------------------------------------------------------------
 //                                                              LwPolylineVertex
 //------------------------------------------------------------------------------
@@ -128,6 +441,122 @@ pub struct LwPolylineVertex {
     pub bulge: f64,
 }
 
+//------------------------------------------------------------------------------
+//                                                                         MText
+//------------------------------------------------------------------------------
+impl MText {
+    /// Returns the full text with paragraph breaks as newlines and all other formatting codes,
+    /// e.g., font, color, and height changes, removed.  Stacked fractions are written with `/`.
+    pub fn plain_text(&self) -> String {
+        let mut raw = self.extended_text.concat();
+        raw.push_str(&self.text);
+
+        let mut result = String::new();
+        let mut chars = raw.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' => (),
+                '\\' => match chars.next() {
+                    Some('P') => result.push('\n'),
+                    Some('~') => result.push(' '),
+                    Some(c @ '\\') | Some(c @ '{') | Some(c @ '}') => result.push(c),
+                    Some('S') => {
+                        // stacked text, e.g., `\S1/2;` or `\S1^2;`
+                        for c in chars.by_ref().take_while(|c| *c != ';') {
+                            result.push(if c == '^' || c == '#' { '/' } else { c });
+                        }
+                    }
+                    Some('A') | Some('C') | Some('c') | Some('F') | Some('f') | Some('H')
+                    | Some('Q') | Some('T') | Some('W') | Some('p') => {
+                        // formatting code with an argument terminated by `;`
+                        for c in chars.by_ref() {
+                            if c == ';' {
+                                break;
+                            }
+                        }
+                    }
+                    Some(_) => (), // toggles like underline and overline
+                    None => result.push('\\'),
+                },
+                _ => result.push(c),
+            }
+        }
+
+        result
+    }
+    /// Approximates the MText with one single-line `Text` per paragraph, positioned using the
+    /// text height, line spacing factor, and attachment point.  Formatting codes are dropped, word
+    /// wrapping is not performed, and the MText is assumed to lie in the XY plane.
+    pub fn to_text_lines(&self) -> Vec<Text> {
+        let text = self.plain_text();
+        let lines = text.split('\n').collect::<Vec<_>>();
+        let rotation =
+            if self.x_axis_direction == Vector::x_axis() || self.x_axis_direction.length() == 0.0 {
+                self.rotation_angle
+            } else {
+                self.x_axis_direction
+                    .y
+                    .atan2(self.x_axis_direction.x)
+                    .to_degrees()
+            };
+        let (sin, cos) = rotation.to_radians().sin_cos();
+
+        // AutoCAD places single-spaced lines 5/3 of the text height apart
+        let height = self.initial_text_height;
+        let pitch = height * 5.0 / 3.0 * self.line_spacing_factor;
+        let total_height = height + pitch * (lines.len() - 1) as f64;
+        let first_baseline_drop = match self.attachment_point {
+            AttachmentPoint::TopLeft | AttachmentPoint::TopCenter | AttachmentPoint::TopRight => {
+                height
+            }
+            AttachmentPoint::MiddleLeft
+            | AttachmentPoint::MiddleCenter
+            | AttachmentPoint::MiddleRight => height - total_height / 2.0,
+            AttachmentPoint::BottomLeft
+            | AttachmentPoint::BottomCenter
+            | AttachmentPoint::BottomRight => height - total_height,
+        };
+        let justification = match self.attachment_point {
+            AttachmentPoint::TopLeft
+            | AttachmentPoint::MiddleLeft
+            | AttachmentPoint::BottomLeft => HorizontalTextJustification::Left,
+            AttachmentPoint::TopCenter
+            | AttachmentPoint::MiddleCenter
+            | AttachmentPoint::BottomCenter => HorizontalTextJustification::Center,
+            AttachmentPoint::TopRight
+            | AttachmentPoint::MiddleRight
+            | AttachmentPoint::BottomRight => HorizontalTextJustification::Right,
+        };
+
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let drop = first_baseline_drop + pitch * i as f64;
+                let location = Point::new(
+                    self.insertion_point.x + drop * sin,
+                    self.insertion_point.y - drop * cos,
+                    self.insertion_point.z,
+                );
+                Text {
+                    location: location.clone(),
+                    second_alignment_point: if justification == HorizontalTextJustification::Left {
+                        Point::origin()
+                    } else {
+                        location
+                    },
+                    text_height: height,
+                    value: String::from(*line),
+                    rotation,
+                    text_style_name: self.text_style_name.clone(),
+                    horizontal_text_justification: justification,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                    ModelPoint
 //------------------------------------------------------------------------------
@@ -138,6 +567,62 @@ impl ModelPoint {
             ..Default::default()
         }
     }
+    /// Returns the entities that draw this point's marker for the `$PDMODE` value `pdmode` in the
+    /// XY plane.  `pdsize` is the absolute marker size; zero and negative `$PDSIZE` values are
+    /// relative to the viewport and must be resolved by the caller.  Mode `0` produces a single
+    /// point entity and mode `1` produces nothing unless a circle or square is added.
+    pub fn marker_geometry(&self, pdmode: i16, pdsize: f64) -> Vec<Entity> {
+        let p = &self.location;
+        let half = pdsize.abs() / 2.0;
+        let offset = |dx: f64, dy: f64| Point::new(p.x + dx, p.y + dy, p.z);
+        let mut entities = vec![];
+        match pdmode & 0x1F {
+            0 => entities.push(ModelPoint::new(p.clone()).into()),
+            2 => {
+                entities.push(Entity::new_line(offset(-half, 0.0), offset(half, 0.0)));
+                entities.push(Entity::new_line(offset(0.0, -half), offset(0.0, half)));
+            }
+            3 => {
+                entities.push(Entity::new_line(offset(-half, -half), offset(half, half)));
+                entities.push(Entity::new_line(offset(-half, half), offset(half, -half)));
+            }
+            4 => entities.push(Entity::new_line(p.clone(), offset(0.0, half))),
+            _ => (),
+        }
+        if pdmode & 32 != 0 {
+            entities.push(Entity::new_circle(p.clone(), half));
+        }
+        if pdmode & 64 != 0 {
+            let corners = [
+                offset(-half, -half),
+                offset(half, -half),
+                offset(half, half),
+                offset(-half, half),
+            ];
+            for i in 0..corners.len() {
+                let next = (i + 1) % corners.len();
+                entities.push(Entity::new_line(corners[i].clone(), corners[next].clone()));
+            }
+        }
+
+        entities
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                             OrdinateDimension
+//------------------------------------------------------------------------------
+impl OrdinateDimension {
+    /// Returns the X or Y distance of the feature location from the dimension origin.
+    pub fn measurement(&self) -> Option<f64> {
+        let origin = &self.dimension_base.definition_point_1;
+        let feature = &self.definition_point_2;
+        if self.dimension_base.is_ordinate_x_type {
+            Some((feature.x - origin.x).abs())
+        } else {
+            Some((feature.y - origin.y).abs())
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -154,6 +639,83 @@ impl Polyline {
         let vertex_handle = drawing.next_handle();
         self.__vertices_and_handles.push((vertex, vertex_handle));
     }
+    /// Returns the vertex locations.  When `closed_wrap` is `true` and the polyline is closed, the
+    /// first location is repeated at the end.
+    pub fn points(&self, closed_wrap: bool) -> Vec<Point> {
+        let mut points = self
+            .vertices()
+            .map(|v| v.location.clone())
+            .collect::<Vec<_>>();
+        if closed_wrap && self.is_closed() && !points.is_empty() {
+            points.push(points[0].clone());
+        }
+
+        points
+    }
+    /// Returns each segment as `(start, end, bulge)`, including the segment from the last vertex
+    /// back to the first when the polyline is closed.
+    pub fn segments(&self) -> Vec<(Point, Point, f64)> {
+        let bulges = self.vertices().map(|v| v.bulge).collect::<Vec<_>>();
+        polyline_segments(&self.points(true), &bulges)
+    }
+    /// Returns the effective `(starting, ending)` width of the segment that starts at vertex `i`.
+    /// Vertices without their own widths use the polyline's default widths.  Panics if `i` is out
+    /// of bounds.
+    pub fn width_at_vertex(&self, i: usize) -> (f64, f64) {
+        let v = &self.__vertices_and_handles[i].0;
+        if v.starting_width == 0.0 && v.ending_width == 0.0 {
+            (self.default_starting_width, self.default_ending_width)
+        } else {
+            (v.starting_width, v.ending_width)
+        }
+    }
+    /// Returns the zero-based vertex indices of each face of a polyface mesh, or `None` if this
+    /// isn't a polyface mesh.  Triangular faces repeat their last index and hidden edges
+    /// (negative indices) are reported like visible ones.
+    pub fn faces(&self) -> Option<Vec<[usize; 4]>> {
+        if !self.is_polyface_mesh() {
+            return None;
+        }
+
+        let faces = self
+            .vertices()
+            .filter(|v| v.is_face_record())
+            .filter_map(|v| {
+                let mut face = [0; 4];
+                let mut last = None;
+                for (i, index) in [
+                    v.polyface_mesh_vertex_index1,
+                    v.polyface_mesh_vertex_index2,
+                    v.polyface_mesh_vertex_index3,
+                    v.polyface_mesh_vertex_index4,
+                ]
+                .iter()
+                .enumerate()
+                {
+                    let index = match index.abs() {
+                        0 => last?,
+                        index => index as usize - 1,
+                    };
+                    face[i] = index;
+                    last = Some(index);
+                }
+                Some(face)
+            })
+            .collect();
+        Some(faces)
+    }
+    /// Returns the `(m, n)` vertex counts of a 3D polygon mesh, or `None` if this isn't a polygon
+    /// mesh.  The mesh vertices are stored row by row, `n` vertices per row.
+    pub fn mesh_dimensions(&self) -> Option<(usize, usize)> {
+        if !self.is_3d_polygon_mesh() {
+            return None;
+        }
+
+        Some((
+            self.polygon_mesh_m_vertex_count.max(0) as usize,
+            self.polygon_mesh_n_vertex_count.max(0) as usize,
+        ))
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -175,6 +737,71 @@ impl ProxyEntity {
         self.__object_drawing_format =
             (version << 4) as u32 + (self.__object_drawing_format & 0xFFFF);
     }
+    /// Returns the proxy graphics with the `310` chunks joined.
+    pub fn graphics_data(&self) -> Vec<u8> {
+        self.graphics_data_string.concat()
+    }
+    /// Sets the proxy graphics and its size in bytes.
+    pub fn set_graphics_data(&mut self, data: &[u8]) {
+        self.graphics_data_string = data.chunks(127).map(|c| c.to_vec()).collect();
+        self.graphics_data_size = data.len() as i32;
+    }
+    /// Returns the original entity data with the `310` chunks joined.
+    pub fn entity_data(&self) -> Vec<u8> {
+        self.entity_data_string.concat()
+    }
+    /// Sets the original entity data and its size in bits.
+    pub fn set_entity_data(&mut self, data: &[u8]) {
+        self.entity_data_string = data.chunks(127).map(|c| c.to_vec()).collect();
+        self.entity_data_size = data.len() as i32 * 8;
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                               RadialDimension
+//------------------------------------------------------------------------------
+impl RadialDimension {
+    /// Returns the radius measured from the center to the definition point on the curve.
+    pub fn measurement(&self) -> Option<f64> {
+        Some(DimensionBase::distance(
+            &self.dimension_base.definition_point_1,
+            &self.definition_point_2,
+        ))
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                              RotatedDimension
+//------------------------------------------------------------------------------
+impl RotatedDimension {
+    /// Returns the distance between the extension line origins; rotated dimensions only measure
+    /// along `rotation_angle`.
+    pub fn measurement(&self) -> Option<f64> {
+        let p1 = &self.definition_point_2;
+        let p2 = &self.definition_point_3;
+        match self.dimension_base.dimension_type {
+            DimensionType::RotatedHorizontalOrVertical => {
+                let (sin, cos) = self.rotation_angle.to_radians().sin_cos();
+                Some(((p2.x - p1.x) * cos + (p2.y - p1.y) * sin).abs())
+            }
+            _ => Some(DimensionBase::distance(p1, p2)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                         Shape
+//------------------------------------------------------------------------------
+impl Shape {
+    /// Creates a new `Shape` placing the named shape from a loaded SHX file.
+    pub fn new(name: &str, location: Point, size: f64) -> Self {
+        Shape {
+            name: String::from(name),
+            location,
+            size,
+            ..Default::default()
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -197,6 +824,335 @@ impl Solid {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                        Spline
+//------------------------------------------------------------------------------
+impl Spline {
+    /// Returns the first weight, or `1.0` if the spline has no weights.
+    #[deprecated(note = "splines have one weight per control point; use `weights` instead")]
+    pub fn weight(&self) -> f64 {
+        self.weights.first().copied().unwrap_or(1.0)
+    }
+    /// Ensures the knot and weight counts agree with the control points and degree.  Splines
+    /// defined only by fit points are not checked.
+    pub fn validate(&self) -> DxfResult<()> {
+        if self.control_points.is_empty() {
+            return Ok(());
+        }
+
+        let expected_knots = self.control_points.len() + self.degree_of_curve.max(0) as usize + 1;
+        if self.knot_values.len() != expected_knots {
+            return Err(DxfError::ValidationError(format!(
+                "a spline of degree {} with {} control points requires {} knot values but has {}",
+                self.degree_of_curve,
+                self.control_points.len(),
+                expected_knots,
+                self.knot_values.len()
+            )));
+        }
+
+        if self.is_rational() && self.weights.len() != self.control_points.len() {
+            return Err(DxfError::ValidationError(format!(
+                "a rational spline with {} control points requires {} weights but has {}",
+                self.control_points.len(),
+                self.control_points.len(),
+                self.weights.len()
+            )));
+        }
+
+        Ok(())
+    }
+    /// Creates a spline of the specified degree that passes through `points`, using chord-length
+    /// parameters over `[0, 1]` and averaged knots.  The degree is reduced when there are too few
+    /// points, and fewer than two points produce a spline with only fit points.  Consecutive
+    /// duplicate points are dropped since they'd give two rows of the system the same parameter.
+    pub fn from_fit_points(points: &[Point], degree: usize) -> Spline {
+        let mut points = points.to_vec();
+        points.dedup();
+        let points = &points[..];
+        let mut spline = Spline {
+            fit_points: points.to_vec(),
+            ..Default::default()
+        };
+        if points.len() < 2 {
+            return spline;
+        }
+
+        let count = points.len();
+        let degree = degree.clamp(1, count - 1);
+        let parameters = chord_length_parameters(points);
+
+        // averaged knots, clamped at both ends
+        let mut knots = vec![0.0; degree + 1];
+        for j in 1..count - degree {
+            let sum: f64 = parameters[j..j + degree].iter().sum();
+            knots.push(sum / degree as f64);
+        }
+        knots.extend(vec![1.0; degree + 1]);
+
+        // solve `basis * control_points = points` for each coordinate
+        let mut matrix = vec![vec![0.0; count]; count];
+        for (row, u) in matrix.iter_mut().zip(&parameters) {
+            let span = find_knot_span(count - 1, degree, *u, &knots);
+            for (j, n) in basis_functions(span, *u, degree, &knots).iter().enumerate() {
+                row[span - degree + j] = *n;
+            }
+        }
+        let rhs = points.iter().map(|p| [p.x, p.y, p.z]).collect::<Vec<_>>();
+        let solution = solve_linear_system(matrix, rhs);
+
+        spline.degree_of_curve = degree as i32;
+        spline.knot_values = knots;
+        spline.control_points = solution
+            .iter()
+            .map(|c| Point::new(c[0], c[1], c[2]))
+            .collect();
+        spline
+    }
+    /// Evaluates the spline at parameter `u`, which is clamped to the range of the knot values.
+    /// Returns `None` if the spline has no control points or an inconsistent knot vector.
+    pub fn point_at(&self, u: f64) -> Option<Point> {
+        if self.control_points.is_empty() || self.validate().is_err() {
+            return None;
+        }
+
+        let degree = self.degree_of_curve.max(0) as usize;
+        let last = self.control_points.len() - 1;
+        let knots = &self.knot_values;
+        let u = u.clamp(knots[degree], knots[last + 1]);
+        let span = find_knot_span(last, degree, u, knots);
+        let basis = basis_functions(span, u, degree, knots);
+        let (mut x, mut y, mut z, mut w) = (0.0, 0.0, 0.0, 0.0);
+        for (j, n) in basis.iter().enumerate() {
+            let index = span - degree + j;
+            let weight = if self.is_rational() {
+                self.weights[index]
+            } else {
+                1.0
+            };
+            let p = &self.control_points[index];
+            x += n * weight * p.x;
+            y += n * weight * p.y;
+            z += n * weight * p.z;
+            w += n * weight;
+        }
+
+        if w == 0.0 {
+            return None;
+        }
+
+        Some(Point::new(x / w, y / w, z / w))
+    }
+    fn combine_points(&mut self) {
+        combine_points_3(
+            &mut self.__control_point_x,
+            &mut self.__control_point_y,
+            &mut self.__control_point_z,
+            &mut self.control_points,
+            Point::new,
+        );
+        combine_points_3(
+            &mut self.__fit_point_x,
+            &mut self.__fit_point_y,
+            &mut self.__fit_point_z,
+            &mut self.fit_points,
+            Point::new,
+        );
+    }
+}
+
+/// Returns the normalized cumulative distance to each point, or evenly spaced values if all the
+/// points coincide.
+fn chord_length_parameters(points: &[Point]) -> Vec<f64> {
+    let mut parameters = vec![0.0];
+    for pair in points.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let distance = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2) + (b.z - a.z).powi(2)).sqrt();
+        parameters.push(parameters[parameters.len() - 1] + distance);
+    }
+
+    let total = parameters[parameters.len() - 1];
+    let last = (points.len() - 1) as f64;
+    parameters
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            if total == 0.0 {
+                i as f64 / last
+            } else {
+                d / total
+            }
+        })
+        .collect()
+}
+
+/// Returns the index of the knot span containing `u`, where `last` is the index of the last
+/// control point.
+fn find_knot_span(last: usize, degree: usize, u: f64, knots: &[f64]) -> usize {
+    if u >= knots[last + 1] {
+        return last;
+    }
+
+    let mut span = degree;
+    while span < last && u >= knots[span + 1] {
+        span += 1;
+    }
+
+    span
+}
+
+/// Returns the `degree + 1` non-zero basis functions at `u` in the specified knot span.
+fn basis_functions(span: usize, u: f64, degree: usize, knots: &[f64]) -> Vec<f64> {
+    let mut values = vec![0.0; degree + 1];
+    let mut left = vec![0.0; degree + 1];
+    let mut right = vec![0.0; degree + 1];
+    values[0] = 1.0;
+    for j in 1..=degree {
+        left[j] = u - knots[span + 1 - j];
+        right[j] = knots[span + j] - u;
+        let mut saved = 0.0;
+        for r in 0..j {
+            let denominator = right[r + 1] + left[j - r];
+            let temp = if denominator == 0.0 {
+                0.0
+            } else {
+                values[r] / denominator
+            };
+            values[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        values[j] = saved;
+    }
+
+    values
+}
+
+/// Solves `matrix * x = rhs` by Gaussian elimination with partial pivoting.
+fn solve_linear_system(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<[f64; 3]>) -> Vec<[f64; 3]> {
+    let size = matrix.len();
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))
+            .unwrap_or(col);
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+        let divisor = matrix[col][col];
+        if divisor == 0.0 {
+            continue;
+        }
+
+        let pivot_row = matrix[col].clone();
+        let pivot_rhs = rhs[col];
+        for row in col + 1..size {
+            let factor = matrix[row][col] / divisor;
+            if factor == 0.0 {
+                continue;
+            }
+
+            for (value, pivot) in matrix[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot;
+            }
+            for (value, pivot) in rhs[row].iter_mut().zip(&pivot_rhs) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+
+    let mut solution = vec![[0.0; 3]; size];
+    for row in (0..size).rev() {
+        for k in 0..3 {
+            let mut value = rhs[row][k];
+            for col in row + 1..size {
+                value -= matrix[row][col] * solution[col][k];
+            }
+            solution[row][k] = if matrix[row][row] == 0.0 {
+                0.0
+            } else {
+                value / matrix[row][row]
+            };
+        }
+    }
+
+    solution
+}
+
+//------------------------------------------------------------------------------
+//                                                                         Table
+//------------------------------------------------------------------------------
+impl Table {
+    /// Returns the cell at the specified zero-based row and column.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&TableCell> {
+        let column_count = self.column_count.max(0) as usize;
+        if column >= column_count {
+            return None;
+        }
+
+        self.cells.get(row * column_count + column)
+    }
+    /// Returns the text of the cell at the specified zero-based row and column.
+    pub fn cell_text(&self, row: usize, column: usize) -> Option<&str> {
+        self.cell(row, column).map(|c| c.text.as_str())
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                     TableCell
+//------------------------------------------------------------------------------
+/// Represents a single cell of a `Table`.  Only the cell's layout, text, and block reference are
+/// read; per-cell style overrides and field objects are dropped.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct TableCell {
+    /// `1` for a text cell, `2` for a block cell.
+    pub cell_type: i16,
+    pub flags: i16,
+    pub is_merged: bool,
+    pub is_auto_fit: bool,
+    /// The number of columns spanned by a merged cell.
+    pub merged_width: i16,
+    /// The number of rows spanned by a merged cell.
+    pub merged_height: i16,
+    pub override_flags: i32,
+    pub virtual_edge_flag: i16,
+    pub rotation: f64,
+    pub text: String,
+    pub text_style_name: String,
+    pub text_height: f64,
+    pub alignment: i16,
+    pub block_handle: Handle,
+}
+
+impl Default for TableCell {
+    fn default() -> Self {
+        TableCell {
+            cell_type: 1,
+            flags: 0,
+            is_merged: false,
+            is_auto_fit: false,
+            merged_width: 1,
+            merged_height: 1,
+            override_flags: 0,
+            virtual_edge_flag: 0,
+            rotation: 0.0,
+            text: String::new(),
+            text_style_name: String::from("STANDARD"),
+            text_height: 0.18,
+            alignment: 1,
+            block_handle: Handle::empty(),
+        }
+    }
+}
+
+impl TableCell {
+    /// Creates a new text `TableCell`.
+    pub fn new(text: &str) -> Self {
+        TableCell {
+            text: String::from(text),
+            ..Default::default()
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                         Trace
 //------------------------------------------------------------------------------
@@ -227,12 +1183,63 @@ impl Vertex {
             ..Default::default()
         }
     }
+    /// A polyface mesh face record only holds vertex indices; its location isn't a point.
+    fn is_face_record(&self) -> bool {
+        self.is_polyface_mesh_vertex() && !self.is_3d_polygon_mesh()
+    }
 }
 
 //------------------------------------------------------------------------------
-//                                                                    EntityType
+//                                                                       Wipeout
 //------------------------------------------------------------------------------
-impl EntityType {
+impl Wipeout {
+    /// Creates a new `Wipeout` masking the polygon defined by the specified boundary vertices.
+    pub fn new(boundary: Vec<Point>) -> Self {
+        let mut wipeout = Wipeout {
+            clipping_type: ImageClippingBoundaryType::Polygonal,
+            clipping_vertices: boundary,
+            ..Default::default()
+        };
+        wipeout.set_show_image(true);
+        wipeout.set_use_clipping_boundary(true);
+        wipeout
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                                    EntityType
+//------------------------------------------------------------------------------
+impl EntityType {
+    /// Recomputes the value of a dimension from its definition points, ignoring any stored text.
+    /// Returns `None` for angular dimensions and non-dimension entities.
+    pub fn dimension_measurement(&self) -> Option<f64> {
+        match self {
+            EntityType::RotatedDimension(ref dim) => dim.measurement(),
+            EntityType::RadialDimension(ref dim) => dim.measurement(),
+            EntityType::DiameterDimension(ref dim) => dim.measurement(),
+            EntityType::AngularThreePointDimension(ref dim) => dim.measurement(),
+            EntityType::OrdinateDimension(ref dim) => dim.measurement(),
+            _ => None,
+        }
+    }
+    /// Returns the extrusion thickness of entity types that have one.
+    pub(crate) fn thickness_mut(&mut self) -> Option<&mut f64> {
+        match self {
+            EntityType::Arc(ref mut e) => Some(&mut e.thickness),
+            EntityType::Attribute(ref mut e) => Some(&mut e.thickness),
+            EntityType::AttributeDefinition(ref mut e) => Some(&mut e.thickness),
+            EntityType::Circle(ref mut e) => Some(&mut e.thickness),
+            EntityType::Line(ref mut e) => Some(&mut e.thickness),
+            EntityType::LwPolyline(ref mut e) => Some(&mut e.thickness),
+            EntityType::ModelPoint(ref mut e) => Some(&mut e.thickness),
+            EntityType::Polyline(ref mut e) => Some(&mut e.thickness),
+            EntityType::Shape(ref mut e) => Some(&mut e.thickness),
+            EntityType::Solid(ref mut e) => Some(&mut e.thickness),
+            EntityType::Text(ref mut e) => Some(&mut e.thickness),
+            EntityType::Trace(ref mut e) => Some(&mut e.thickness),
+            _ => None,
+        }
+    }
     fn apply_dimension_code_pair(&mut self, pair: &CodePair) -> DxfResult<bool> {
         match *self {
             EntityType::RotatedDimension(ref mut dim) => match pair.code {
@@ -392,6 +1399,12 @@ impl EntityCommon {
 //------------------------------------------------------------------------------
 //                                                                        Entity
 //------------------------------------------------------------------------------
+impl From<EntityType> for Entity {
+    fn from(specific: EntityType) -> Self {
+        Entity::new(specific)
+    }
+}
+
 impl Entity {
     /// Creates a new `Entity` with the default common values.
     pub fn new(specific: EntityType) -> Self {
@@ -400,11 +1413,914 @@ impl Entity {
             specific,
         }
     }
+    /// Creates a new `Line` entity from `p1` to `p2`.
+    pub fn new_line(p1: Point, p2: Point) -> Self {
+        Line::new(p1, p2).into()
+    }
+    /// Creates a new `Circle` entity.
+    pub fn new_circle(center: Point, radius: f64) -> Self {
+        Circle::new(center, radius).into()
+    }
+    /// Creates a new single-line `Text` entity at `location`.
+    pub fn new_text(location: Point, text_height: f64, value: &str) -> Self {
+        Text {
+            location,
+            text_height,
+            value: String::from(value),
+            ..Default::default()
+        }
+        .into()
+    }
+    /// Returns the values shared by all entity types, e.g., layer, color, and handle.
+    pub fn common(&self) -> &EntityCommon {
+        &self.common
+    }
+    /// Returns the mutable values shared by all entity types.
+    pub fn common_mut(&mut self) -> &mut EntityCommon {
+        &mut self.common
+    }
+    /// Returns the handle of the entity.
+    pub fn handle(&self) -> Handle {
+        self.common.handle
+    }
+    /// Sets the handle of the entity.
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.common.handle = handle;
+    }
+    /// Returns a copy of the entity placed on `layer`.  The copy and its vertices or attributes
+    /// are given new handles when added to a `Drawing`.
+    pub fn clone_to_layer(&self, layer: &str) -> Entity {
+        let mut copy = self.clone();
+        copy.common.layer = String::from(layer);
+        copy.common.handle = Handle::empty();
+        match copy.specific {
+            EntityType::Insert(ref mut ins) => {
+                for a in ins.__attributes_and_handles.iter_mut() {
+                    a.1 = AUTO_REPLACE_HANDLE;
+                }
+            }
+            EntityType::Polyline(ref mut poly) => {
+                for v in poly.__vertices_and_handles.iter_mut() {
+                    v.1 = AUTO_REPLACE_HANDLE;
+                }
+            }
+            _ => (),
+        }
+
+        copy
+    }
+    /// Returns the handle of the entity's owner.
+    pub fn owner_handle(&self) -> Handle {
+        self.common.__owner_handle
+    }
+    /// Sets the handle of the entity's owner.
+    pub fn set_owner_handle(&mut self, handle: Handle) {
+        self.common.__owner_handle = handle;
+    }
+    /// Returns the scale applied to the entity's line type pattern; the entity's own scale (code
+    /// `48`) multiplied by the drawing's global `$LTSCALE`.
+    pub fn effective_linetype_scale(&self, drawing: &Drawing) -> f64 {
+        self.common.line_type_scale * drawing.header.line_type_scale
+    }
+    /// Returns the entities measured by this dimension, as recorded by the drawing's `DIMASSOC`
+    /// objects.  Non-associative dimensions and other entity types return an empty list.
+    pub fn associated_entities<'a>(&self, drawing: &'a Drawing) -> Vec<&'a Entity> {
+        let mut entities = vec![];
+        if self.common.handle.is_empty() {
+            return entities;
+        }
+
+        for assoc in drawing.objects().filter_map(|o| o.as_dimension_assoc()) {
+            if assoc.__dimension_handle != self.common.handle {
+                continue;
+            }
+
+            for item in [
+                assoc.main_object(drawing),
+                assoc.intersection_object(drawing),
+            ]
+            .iter()
+            .flatten()
+            {
+                if let DrawingItem::Entity(entity) = item {
+                    if !entities.iter().any(|e: &&Entity| std::ptr::eq(*e, *entity)) {
+                        entities.push(*entity);
+                    }
+                }
+            }
+        }
+
+        entities
+    }
+    /// Returns the `DimensionBase` shared by all dimension entities, or `None` if the entity isn't a
+    /// dimension.
+    pub fn dimension_base(&self) -> Option<&DimensionBase> {
+        match self.specific {
+            EntityType::RotatedDimension(ref d) => Some(&d.dimension_base),
+            EntityType::RadialDimension(ref d) => Some(&d.dimension_base),
+            EntityType::DiameterDimension(ref d) => Some(&d.dimension_base),
+            EntityType::AngularThreePointDimension(ref d) => Some(&d.dimension_base),
+            EntityType::OrdinateDimension(ref d) => Some(&d.dimension_base),
+            _ => None,
+        }
+    }
+    /// Returns the mutable `DimensionBase` shared by all dimension entities, or `None` if the
+    /// entity isn't a dimension.
+    pub fn dimension_base_mut(&mut self) -> Option<&mut DimensionBase> {
+        match self.specific {
+            EntityType::RotatedDimension(ref mut d) => Some(&mut d.dimension_base),
+            EntityType::RadialDimension(ref mut d) => Some(&mut d.dimension_base),
+            EntityType::DiameterDimension(ref mut d) => Some(&mut d.dimension_base),
+            EntityType::AngularThreePointDimension(ref mut d) => Some(&mut d.dimension_base),
+            EntityType::OrdinateDimension(ref mut d) => Some(&mut d.dimension_base),
+            _ => None,
+        }
+    }
+    /// Returns the entity's extrusion direction (code `210`).  Entities without one, or with an
+    /// unset (zero length) one, return the default of `(0, 0, 1)`.
+    pub fn extrusion_direction(&self) -> Vector {
+        let direction = match self.specific {
+            EntityType::Arc(ref e) => &e.normal,
+            EntityType::ArcAlignedText(ref e) => &e.extrusion_direction,
+            EntityType::AttributeDefinition(ref e) => &e.normal,
+            EntityType::Attribute(ref e) => &e.normal,
+            EntityType::Circle(ref e) => &e.normal,
+            EntityType::Ellipse(ref e) => &e.normal,
+            EntityType::Insert(ref e) => &e.extrusion_direction,
+            EntityType::Leader(ref e) => &e.normal,
+            EntityType::Line(ref e) => &e.extrusion_direction,
+            EntityType::LwPolyline(ref e) => &e.extrusion_direction,
+            EntityType::MLine(ref e) => &e.normal,
+            EntityType::MText(ref e) => &e.extrusion_direction,
+            EntityType::ModelPoint(ref e) => &e.extrusion_direction,
+            EntityType::Polyline(ref e) => &e.normal,
+            EntityType::RText(ref e) => &e.extrusion_direction,
+            EntityType::Shape(ref e) => &e.extrusion_direction,
+            EntityType::Solid(ref e) => &e.extrusion_direction,
+            EntityType::Spline(ref e) => &e.normal,
+            EntityType::Text(ref e) => &e.normal,
+            EntityType::Tolerance(ref e) => &e.extrusion_direction,
+            EntityType::Trace(ref e) => &e.extrusion_direction,
+            EntityType::DgnUnderlay(ref e) => &e.normal,
+            EntityType::DwfUnderlay(ref e) => &e.normal,
+            EntityType::PdfUnderlay(ref e) => &e.normal,
+            _ => match self.dimension_base() {
+                Some(dimension_base) => &dimension_base.normal,
+                None => return Vector::z_axis(),
+            },
+        };
+        if direction.length() == 0.0 {
+            Vector::z_axis()
+        } else {
+            direction.clone()
+        }
+    }
     /// Ensures all entity values are valid.
     pub fn normalize(&mut self) {
         self.common.normalize();
         // no entity-specific values to set
     }
+    /// Returns the `(min, max)` corners of the entity's axis-aligned bounding box in world
+    /// coordinates, or `None` for unbounded entities and entities without geometry.  Curves that
+    /// aren't in the XY plane, ellipses, and polyline bulges use a conservative box; text and
+    /// inserts are reduced to their insertion point.
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        fn sphere(center: Point, radius: f64) -> Vec<Point> {
+            vec![
+                Point::new(center.x - radius, center.y - radius, center.z - radius),
+                Point::new(center.x + radius, center.y + radius, center.z + radius),
+            ]
+        }
+        fn polyline_points(vertices: &[(Point, f64)], is_closed: bool) -> Vec<Point> {
+            let mut points = vertices.iter().map(|v| v.0.clone()).collect::<Vec<_>>();
+            let segment_count = if is_closed {
+                vertices.len()
+            } else {
+                vertices.len().saturating_sub(1)
+            };
+            for i in 0..segment_count {
+                let (ref start, bulge) = vertices[i];
+                let end = &vertices[(i + 1) % vertices.len()].0;
+                if bulge != 0.0 {
+                    // the arc never strays further from the chord's midpoint than its sagitta or half the chord
+                    let half_chord = Vector::new(end.x - start.x, end.y - start.y, end.z - start.z)
+                        .length()
+                        / 2.0;
+                    let radius = bulge.abs().max(1.0) * half_chord;
+                    let mid = Point::new(
+                        (start.x + end.x) / 2.0,
+                        (start.y + end.y) / 2.0,
+                        (start.z + end.z) / 2.0,
+                    );
+                    points.extend(sphere(mid, radius));
+                }
+            }
+            points
+        }
+        let points = match self.specific {
+            EntityType::Arc(ref arc) => {
+                let center = arc.center_wcs();
+                if arc.normal == Vector::z_axis() {
+                    let start = arc.start_angle;
+                    let mut end = arc.end_angle;
+                    while end < start {
+                        end += 360.0;
+                    }
+                    let mut angles = vec![start, end];
+                    angles.extend(
+                        (0..8)
+                            .map(|quadrant| f64::from(quadrant) * 90.0)
+                            .filter(|a| *a > start && *a < end),
+                    );
+                    angles
+                        .iter()
+                        .map(|a| {
+                            let (sin, cos) = a.to_radians().sin_cos();
+                            Point::new(
+                                center.x + arc.radius * cos,
+                                center.y + arc.radius * sin,
+                                center.z,
+                            )
+                        })
+                        .collect()
+                } else {
+                    sphere(center, arc.radius)
+                }
+            }
+            EntityType::Circle(ref circle) => {
+                let center = circle.center_wcs();
+                if circle.normal == Vector::z_axis() {
+                    vec![
+                        Point::new(center.x - circle.radius, center.y - circle.radius, center.z),
+                        Point::new(center.x + circle.radius, center.y + circle.radius, center.z),
+                    ]
+                } else {
+                    sphere(center, circle.radius)
+                }
+            }
+            EntityType::Ellipse(ref ellipse) => {
+                sphere(ellipse.center.clone(), ellipse.major_axis.length())
+            }
+            EntityType::Face3D(ref face) => vec![
+                face.first_corner.clone(),
+                face.second_corner.clone(),
+                face.third_corner.clone(),
+                face.fourth_corner.clone(),
+            ],
+            EntityType::Insert(ref insert) => vec![insert.location.clone()],
+            EntityType::Leader(ref leader) => leader.vertices.clone(),
+            EntityType::Line(ref line) => vec![line.p1.clone(), line.p2.clone()],
+            EntityType::LwPolyline(ref poly) => {
+                let vertices = poly
+                    .vertices
+                    .iter()
+                    .map(|v| (Point::new(v.x, v.y, 0.0), v.bulge))
+                    .collect::<Vec<_>>();
+                polyline_points(&vertices, poly.is_closed())
+                    .iter()
+                    .map(|p| p.to_wcs_with(&poly.extrusion_direction))
+                    .collect()
+            }
+            EntityType::ModelPoint(ref point) => vec![point.location.clone()],
+            EntityType::MText(ref mtext) => vec![mtext.insertion_point.clone()],
+            EntityType::Polyline(ref poly) => {
+                let is_polyface_mesh = poly.is_polyface_mesh();
+                let vertices = poly
+                    .vertices()
+                    .filter(|v| !is_polyface_mesh || !v.is_face_record())
+                    .map(|v| (v.location.clone(), v.bulge))
+                    .collect::<Vec<_>>();
+                polyline_points(&vertices, poly.is_closed())
+            }
+            EntityType::Solid(ref solid) => vec![
+                solid.first_corner.clone(),
+                solid.second_corner.clone(),
+                solid.third_corner.clone(),
+                solid.fourth_corner.clone(),
+            ],
+            EntityType::Spline(ref spline) => spline
+                .control_points
+                .iter()
+                .chain(spline.fit_points.iter())
+                .cloned()
+                .collect(),
+            EntityType::Text(ref text) => vec![text.location.clone()],
+            EntityType::Trace(ref trace) => vec![
+                trace.first_corner.clone(),
+                trace.second_corner.clone(),
+                trace.third_corner.clone(),
+                trace.fourth_corner.clone(),
+            ],
+            _ => vec![],
+        };
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold((first.clone(), first), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Point::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        }))
+    }
+    /// Moves the entity by `delta`.
+    pub fn translate(&mut self, delta: Vector) {
+        self.transform(&TransformationMatrix::translation(
+            delta.x, delta.y, delta.z,
+        ));
+    }
+    /// Rotates the entity about `center` in the XY plane by `angle` degrees counter-clockwise.
+    pub fn rotate(&mut self, center: Point, angle: f64) {
+        self.transform(&Entity::about_point(
+            &center,
+            TransformationMatrix::rotation_about_z(angle),
+        ));
+    }
+    /// Uniformly scales the entity about `center`.
+    pub fn scale(&mut self, center: Point, factor: f64) {
+        self.transform(&Entity::about_point(
+            &center,
+            TransformationMatrix::scale(factor, factor, factor),
+        ));
+    }
+    /// Mirrors the entity across the line through `p1` and `p2` in the XY plane.  Arcs keep their
+    /// counter-clockwise direction and text stays readable.
+    pub fn mirror(&mut self, p1: Point, p2: Point) {
+        let (sin, cos) = (2.0 * (p2.y - p1.y).atan2(p2.x - p1.x)).sin_cos();
+        let reflection = TransformationMatrix {
+            m11: cos,
+            m12: sin,
+            m21: sin,
+            m22: -cos,
+            ..TransformationMatrix::identity()
+        };
+        self.transform(&Entity::about_point(&p1, reflection));
+    }
+    /// Applies the transformation to the entity's geometry.  All coordinates are treated as world coordinates and
+    /// radii, heights, and widths are scaled by the length of the transformed X axis.
+    pub fn transform(&mut self, m: &TransformationMatrix) {
+        let tp = |p: &mut Point| *p = m.transform_point(p);
+        let tv = |v: &mut Vector| *v = m.transform_vector(v);
+        let x_axis = m.transform_vector(&Vector::x_axis());
+        let y_axis = m.transform_vector(&Vector::y_axis());
+        let z_axis = m.transform_vector(&Vector::z_axis());
+        let scale = x_axis.length();
+        let rotation = x_axis.y.atan2(x_axis.x).to_degrees();
+        let is_mirrored = x_axis.x * y_axis.y - x_axis.y * y_axis.x < 0.0;
+        match self.specific {
+            EntityType::Arc(ref mut arc) => {
+                let start = m.transform_point(&Entity::point_on_circle(
+                    &arc.center,
+                    arc.radius,
+                    arc.start_angle,
+                ));
+                let end = m.transform_point(&Entity::point_on_circle(
+                    &arc.center,
+                    arc.radius,
+                    arc.end_angle,
+                ));
+                tp(&mut arc.center);
+                arc.radius *= scale;
+                let start_angle = Entity::angle_from_center(&arc.center, &start);
+                let end_angle = Entity::angle_from_center(&arc.center, &end);
+                if is_mirrored {
+                    arc.start_angle = end_angle;
+                    arc.end_angle = start_angle;
+                } else {
+                    arc.start_angle = start_angle;
+                    arc.end_angle = end_angle;
+                }
+            }
+            EntityType::Attribute(ref mut att) => {
+                Entity::transform_text(
+                    m,
+                    &mut att.location,
+                    &mut att.second_alignment_point,
+                    &mut att.text_height,
+                    &mut att.rotation,
+                );
+            }
+            EntityType::AttributeDefinition(ref mut att) => {
+                Entity::transform_text(
+                    m,
+                    &mut att.location,
+                    &mut att.second_alignment_point,
+                    &mut att.text_height,
+                    &mut att.rotation,
+                );
+            }
+            EntityType::Circle(ref mut circle) => {
+                tp(&mut circle.center);
+                circle.radius *= scale;
+            }
+            EntityType::RotatedDimension(ref mut dim) => {
+                Entity::transform_dimension_base(m, &mut dim.dimension_base);
+                tp(&mut dim.insertion_point);
+                tp(&mut dim.definition_point_2);
+                tp(&mut dim.definition_point_3);
+                dim.rotation_angle += rotation;
+            }
+            EntityType::RadialDimension(ref mut dim) => {
+                Entity::transform_dimension_base(m, &mut dim.dimension_base);
+                tp(&mut dim.definition_point_2);
+            }
+            EntityType::DiameterDimension(ref mut dim) => {
+                Entity::transform_dimension_base(m, &mut dim.dimension_base);
+                tp(&mut dim.definition_point_2);
+            }
+            EntityType::AngularThreePointDimension(ref mut dim) => {
+                Entity::transform_dimension_base(m, &mut dim.dimension_base);
+                tp(&mut dim.definition_point_2);
+                tp(&mut dim.definition_point_3);
+                tp(&mut dim.definition_point_4);
+                tp(&mut dim.definition_point_5);
+            }
+            EntityType::OrdinateDimension(ref mut dim) => {
+                Entity::transform_dimension_base(m, &mut dim.dimension_base);
+                tp(&mut dim.definition_point_2);
+                tp(&mut dim.definition_point_3);
+            }
+            EntityType::Ellipse(ref mut ellipse) => {
+                tp(&mut ellipse.center);
+                tv(&mut ellipse.major_axis);
+                if is_mirrored {
+                    let start = ellipse.start_parameter;
+                    ellipse.start_parameter = std::f64::consts::PI * 2.0 - ellipse.end_parameter;
+                    ellipse.end_parameter = std::f64::consts::PI * 2.0 - start;
+                }
+            }
+            EntityType::Face3D(ref mut face) => {
+                tp(&mut face.first_corner);
+                tp(&mut face.second_corner);
+                tp(&mut face.third_corner);
+                tp(&mut face.fourth_corner);
+            }
+            EntityType::Helix(ref mut helix) => {
+                tp(&mut helix.axis_base_point);
+                tp(&mut helix.start_point);
+                tv(&mut helix.axis_vector);
+                helix.radius *= scale;
+                helix.turn_height *= scale;
+                for p in helix.spline.control_points.iter_mut() {
+                    tp(p);
+                }
+                for p in helix.spline.fit_points.iter_mut() {
+                    tp(p);
+                }
+            }
+            EntityType::Image(ref mut image) => {
+                tp(&mut image.location);
+                tv(&mut image.u_vector);
+                tv(&mut image.v_vector);
+            }
+            EntityType::Insert(ref mut ins) => {
+                tp(&mut ins.location);
+                ins.x_scale_factor *= x_axis.length();
+                ins.y_scale_factor *= y_axis.length();
+                ins.z_scale_factor *= z_axis.length();
+                if is_mirrored {
+                    ins.y_scale_factor = -ins.y_scale_factor;
+                }
+                ins.rotation += rotation;
+                ins.column_spacing *= scale;
+                ins.row_spacing *= scale;
+                for att in ins.attributes_mut() {
+                    Entity::transform_text(
+                        m,
+                        &mut att.location,
+                        &mut att.second_alignment_point,
+                        &mut att.text_height,
+                        &mut att.rotation,
+                    );
+                }
+            }
+            EntityType::Leader(ref mut leader) => {
+                for v in leader.vertices.iter_mut() {
+                    tp(v);
+                }
+            }
+            EntityType::Light(ref mut light) => {
+                tp(&mut light.position);
+                tp(&mut light.target_location);
+            }
+            EntityType::Line(ref mut line) => {
+                tp(&mut line.p1);
+                tp(&mut line.p2);
+            }
+            EntityType::LwPolyline(ref mut poly) => {
+                for v in poly.vertices.iter_mut() {
+                    let p = m.transform_point(&Point::new(v.x, v.y, 0.0));
+                    v.x = p.x;
+                    v.y = p.y;
+                    v.starting_width *= scale;
+                    v.ending_width *= scale;
+                    if is_mirrored {
+                        v.bulge = -v.bulge;
+                    }
+                }
+            }
+            EntityType::MLine(ref mut mline) => {
+                tp(&mut mline.start_point);
+                for v in mline.vertices.iter_mut() {
+                    tp(v);
+                }
+                for v in mline.segment_directions.iter_mut() {
+                    tv(v);
+                }
+                for v in mline.miter_directions.iter_mut() {
+                    tv(v);
+                }
+            }
+            EntityType::MText(ref mut mtext) => {
+                tp(&mut mtext.insertion_point);
+                tv(&mut mtext.x_axis_direction);
+                mtext.initial_text_height *= scale;
+                mtext.reference_rectangle_width *= scale;
+                if is_mirrored {
+                    let d = &mtext.x_axis_direction;
+                    let angle = Entity::readable_angle(d.y.atan2(d.x).to_degrees());
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    mtext.x_axis_direction = Vector::new(cos * d.length(), sin * d.length(), d.z);
+                    mtext.rotation_angle = angle;
+                } else {
+                    mtext.rotation_angle += rotation;
+                }
+            }
+            EntityType::ModelPoint(ref mut point) => {
+                tp(&mut point.location);
+            }
+            EntityType::Ole2Frame(ref mut frame) => {
+                tp(&mut frame.upper_left_corner);
+                tp(&mut frame.lower_right_corner);
+            }
+            EntityType::Polyline(ref mut poly) => {
+                for v in poly.vertices_mut() {
+                    tp(&mut v.location);
+                }
+            }
+            EntityType::Ray(ref mut ray) => {
+                tp(&mut ray.start_point);
+                ray.unit_direction_vector =
+                    m.transform_vector(&ray.unit_direction_vector).normalize();
+            }
+            EntityType::RText(ref mut rtext) => {
+                tp(&mut rtext.insertion_point);
+                rtext.text_height *= scale;
+                rtext.rotation_angle += rotation;
+            }
+            EntityType::Section(ref mut section) => {
+                for v in section.vertices.iter_mut() {
+                    tp(v);
+                }
+                for v in section.back_line_vertices.iter_mut() {
+                    tp(v);
+                }
+            }
+            EntityType::Shape(ref mut shape) => {
+                tp(&mut shape.location);
+                shape.size *= scale;
+                shape.rotation_angle += rotation;
+            }
+            EntityType::Solid(ref mut solid) => {
+                tp(&mut solid.first_corner);
+                tp(&mut solid.second_corner);
+                tp(&mut solid.third_corner);
+                tp(&mut solid.fourth_corner);
+            }
+            EntityType::Spline(ref mut spline) => {
+                for p in spline.control_points.iter_mut() {
+                    tp(p);
+                }
+                for p in spline.fit_points.iter_mut() {
+                    tp(p);
+                }
+            }
+            EntityType::Table(ref mut table) => {
+                tp(&mut table.insertion_point);
+                tv(&mut table.horizontal_direction);
+            }
+            EntityType::Text(ref mut text) => {
+                Entity::transform_text(
+                    m,
+                    &mut text.location,
+                    &mut text.second_alignment_point,
+                    &mut text.text_height,
+                    &mut text.rotation,
+                );
+            }
+            EntityType::Tolerance(ref mut tol) => {
+                tp(&mut tol.insertion_point);
+                tv(&mut tol.direction_vector);
+            }
+            EntityType::Trace(ref mut trace) => {
+                tp(&mut trace.first_corner);
+                tp(&mut trace.second_corner);
+                tp(&mut trace.third_corner);
+                tp(&mut trace.fourth_corner);
+            }
+            EntityType::Vertex(ref mut vertex) => {
+                tp(&mut vertex.location);
+            }
+            EntityType::Wipeout(ref mut wipeout) => {
+                tp(&mut wipeout.location);
+                tv(&mut wipeout.u_vector);
+                tv(&mut wipeout.v_vector);
+            }
+            EntityType::XLine(ref mut xline) => {
+                tp(&mut xline.first_point);
+                xline.unit_direction_vector =
+                    m.transform_vector(&xline.unit_direction_vector).normalize();
+            }
+            EntityType::DgnUnderlay(ref mut underlay) => {
+                tp(&mut underlay.insertion_point);
+                underlay.rotation_angle += rotation;
+            }
+            EntityType::DwfUnderlay(ref mut underlay) => {
+                tp(&mut underlay.insertion_point);
+                underlay.rotation_angle += rotation;
+            }
+            EntityType::PdfUnderlay(ref mut underlay) => {
+                tp(&mut underlay.insertion_point);
+                underlay.rotation_angle += rotation;
+            }
+            _ => (), // no geometry that can be transformed
+        }
+    }
+    /// Projects the entity onto the world XY plane, converting OCS geometry to world coordinates
+    /// first and resetting extrusion directions to +Z and thicknesses to 0.  Tilted circles and arcs
+    /// become ellipses, or lines when viewed edge-on.  Bulges on tilted polylines and the shape of
+    /// tilted text and inserts are approximated.  Returns `false` and leaves the entity unchanged
+    /// if it can't be flattened, e.g., 3D solids, meshes, and helixes.
+    pub fn flatten_to_2d(&mut self) -> bool {
+        let flat = |p: &mut Point| p.z = 0.0;
+        let flat_ocs = |p: &mut Point, normal: &Vector| {
+            *p = ocs_point_to_wcs(p.clone(), normal.clone());
+            p.z = 0.0;
+        };
+        let replacement = match self.specific {
+            EntityType::Arc(ref mut arc) => {
+                if Entity::is_z_aligned(&arc.normal) {
+                    flat_ocs(&mut arc.center, &arc.normal);
+                    if arc.normal.z < 0.0 {
+                        let start = arc.start_angle;
+                        arc.start_angle = (180.0 - arc.end_angle).rem_euclid(360.0);
+                        arc.end_angle = (180.0 - start).rem_euclid(360.0);
+                    }
+                    arc.normal = Vector::z_axis();
+                    arc.thickness = 0.0;
+                    None
+                } else {
+                    let (ax, ay) = Entity::ocs_axes(&arc.normal);
+                    let mut end_angle = arc.end_angle;
+                    if end_angle <= arc.start_angle {
+                        end_angle += 360.0;
+                    }
+                    Some(Entity::projected_ellipse(
+                        ocs_point_to_wcs(arc.center.clone(), arc.normal.clone()),
+                        Entity::scaled(&ax, arc.radius),
+                        Entity::scaled(&ay, arc.radius),
+                        arc.start_angle.to_radians(),
+                        end_angle.to_radians(),
+                    ))
+                }
+            }
+            EntityType::Circle(ref mut circle) => {
+                if Entity::is_z_aligned(&circle.normal) {
+                    flat_ocs(&mut circle.center, &circle.normal);
+                    circle.normal = Vector::z_axis();
+                    circle.thickness = 0.0;
+                    None
+                } else {
+                    let (ax, ay) = Entity::ocs_axes(&circle.normal);
+                    Some(Entity::projected_ellipse(
+                        ocs_point_to_wcs(circle.center.clone(), circle.normal.clone()),
+                        Entity::scaled(&ax, circle.radius),
+                        Entity::scaled(&ay, circle.radius),
+                        0.0,
+                        std::f64::consts::PI * 2.0,
+                    ))
+                }
+            }
+            EntityType::Ellipse(ref ellipse) => {
+                let minor = Entity::scaled(
+                    &ellipse.normal.normalize().cross(&ellipse.major_axis),
+                    ellipse.minor_axis_ratio,
+                );
+                let mut end_parameter = ellipse.end_parameter;
+                if end_parameter <= ellipse.start_parameter {
+                    end_parameter += std::f64::consts::PI * 2.0;
+                }
+                Some(Entity::projected_ellipse(
+                    ellipse.center.clone(),
+                    ellipse.major_axis.clone(),
+                    minor,
+                    ellipse.start_parameter,
+                    end_parameter,
+                ))
+            }
+            EntityType::Attribute(ref mut att) => {
+                Entity::flatten_text(
+                    &mut att.location,
+                    &mut att.second_alignment_point,
+                    &mut att.rotation,
+                    &mut att.normal,
+                );
+                att.thickness = 0.0;
+                None
+            }
+            EntityType::AttributeDefinition(ref mut att) => {
+                Entity::flatten_text(
+                    &mut att.location,
+                    &mut att.second_alignment_point,
+                    &mut att.rotation,
+                    &mut att.normal,
+                );
+                att.thickness = 0.0;
+                None
+            }
+            EntityType::Text(ref mut text) => {
+                Entity::flatten_text(
+                    &mut text.location,
+                    &mut text.second_alignment_point,
+                    &mut text.rotation,
+                    &mut text.normal,
+                );
+                text.thickness = 0.0;
+                None
+            }
+            EntityType::Face3D(ref mut face) => {
+                flat(&mut face.first_corner);
+                flat(&mut face.second_corner);
+                flat(&mut face.third_corner);
+                flat(&mut face.fourth_corner);
+                None
+            }
+            EntityType::Insert(ref mut ins) => {
+                let normal = ins.extrusion_direction.clone();
+                let mut unused = Point::origin();
+                Entity::flatten_text(
+                    &mut ins.location,
+                    &mut unused,
+                    &mut ins.rotation,
+                    &mut ins.extrusion_direction,
+                );
+                if normal.z < 0.0 {
+                    ins.y_scale_factor = -ins.y_scale_factor;
+                }
+                for att in ins.attributes_mut() {
+                    Entity::flatten_text(
+                        &mut att.location,
+                        &mut att.second_alignment_point,
+                        &mut att.rotation,
+                        &mut att.normal,
+                    );
+                    att.thickness = 0.0;
+                }
+                None
+            }
+            EntityType::Leader(ref mut leader) => {
+                for v in leader.vertices.iter_mut() {
+                    flat(v);
+                }
+                leader.normal = Vector::z_axis();
+                None
+            }
+            EntityType::Line(ref mut line) => {
+                flat(&mut line.p1);
+                flat(&mut line.p2);
+                line.extrusion_direction = Vector::z_axis();
+                line.thickness = 0.0;
+                None
+            }
+            EntityType::LwPolyline(ref mut poly) => {
+                let normal = poly.extrusion_direction.clone();
+                // the vertices sit at the elevation in the polyline's OCS
+                let elevation = self.common.elevation;
+                for v in poly.vertices.iter_mut() {
+                    let mut p = Point::new(v.x, v.y, elevation);
+                    flat_ocs(&mut p, &normal);
+                    v.x = p.x;
+                    v.y = p.y;
+                    if normal.z < 0.0 {
+                        v.bulge = -v.bulge;
+                    }
+                }
+                poly.thickness = 0.0;
+                poly.extrusion_direction = Vector::z_axis();
+                self.common.elevation = 0.0;
+                None
+            }
+            EntityType::MText(ref mut mtext) => {
+                flat(&mut mtext.insertion_point);
+                mtext.x_axis_direction.z = 0.0;
+                mtext.extrusion_direction = Vector::z_axis();
+                None
+            }
+            EntityType::ModelPoint(ref mut point) => {
+                flat(&mut point.location);
+                point.extrusion_direction = Vector::z_axis();
+                point.thickness = 0.0;
+                None
+            }
+            EntityType::Polyline(ref mut poly) => {
+                if poly.is_polyface_mesh() || poly.is_3d_polygon_mesh() {
+                    return false;
+                }
+
+                let is_3d = poly.is_3d_polyline();
+                let normal = poly.normal.clone();
+                let elevation = poly.location.z;
+                for v in poly.vertices_mut() {
+                    if is_3d {
+                        flat(&mut v.location);
+                    } else {
+                        v.location.z = elevation;
+                        flat_ocs(&mut v.location, &normal);
+                        if normal.z < 0.0 {
+                            v.bulge = -v.bulge;
+                        }
+                    }
+                }
+                poly.location = Point::origin();
+                poly.normal = Vector::z_axis();
+                poly.thickness = 0.0;
+                None
+            }
+            EntityType::Ray(ref mut ray) => {
+                let direction = Vector::new(
+                    ray.unit_direction_vector.x,
+                    ray.unit_direction_vector.y,
+                    0.0,
+                );
+                if direction.length() < 1e-12 {
+                    return false;
+                }
+
+                flat(&mut ray.start_point);
+                ray.unit_direction_vector = direction.normalize();
+                None
+            }
+            EntityType::Solid(ref mut solid) => {
+                let normal = solid.extrusion_direction.clone();
+                flat_ocs(&mut solid.first_corner, &normal);
+                flat_ocs(&mut solid.second_corner, &normal);
+                flat_ocs(&mut solid.third_corner, &normal);
+                flat_ocs(&mut solid.fourth_corner, &normal);
+                solid.extrusion_direction = Vector::z_axis();
+                solid.thickness = 0.0;
+                None
+            }
+            EntityType::Spline(ref mut spline) => {
+                for p in spline.control_points.iter_mut() {
+                    flat(p);
+                }
+                for p in spline.fit_points.iter_mut() {
+                    flat(p);
+                }
+                flat(&mut spline.start_tangent);
+                flat(&mut spline.end_tangent);
+                spline.normal = Vector::z_axis();
+                None
+            }
+            EntityType::Trace(ref mut trace) => {
+                let normal = trace.extrusion_direction.clone();
+                flat_ocs(&mut trace.first_corner, &normal);
+                flat_ocs(&mut trace.second_corner, &normal);
+                flat_ocs(&mut trace.third_corner, &normal);
+                flat_ocs(&mut trace.fourth_corner, &normal);
+                trace.extrusion_direction = Vector::z_axis();
+                trace.thickness = 0.0;
+                None
+            }
+            EntityType::XLine(ref mut xline) => {
+                let direction = Vector::new(
+                    xline.unit_direction_vector.x,
+                    xline.unit_direction_vector.y,
+                    0.0,
+                );
+                if direction.length() < 1e-12 {
+                    return false;
+                }
+
+                flat(&mut xline.first_point);
+                xline.unit_direction_vector = direction.normalize();
+                None
+            }
+            EntityType::Body(_)
+            | EntityType::Helix(_)
+            | EntityType::Region(_)
+            | EntityType::Solid3D(_) => return false,
+            _ => {
+                // remaining entities are treated as world coordinates
+                self.transform(&TransformationMatrix::scale(1.0, 1.0, 0.0));
+                None
+            }
+        };
+        if let Some(specific) = replacement {
+            self.specific = specific;
+        }
+
+        true
+    }
     pub(crate) fn read(iter: &mut CodePairPutBack) -> DxfResult<Option<Entity>> {
         'new_entity: loop {
             match iter.next() {
@@ -732,22 +2648,8 @@ impl Entity {
                     Point::new,
                 );
             }
-            EntityType::Spline(ref mut spline) => {
-                combine_points_3(
-                    &mut spline.__control_point_x,
-                    &mut spline.__control_point_y,
-                    &mut spline.__control_point_z,
-                    &mut spline.control_points,
-                    Point::new,
-                );
-                combine_points_3(
-                    &mut spline.__fit_point_x,
-                    &mut spline.__fit_point_y,
-                    &mut spline.__fit_point_z,
-                    &mut spline.fit_points,
-                    Point::new,
-                );
-            }
+            EntityType::Helix(ref mut helix) => helix.spline.combine_points(),
+            EntityType::Spline(ref mut spline) => spline.combine_points(),
             EntityType::DgnUnderlay(ref mut underlay) => {
                 combine_points_2(
                     &mut underlay.__point_x,
@@ -793,12 +2695,19 @@ impl Entity {
             EntityType::AttributeDefinition(ref mut att) => {
                 Entity::apply_custom_reader_attributedefinition(&mut self.common, att, iter)
             }
+            EntityType::Helix(_) => self.apply_custom_reader_helix(iter),
             EntityType::LwPolyline(ref mut poly) => {
                 Entity::apply_custom_reader_lwpolyline(&mut self.common, poly, iter)
             }
             EntityType::MText(ref mut mtext) => {
                 Entity::apply_custom_reader_mtext(&mut self.common, mtext, iter)
             }
+            EntityType::ProxyEntity(ref mut proxy) => {
+                Entity::apply_custom_reader_proxyentity(&mut self.common, proxy, iter)
+            }
+            EntityType::Table(ref mut table) => {
+                Entity::apply_custom_reader_table(&mut self.common, table, iter)
+            }
             _ => Ok(false), // no custom reader
         }
     }
@@ -1101,6 +3010,38 @@ impl Entity {
             }
         }
     }
+    fn apply_custom_reader_helix(&mut self, iter: &mut CodePairPutBack) -> DxfResult<bool> {
+        // the underlying `AcDbSpline` data reuses the helix codes so it's read separately
+        let mut in_spline = false;
+        loop {
+            let pair = match iter.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Err(e),
+                None => break,
+            };
+            if pair.code == 100 {
+                in_spline = pair.assert_string()? == "AcDbSpline";
+            } else if !in_spline {
+                self.apply_code_pair(&pair, iter)?;
+            } else if let EntityType::Helix(ref mut helix) = self.specific {
+                let mut spline = EntityType::Spline(std::mem::take(&mut helix.spline));
+                let applied = spline.try_apply_code_pair(&pair)?;
+                if let EntityType::Spline(spline) = spline {
+                    helix.spline = spline;
+                }
+                if !applied {
+                    self.common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+
+        self.post_parse()?;
+        Ok(true)
+    }
     fn apply_custom_reader_lwpolyline(
         common: &mut EntityCommon,
         poly: &mut LwPolyline,
@@ -1311,15 +3252,219 @@ impl Entity {
             }
         }
     }
-    fn add_custom_code_pairs(&self, pairs: &mut Vec<CodePair>, version: AcadVersion) -> bool {
-        match self.specific {
-            EntityType::RotatedDimension(ref dim) => {
-                Entity::add_custom_code_pairs_rotateddimension(pairs, dim, version);
-            }
-            EntityType::RadialDimension(ref dim) => {
-                Entity::add_custom_code_pairs_radialdimension(pairs, dim, version);
-            }
-            EntityType::DiameterDimension(ref dim) => {
+    fn apply_custom_reader_proxyentity(
+        common: &mut EntityCommon,
+        proxy: &mut ProxyEntity,
+        iter: &mut CodePairPutBack,
+    ) -> DxfResult<bool> {
+        // the owner handle (330) and the proxy object ids share codes, and the graphics and
+        // entity data both use 310, so the position in the entity decides where a pair goes
+        let mut reading_proxy_data = false;
+        let mut reading_entity_data = false;
+        loop {
+            let pair = next_pair!(iter);
+            if !reading_proxy_data {
+                match pair.code {
+                    100 if pair.assert_string()? == "AcDbProxyEntity" => {
+                        reading_proxy_data = true;
+                    }
+                    90 => {
+                        proxy.proxy_entity_class_id = pair.assert_i32()?;
+                        reading_proxy_data = true;
+                    }
+                    _ => {
+                        common.apply_individual_pair(&pair, iter)?;
+                    }
+                }
+                continue;
+            }
+
+            match pair.code {
+                90 => {
+                    proxy.proxy_entity_class_id = pair.assert_i32()?;
+                }
+                91 => {
+                    proxy.application_entity_class_id = pair.assert_i32()?;
+                }
+                92 => {
+                    proxy.graphics_data_size = pair.assert_i32()?;
+                }
+                93 => {
+                    proxy.entity_data_size = pair.assert_i32()?;
+                    reading_entity_data = true;
+                }
+                310 => {
+                    if reading_entity_data {
+                        proxy.entity_data_string.push(pair.assert_binary()?);
+                    } else {
+                        proxy.graphics_data_string.push(pair.assert_binary()?);
+                    }
+                }
+                330 | 340 | 350 | 360 => {
+                    proxy.object_ids.push((pair.code, pair.assert_string()?));
+                }
+                94 => {
+                    proxy.terminator = pair.assert_i32()?;
+                }
+                95 => {
+                    proxy.__object_drawing_format = pair.assert_i32()? as u32;
+                }
+                70 => {
+                    proxy.original_data_format_is_dxf = as_bool(pair.assert_i16()?);
+                }
+                _ => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+    }
+    fn apply_custom_reader_table(
+        common: &mut EntityCommon,
+        table: &mut Table,
+        iter: &mut CodePairPutBack,
+    ) -> DxfResult<bool> {
+        let mut seen_table_subclass = false;
+        let mut text_chunks = String::new();
+        loop {
+            let pair = next_pair!(iter);
+            if table.cells.is_empty() {
+                match pair.code {
+                    100 => {
+                        seen_table_subclass = pair.assert_string()? == "AcDbTable";
+                    }
+                    2 if !seen_table_subclass => {
+                        table.block_name = pair.assert_string()?;
+                    }
+                    10 => {
+                        table.insertion_point.x = pair.assert_f64()?;
+                    }
+                    20 => {
+                        table.insertion_point.y = pair.assert_f64()?;
+                    }
+                    30 => {
+                        table.insertion_point.z = pair.assert_f64()?;
+                    }
+                    11 => {
+                        table.horizontal_direction.x = pair.assert_f64()?;
+                    }
+                    21 => {
+                        table.horizontal_direction.y = pair.assert_f64()?;
+                    }
+                    31 => {
+                        table.horizontal_direction.z = pair.assert_f64()?;
+                    }
+                    90 => {
+                        table.value_flags = pair.assert_i32()?;
+                    }
+                    91 => {
+                        table.row_count = pair.assert_i32()?;
+                    }
+                    92 => {
+                        table.column_count = pair.assert_i32()?;
+                    }
+                    141 => {
+                        table.row_heights.push(pair.assert_f64()?);
+                    }
+                    142 => {
+                        table.column_widths.push(pair.assert_f64()?);
+                    }
+                    171 => {
+                        table.cells.push(TableCell {
+                            cell_type: pair.assert_i16()?,
+                            ..Default::default()
+                        });
+                    }
+                    280 => {
+                        table.version = pair.assert_i16()?;
+                    }
+                    342 => {
+                        table.__table_style_handle = pair.as_handle()?;
+                    }
+                    343 => {
+                        table.__block_record_handle = pair.as_handle()?;
+                    }
+                    102 | 1001 => {
+                        common.apply_individual_pair(&pair, iter)?;
+                    }
+                    _ if seen_table_subclass => {
+                        // unsupported table-level override
+                    }
+                    _ => {
+                        common.apply_individual_pair(&pair, iter)?;
+                    }
+                }
+            } else {
+                match pair.code {
+                    1 => {
+                        text_chunks.push_str(&pair.assert_string()?);
+                        vec_last!(table.cells).text = text_chunks.clone();
+                        text_chunks.clear();
+                    }
+                    2 => {
+                        text_chunks.push_str(&pair.assert_string()?);
+                    }
+                    7 => {
+                        vec_last!(table.cells).text_style_name = pair.assert_string()?;
+                    }
+                    91 => {
+                        vec_last!(table.cells).override_flags = pair.assert_i32()?;
+                    }
+                    140 => {
+                        vec_last!(table.cells).text_height = pair.assert_f64()?;
+                    }
+                    145 => {
+                        vec_last!(table.cells).rotation = pair.assert_f64()?;
+                    }
+                    170 => {
+                        vec_last!(table.cells).alignment = pair.assert_i16()?;
+                    }
+                    171 => {
+                        text_chunks.clear();
+                        table.cells.push(TableCell {
+                            cell_type: pair.assert_i16()?,
+                            ..Default::default()
+                        });
+                    }
+                    172 => {
+                        vec_last!(table.cells).flags = pair.assert_i16()?;
+                    }
+                    173 => {
+                        vec_last!(table.cells).is_merged = as_bool(pair.assert_i16()?);
+                    }
+                    174 => {
+                        vec_last!(table.cells).is_auto_fit = as_bool(pair.assert_i16()?);
+                    }
+                    175 => {
+                        vec_last!(table.cells).merged_width = pair.assert_i16()?;
+                    }
+                    176 => {
+                        vec_last!(table.cells).merged_height = pair.assert_i16()?;
+                    }
+                    178 => {
+                        vec_last!(table.cells).virtual_edge_flag = pair.assert_i16()?;
+                    }
+                    340 => {
+                        vec_last!(table.cells).block_handle = pair.as_handle()?;
+                    }
+                    1001 => {
+                        common.apply_individual_pair(&pair, iter)?;
+                    }
+                    _ => {
+                        // unsupported cell override or content
+                    }
+                }
+            }
+        }
+    }
+    fn add_custom_code_pairs(&self, pairs: &mut Vec<CodePair>, version: AcadVersion) -> bool {
+        match self.specific {
+            EntityType::RotatedDimension(ref dim) => {
+                Entity::add_custom_code_pairs_rotateddimension(pairs, dim, version);
+            }
+            EntityType::RadialDimension(ref dim) => {
+                Entity::add_custom_code_pairs_radialdimension(pairs, dim, version);
+            }
+            EntityType::DiameterDimension(ref dim) => {
                 Entity::add_custom_code_pairs_diameterdimension(pairs, dim, version);
             }
             EntityType::AngularThreePointDimension(ref dim) => {
@@ -1328,9 +3473,21 @@ impl Entity {
             EntityType::OrdinateDimension(ref dim) => {
                 Entity::add_custom_code_pairs_ordinatedimension(pairs, dim, version);
             }
+            EntityType::Helix(ref helix) => {
+                // the helix data follows the spline it's approximated by
+                EntityType::Spline(helix.spline.clone()).add_code_pairs(
+                    pairs,
+                    &self.common,
+                    version,
+                );
+                self.specific.add_code_pairs(pairs, &self.common, version);
+            }
             EntityType::Polyline(ref poly) => {
                 Entity::add_custom_code_pairs_polyline(pairs, poly, version);
             }
+            EntityType::Table(ref table) => {
+                Entity::add_custom_code_pairs_table(pairs, table);
+            }
             EntityType::Vertex(ref v) => {
                 Entity::add_custom_code_pairs_vertex(pairs, v, version);
             }
@@ -1345,7 +3502,7 @@ impl Entity {
         version: AcadVersion,
     ) -> bool {
         dim.dimension_base.add_code_pairs(pairs, version);
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, "AcDbAlignedDimension"));
         }
         pairs.push(CodePair::new_f64(12, dim.insertion_point.x));
@@ -1359,7 +3516,7 @@ impl Entity {
         pairs.push(CodePair::new_f64(34, dim.definition_point_3.z));
         pairs.push(CodePair::new_f64(50, dim.rotation_angle));
         pairs.push(CodePair::new_f64(52, dim.extension_line_angle));
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, "AcDbRotatedDimension"));
         }
         true
@@ -1436,7 +3593,7 @@ impl Entity {
         } else {
             "AcDb2dPolyline"
         };
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, subclass_marker));
         }
         if version <= AcadVersion::R13 {
@@ -1487,6 +3644,55 @@ impl Entity {
         }
         true
     }
+    fn add_custom_code_pairs_table(pairs: &mut Vec<CodePair>, table: &Table) {
+        pairs.push(CodePair::new_str(100, "AcDbBlockReference"));
+        pairs.push(CodePair::new_string(2, &table.block_name));
+        pairs.push(CodePair::new_f64(10, table.insertion_point.x));
+        pairs.push(CodePair::new_f64(20, table.insertion_point.y));
+        pairs.push(CodePair::new_f64(30, table.insertion_point.z));
+        pairs.push(CodePair::new_str(100, "AcDbTable"));
+        pairs.push(CodePair::new_i16(280, table.version));
+        pairs.push(CodePair::new_handle(342, &table.__table_style_handle));
+        pairs.push(CodePair::new_handle(343, &table.__block_record_handle));
+        pairs.push(CodePair::new_f64(11, table.horizontal_direction.x));
+        pairs.push(CodePair::new_f64(21, table.horizontal_direction.y));
+        pairs.push(CodePair::new_f64(31, table.horizontal_direction.z));
+        pairs.push(CodePair::new_i32(90, table.value_flags));
+        pairs.push(CodePair::new_i32(91, table.row_count));
+        pairs.push(CodePair::new_i32(92, table.column_count));
+        for height in &table.row_heights {
+            pairs.push(CodePair::new_f64(141, *height));
+        }
+        for width in &table.column_widths {
+            pairs.push(CodePair::new_f64(142, *width));
+        }
+        for cell in &table.cells {
+            pairs.push(CodePair::new_i16(171, cell.cell_type));
+            pairs.push(CodePair::new_i16(172, cell.flags));
+            pairs.push(CodePair::new_i16(173, as_i16(cell.is_merged)));
+            pairs.push(CodePair::new_i16(174, as_i16(cell.is_auto_fit)));
+            pairs.push(CodePair::new_i16(175, cell.merged_width));
+            pairs.push(CodePair::new_i16(176, cell.merged_height));
+            pairs.push(CodePair::new_i32(91, cell.override_flags));
+            pairs.push(CodePair::new_i16(178, cell.virtual_edge_flag));
+            pairs.push(CodePair::new_f64(145, cell.rotation));
+            if cell.cell_type == 2 {
+                pairs.push(CodePair::new_handle(340, &cell.block_handle));
+            } else {
+                // long strings are split into 250 character chunks with the last one in code 1
+                let chars = cell.text.chars().collect::<Vec<_>>();
+                let mut chunks = chars.chunks(250).collect::<Vec<_>>();
+                let last = chunks.pop().unwrap_or(&[]);
+                for chunk in chunks {
+                    pairs.push(CodePair::new_string(2, &chunk.iter().collect::<String>()));
+                }
+                pairs.push(CodePair::new_string(1, &last.iter().collect::<String>()));
+                pairs.push(CodePair::new_string(7, &cell.text_style_name));
+                pairs.push(CodePair::new_f64(140, cell.text_height));
+                pairs.push(CodePair::new_i16(170, cell.alignment));
+            }
+        }
+    }
     fn add_custom_code_pairs_vertex(
         pairs: &mut Vec<CodePair>,
         v: &Vertex,
@@ -1498,7 +3704,7 @@ impl Entity {
         } else {
             "AcDb2dVertex"
         };
-        if version >= AcadVersion::R13 {
+        if version.is_r13_or_later() {
             pairs.push(CodePair::new_str(100, subclass_marker));
         }
         pairs.push(CodePair::new_f64(10, v.location.x));
@@ -1619,6 +3825,154 @@ impl Entity {
             pairs.push(CodePair::new_string(5, &handle.as_string()));
         }
     }
+    fn point_on_circle(center: &Point, radius: f64, angle: f64) -> Point {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Point::new(center.x + radius * cos, center.y + radius * sin, center.z)
+    }
+    fn angle_from_center(center: &Point, p: &Point) -> f64 {
+        let angle = (p.y - center.y).atan2(p.x - center.x).to_degrees();
+        if angle < 0.0 {
+            angle + 360.0
+        } else {
+            angle
+        }
+    }
+    fn transform_text(
+        m: &TransformationMatrix,
+        location: &mut Point,
+        second_alignment_point: &mut Point,
+        text_height: &mut f64,
+        rotation: &mut f64,
+    ) {
+        let x_axis = m.transform_vector(&Vector::x_axis());
+        let y_axis = m.transform_vector(&Vector::y_axis());
+        *location = m.transform_point(location);
+        *second_alignment_point = m.transform_point(second_alignment_point);
+        *text_height *= x_axis.length();
+        if x_axis.x * y_axis.y - x_axis.y * y_axis.x < 0.0 {
+            let (sin, cos) = rotation.to_radians().sin_cos();
+            let direction = m.transform_vector(&Vector::new(cos, sin, 0.0));
+            *rotation = Entity::readable_angle(direction.y.atan2(direction.x).to_degrees());
+        } else {
+            *rotation += x_axis.y.atan2(x_axis.x).to_degrees();
+        }
+    }
+    // mirrored text keeps reading left to right, like AutoCAD with `$MIRRTEXT` off
+    fn readable_angle(angle: f64) -> f64 {
+        let angle = angle.rem_euclid(360.0);
+        if angle > 90.0 && angle < 270.0 {
+            angle - 180.0
+        } else {
+            angle
+        }
+    }
+    fn about_point(center: &Point, m: TransformationMatrix) -> TransformationMatrix {
+        TransformationMatrix::translation(center.x, center.y, center.z)
+            * m
+            * TransformationMatrix::translation(-center.x, -center.y, -center.z)
+    }
+    fn is_z_aligned(normal: &Vector) -> bool {
+        normal.x.abs() < 1e-12 && normal.y.abs() < 1e-12
+    }
+    fn scaled(v: &Vector, factor: f64) -> Vector {
+        Vector::new(v.x * factor, v.y * factor, v.z * factor)
+    }
+    /// Returns the world X and Y axes of the OCS defined by `normal`.
+    fn ocs_axes(normal: &Vector) -> (Vector, Vector) {
+        let ax = ocs_point_to_wcs(Point::new(1.0, 0.0, 0.0), normal.clone());
+        let ay = ocs_point_to_wcs(Point::new(0.0, 1.0, 0.0), normal.clone());
+        (Vector::new(ax.x, ax.y, ax.z), Vector::new(ay.x, ay.y, ay.z))
+    }
+    /// Projects the curve `center + a * cos(t) + b * sin(t)` for `t` in `[start, end]` onto the XY
+    /// plane, returning an `Ellipse`, or a `Line` when the curve is seen edge-on.
+    fn projected_ellipse(center: Point, a: Vector, b: Vector, start: f64, end: f64) -> EntityType {
+        let center = Point::new(center.x, center.y, 0.0);
+        let a = Vector::new(a.x, a.y, 0.0);
+        let mut b = Vector::new(b.x, b.y, 0.0);
+        let (mut start, mut end) = (start, end);
+        let cross = a.x * b.y - a.y * b.x;
+        if cross < 0.0 {
+            // the curve runs clockwise when seen from above
+            b = Entity::scaled(&b, -1.0);
+            let old_start = start;
+            start = -end;
+            end = -old_start;
+        }
+
+        // find the principal axes of the projected curve
+        let dot = |u: &Vector, v: &Vector| u.x * v.x + u.y * v.y;
+        let t0 = 0.5 * (2.0 * dot(&a, &b)).atan2(dot(&a, &a) - dot(&b, &b));
+        let (sin, cos) = t0.sin_cos();
+        let major = Vector::new(a.x * cos + b.x * sin, a.y * cos + b.y * sin, 0.0);
+        let minor = Vector::new(-a.x * sin + b.x * cos, -a.y * sin + b.y * cos, 0.0);
+        if major.length() == 0.0 {
+            return EntityType::ModelPoint(ModelPoint::new(center));
+        }
+
+        let ratio = minor.length() / major.length();
+        if ratio < 1e-9 {
+            // seen edge-on, so the curve covers a segment along the major axis
+            let direction = major.normalize();
+            let samples = 360;
+            let (mut min, mut max) = (f64::MAX, f64::MIN);
+            for i in 0..=samples {
+                let t = start + (end - start) * i as f64 / samples as f64;
+                let offset = dot(&major, &direction) * (t - t0).cos();
+                min = min.min(offset);
+                max = max.max(offset);
+            }
+            let at = |offset: f64| {
+                Point::new(
+                    center.x + direction.x * offset,
+                    center.y + direction.y * offset,
+                    0.0,
+                )
+            };
+            return EntityType::Line(Line::new(at(min), at(max)));
+        }
+
+        let full_circle = std::f64::consts::PI * 2.0;
+        let (start_parameter, end_parameter) = if end - start >= full_circle - 1e-12 {
+            (0.0, full_circle)
+        } else {
+            (
+                (start - t0).rem_euclid(full_circle),
+                (end - t0).rem_euclid(full_circle),
+            )
+        };
+        EntityType::Ellipse(Ellipse {
+            center,
+            major_axis: major,
+            normal: Vector::z_axis(),
+            minor_axis_ratio: ratio,
+            start_parameter,
+            end_parameter,
+        })
+    }
+    /// Flattens the OCS location and rotation of a text-like entity.
+    fn flatten_text(
+        location: &mut Point,
+        second_alignment_point: &mut Point,
+        rotation: &mut f64,
+        normal: &mut Vector,
+    ) {
+        let (ax, ay) = Entity::ocs_axes(normal);
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let dx = ax.x * cos + ay.x * sin;
+        let dy = ax.y * cos + ay.y * sin;
+        if dx.abs() > 1e-12 || dy.abs() > 1e-12 {
+            *rotation = dy.atan2(dx).to_degrees().rem_euclid(360.0);
+        }
+        for p in [location, second_alignment_point] {
+            *p = ocs_point_to_wcs(p.clone(), normal.clone());
+            p.z = 0.0;
+        }
+        *normal = Vector::z_axis();
+    }
+    fn transform_dimension_base(m: &TransformationMatrix, dim: &mut DimensionBase) {
+        dim.definition_point_1 = m.transform_point(&dim.definition_point_1);
+        dim.text_mid_point = m.transform_point(&dim.text_mid_point);
+    }
 }
 
 #[cfg(test)]
@@ -3284,4 +5638,1486 @@ mod tests {
         assert_eq!("STANDARD", dim_styles[1].name);
         assert_eq!("style name", dim_styles[2].name);
     }
+
+    #[test]
+    fn transform_mirrored_arc() {
+        let mut ent = Entity::new(EntityType::Arc(Arc::new(
+            Point::new(1.0, 0.0, 0.0),
+            1.0,
+            0.0,
+            90.0,
+        )));
+        ent.transform(&TransformationMatrix::scale(-1.0, 1.0, 1.0));
+        match ent.specific {
+            EntityType::Arc(ref arc) => {
+                assert_eq!(Point::new(-1.0, 0.0, 0.0), arc.center);
+                assert!(approx_eq!(f64, 1.0, arc.radius));
+                assert!(approx_eq!(f64, 90.0, arc.start_angle, epsilon = 1e-10));
+                assert!(approx_eq!(f64, 180.0, arc.end_angle, epsilon = 1e-10));
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn set_common_values_without_matching_on_type() {
+        let mut entities = [
+            Entity::new(EntityType::Line(Default::default())),
+            Entity::new(EntityType::Circle(Default::default())),
+        ];
+        for e in entities.iter_mut() {
+            e.common_mut().layer = String::from("some-layer");
+            e.common_mut().color = Color::from_index(1);
+        }
+        assert!(entities.iter().all(|e| e.common().layer == "some-layer"));
+        assert!(entities
+            .iter()
+            .all(|e| e.common().color == Color::from_index(1)));
+    }
+
+    #[test]
+    fn round_trip_wipeout_boundary() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Wipeout(Wipeout::new(vec![
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 4.0, 0.0),
+            Point::new(5.0, 6.0, 0.0),
+        ]))));
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let entities = reparsed.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Wipeout(ref wipeout) => {
+                assert_eq!(
+                    vec![
+                        Point::new(1.0, 2.0, 0.0),
+                        Point::new(3.0, 4.0, 0.0),
+                        Point::new(5.0, 6.0, 0.0),
+                    ],
+                    wipeout.clipping_vertices
+                );
+                assert_eq!(Vector::y_axis(), wipeout.v_vector);
+                assert_eq!(ImageClippingBoundaryType::Polygonal, wipeout.clipping_type);
+                assert!(wipeout.use_clipping_boundary());
+            }
+            _ => panic!("expected a wipeout"),
+        }
+    }
+
+    #[test]
+    fn linear_dimension_measurement() {
+        let mut dim = RotatedDimension {
+            definition_point_2: Point::new(1.0, 1.0, 0.0),
+            definition_point_3: Point::new(4.0, 5.0, 0.0),
+            ..Default::default()
+        };
+        dim.dimension_base.dimension_type = DimensionType::Aligned;
+        assert!(approx_eq!(f64, 5.0, dim.measurement().unwrap()));
+
+        dim.dimension_base.dimension_type = DimensionType::RotatedHorizontalOrVertical;
+        dim.rotation_angle = 0.0;
+        assert!(approx_eq!(f64, 3.0, dim.measurement().unwrap()));
+        dim.rotation_angle = 90.0;
+        assert!(approx_eq!(f64, 4.0, dim.measurement().unwrap()));
+    }
+
+    #[test]
+    fn radial_and_diameter_dimension_measurement() {
+        let mut radial = RadialDimension {
+            definition_point_2: Point::new(3.0, 4.0, 0.0),
+            ..Default::default()
+        };
+        radial.dimension_base.definition_point_1 = Point::origin();
+        let radial = EntityType::RadialDimension(radial);
+        assert!(approx_eq!(
+            f64,
+            5.0,
+            radial.dimension_measurement().unwrap()
+        ));
+
+        let mut diameter = DiameterDimension {
+            definition_point_2: Point::new(2.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        diameter.dimension_base.definition_point_1 = Point::new(-2.0, 0.0, 0.0);
+        assert!(approx_eq!(f64, 4.0, diameter.measurement().unwrap()));
+    }
+
+    #[test]
+    fn angular_dimension_has_no_measurement() {
+        let dim = AngularThreePointDimension::default();
+        assert_eq!(None, dim.measurement());
+        let dim = EntityType::AngularThreePointDimension(dim);
+        assert_eq!(None, dim.dimension_measurement());
+        assert_eq!(
+            None,
+            EntityType::Line(Default::default()).dimension_measurement()
+        );
+    }
+
+    #[test]
+    fn convenience_constructors() {
+        let line = Entity::new_line(Point::new(1.0, 2.0, 0.0), Point::new(3.0, 4.0, 0.0));
+        assert_eq!("0", line.common.layer);
+        match line.specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(1.0, 2.0, 0.0), line.p1);
+                assert_eq!(Point::new(3.0, 4.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+
+        let text = Entity::new_text(Point::new(1.0, 1.0, 0.0), 2.5, "hello");
+        match text.specific {
+            EntityType::Text(ref text) => {
+                assert_eq!(Point::new(1.0, 1.0, 0.0), text.location);
+                assert!(approx_eq!(f64, 2.5, text.text_height));
+                assert_eq!("hello", text.value);
+            }
+            _ => panic!("expected text"),
+        }
+
+        let circle: Entity = Circle::new(Point::origin(), 2.0).into();
+        assert_eq!(
+            Entity::new_circle(Point::origin(), 2.0).specific,
+            circle.specific
+        );
+        let mut drawing = Drawing::new();
+        drawing.add_entity(ModelPoint::new(Point::origin()).into());
+        assert_eq!(1, drawing.entities().count());
+    }
+
+    #[test]
+    fn round_trip_mline_with_style() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut style = MLineStyle {
+            style_name: String::from("double"),
+            ..Default::default()
+        };
+        style.elements.push(MLineStyleElement::new(
+            0.5,
+            Color::from_index(1),
+            String::from("BYLAYER"),
+        ));
+        style.elements.push(MLineStyleElement::new(
+            -0.5,
+            Color::from_index(2),
+            String::from("BYLAYER"),
+        ));
+        drawing.add_object(Object::new(ObjectType::MLineStyle(style)));
+        let mline = MLine {
+            style_name: String::from("double"),
+            style_element_count: 2,
+            start_point: Point::new(1.0, 2.0, 0.0),
+            vertices: vec![
+                Point::new(1.0, 2.0, 0.0),
+                Point::new(4.0, 2.0, 0.0),
+                Point::new(4.0, 6.0, 0.0),
+            ],
+            segment_directions: vec![Vector::x_axis(), Vector::y_axis(), Vector::y_axis()],
+            miter_directions: vec![
+                Vector::y_axis(),
+                Vector::new(-1.0, 1.0, 0.0),
+                Vector::x_axis(),
+            ],
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::MLine(mline)));
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let entities = reparsed.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::MLine(ref mline) => {
+                assert_eq!("double", mline.style_name);
+                assert_eq!(Point::new(1.0, 2.0, 0.0), mline.start_point);
+                assert_eq!(
+                    vec![
+                        Point::new(1.0, 2.0, 0.0),
+                        Point::new(4.0, 2.0, 0.0),
+                        Point::new(4.0, 6.0, 0.0),
+                    ],
+                    mline.vertices
+                );
+                assert_eq!(
+                    vec![Vector::x_axis(), Vector::y_axis(), Vector::y_axis()],
+                    mline.segment_directions
+                );
+                assert_eq!(
+                    vec![
+                        Vector::y_axis(),
+                        Vector::new(-1.0, 1.0, 0.0),
+                        Vector::x_axis(),
+                    ],
+                    mline.miter_directions
+                );
+            }
+            _ => panic!("expected an mline"),
+        }
+        let styles = reparsed
+            .objects()
+            .filter_map(|o| match o.specific {
+                ObjectType::MLineStyle(ref style) if style.style_name == "double" => Some(style),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(1, styles.len());
+        assert_eq!(
+            vec![
+                MLineStyleElement::new(0.5, Color::from_index(1), String::from("BYLAYER")),
+                MLineStyleElement::new(-0.5, Color::from_index(2), String::from("BYLAYER")),
+            ],
+            styles[0].elements
+        );
+    }
+
+    #[test]
+    fn center_wcs_with_flipped_extrusion() {
+        let mut circle = Circle::new(Point::new(1.0, 2.0, 3.0), 1.0);
+        assert_eq!(Point::new(1.0, 2.0, 3.0), circle.center_wcs());
+        circle.normal = Vector::new(0.0, 0.0, -1.0);
+        assert_eq!(Point::new(-1.0, 2.0, -3.0), circle.center_wcs());
+
+        let mut arc = Arc::new(Point::new(1.0, 2.0, 3.0), 1.0, 0.0, 90.0);
+        arc.normal = Vector::new(0.0, 0.0, -1.0);
+        assert_eq!(Point::new(-1.0, 2.0, -3.0), arc.center_wcs());
+    }
+
+    #[test]
+    fn polyface_mesh_faces() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline::default();
+        assert_eq!(None, poly.faces());
+        poly.set_is_polyface_mesh(true);
+        for (x, y) in &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            let mut vertex = Vertex::new(Point::new(*x, *y, 0.0));
+            vertex.set_is_polyface_mesh_vertex(true);
+            vertex.set_is_3d_polygon_mesh(true);
+            poly.add_vertex(&mut drawing, vertex);
+        }
+        let mut quad = Vertex::default();
+        quad.set_is_polyface_mesh_vertex(true);
+        quad.polyface_mesh_vertex_index1 = 1;
+        quad.polyface_mesh_vertex_index2 = -2;
+        quad.polyface_mesh_vertex_index3 = 3;
+        quad.polyface_mesh_vertex_index4 = 4;
+        poly.add_vertex(&mut drawing, quad);
+        let mut triangle = Vertex::default();
+        triangle.set_is_polyface_mesh_vertex(true);
+        triangle.polyface_mesh_vertex_index1 = 1;
+        triangle.polyface_mesh_vertex_index2 = 3;
+        triangle.polyface_mesh_vertex_index3 = -4;
+        poly.add_vertex(&mut drawing, triangle);
+        assert_eq!(Some(vec![[0, 1, 2, 3], [0, 2, 3, 3]]), poly.faces());
+        assert_eq!(None, poly.mesh_dimensions());
+    }
+
+    #[test]
+    fn polygon_mesh_dimensions() {
+        let mut poly = Polyline::default();
+        assert_eq!(None, poly.mesh_dimensions());
+        poly.set_is_3d_polygon_mesh(true);
+        poly.polygon_mesh_m_vertex_count = 3;
+        poly.polygon_mesh_n_vertex_count = 4;
+        assert_eq!(Some((3, 4)), poly.mesh_dimensions());
+        assert_eq!(None, poly.faces());
+    }
+
+    #[test]
+    fn read_table_cell_text() {
+        let drawing = from_section_pairs(
+            "ENTITIES",
+            vec![
+                CodePair::new_str(0, "ACAD_TABLE"),
+                CodePair::new_str(100, "AcDbEntity"),
+                CodePair::new_str(8, "schedule"),
+                CodePair::new_str(100, "AcDbBlockReference"),
+                CodePair::new_str(2, "*T1"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_f64(20, 2.0),
+                CodePair::new_f64(30, 0.0),
+                CodePair::new_str(100, "AcDbTable"),
+                CodePair::new_i16(280, 0),
+                CodePair::new_str(342, "A1"),
+                CodePair::new_i32(90, 22),
+                CodePair::new_i32(91, 2),
+                CodePair::new_i32(92, 2),
+                CodePair::new_i32(93, 0),
+                CodePair::new_f64(141, 0.5),
+                CodePair::new_f64(141, 0.5),
+                CodePair::new_f64(142, 2.0),
+                CodePair::new_f64(142, 3.0),
+                CodePair::new_i16(171, 1),
+                CodePair::new_i16(172, 0),
+                CodePair::new_i32(91, 0),
+                CodePair::new_str(1, "PART"),
+                CodePair::new_str(7, "STANDARD"),
+                CodePair::new_i16(171, 1),
+                CodePair::new_i32(91, 0),
+                CodePair::new_str(2, "QUAN"),
+                CodePair::new_str(1, "TITY"),
+                CodePair::new_i16(64, 7),
+                CodePair::new_i16(171, 1),
+                CodePair::new_str(1, "bolt"),
+                CodePair::new_i16(171, 1),
+                CodePair::new_str(1, "12"),
+            ],
+        );
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        assert_eq!("schedule", entities[0].common.layer);
+        match entities[0].specific {
+            EntityType::Table(ref table) => {
+                assert_eq!("*T1", table.block_name);
+                assert_eq!(Point::new(1.0, 2.0, 0.0), table.insertion_point);
+                assert_eq!(Handle(0xA1), table.__table_style_handle);
+                assert_eq!(2, table.row_count);
+                assert_eq!(2, table.column_count);
+                assert_eq!(vec![2.0, 3.0], table.column_widths);
+                assert_eq!(4, table.cells.len());
+                assert_eq!(Some("PART"), table.cell_text(0, 0));
+                assert_eq!(Some("QUANTITY"), table.cell_text(0, 1));
+                assert_eq!(Some("bolt"), table.cell_text(1, 0));
+                assert_eq!(Some("12"), table.cell_text(1, 1));
+                assert_eq!(None, table.cell_text(2, 0));
+                assert_eq!(None, table.cell_text(0, 2));
+            }
+            _ => panic!("expected a table"),
+        }
+    }
+
+    #[test]
+    fn round_trip_table() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        let long_text = "x".repeat(300);
+        let table = Table {
+            insertion_point: Point::new(1.0, 2.0, 0.0),
+            row_count: 1,
+            column_count: 2,
+            row_heights: vec![0.5],
+            column_widths: vec![2.0, 3.0],
+            cells: vec![TableCell::new("name"), TableCell::new(&long_text)],
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Table(table)));
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let entities = reparsed.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Table(ref table) => {
+                assert_eq!(Point::new(1.0, 2.0, 0.0), table.insertion_point);
+                assert_eq!(vec![0.5], table.row_heights);
+                assert_eq!(vec![2.0, 3.0], table.column_widths);
+                assert_eq!(
+                    vec![TableCell::new("name"), TableCell::new(&long_text)],
+                    table.cells
+                );
+            }
+            _ => panic!("expected a table"),
+        }
+    }
+
+    #[test]
+    fn entity_handle_accessors() {
+        let mut ent = Entity::new_line(Point::origin(), Point::new(1.0, 1.0, 0.0));
+        assert!(ent.handle().is_empty());
+        ent.set_handle(Handle(0x2A));
+        ent.set_owner_handle(Handle(0x1F));
+        assert_eq!(Handle(0x2A), ent.handle());
+        assert_eq!(Handle(0x1F), ent.owner_handle());
+        assert_eq!(Handle(0x2A), ent.common.handle);
+    }
+
+    #[test]
+    fn entity_bounding_boxes() {
+        let arc = Entity::new(EntityType::Arc(Arc::new(
+            Point::new(1.0, 1.0, 0.0),
+            2.0,
+            0.0,
+            180.0,
+        )));
+        let (min, max) = arc.bounding_box().unwrap();
+        assert!(approx_eq!(f64, -1.0, min.x, epsilon = 1e-12));
+        assert!(approx_eq!(f64, 1.0, min.y, epsilon = 1e-12));
+        assert!(approx_eq!(f64, 3.0, max.x, epsilon = 1e-12));
+        assert!(approx_eq!(f64, 3.0, max.y, epsilon = 1e-12));
+
+        let wrapped = Entity::new(EntityType::Arc(Arc::new(Point::origin(), 1.0, 270.0, 90.0)));
+        let (min, max) = wrapped.bounding_box().unwrap();
+        assert!(approx_eq!(f64, 0.0, min.x, epsilon = 1e-12));
+        assert!(approx_eq!(f64, -1.0, min.y, epsilon = 1e-12));
+        assert!(approx_eq!(f64, 1.0, max.x, epsilon = 1e-12));
+
+        let mut poly = LwPolyline::default();
+        poly.vertices.push(LwPolylineVertex {
+            bulge: 1.0,
+            ..Default::default()
+        });
+        poly.vertices.push(LwPolylineVertex {
+            x: 2.0,
+            ..Default::default()
+        });
+        let (min, max) = Entity::new(EntityType::LwPolyline(poly))
+            .bounding_box()
+            .unwrap();
+        assert_eq!(Point::new(0.0, -1.0, -1.0), min);
+        assert_eq!(Point::new(2.0, 1.0, 1.0), max);
+
+        let ray = Entity::new(EntityType::Ray(Ray::default()));
+        assert_eq!(None, ray.bounding_box());
+    }
+
+    #[test]
+    fn polyface_mesh_bounding_box_skips_face_records() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline::default();
+        poly.set_is_polyface_mesh(true);
+        for (x, y) in &[(10.0, 20.0), (11.0, 20.0), (11.0, 21.0)] {
+            let mut vertex = Vertex::new(Point::new(*x, *y, 5.0));
+            vertex.set_is_polyface_mesh_vertex(true);
+            vertex.set_is_3d_polygon_mesh(true);
+            poly.add_vertex(&mut drawing, vertex);
+        }
+        // the face record's location is the origin
+        let mut face = Vertex::default();
+        face.set_is_polyface_mesh_vertex(true);
+        face.polyface_mesh_vertex_index1 = 1;
+        face.polyface_mesh_vertex_index2 = 2;
+        face.polyface_mesh_vertex_index3 = 3;
+        poly.add_vertex(&mut drawing, face);
+        let (min, max) = Entity::new(EntityType::Polyline(poly))
+            .bounding_box()
+            .unwrap();
+        assert_eq!(Point::new(10.0, 20.0, 5.0), min);
+        assert_eq!(Point::new(11.0, 21.0, 5.0), max);
+    }
+
+    #[test]
+    fn round_trip_rational_spline_weights() {
+        let mut spline = Spline {
+            degree_of_curve: 1,
+            control_points: vec![Point::new(0.0, 0.0, 0.0), Point::new(1.0, 1.0, 0.0)],
+            knot_values: vec![0.0, 0.0, 1.0, 1.0],
+            weights: vec![1.0, 0.5],
+            ..Default::default()
+        };
+        spline.set_is_rational(true);
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Spline(spline)));
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        let entity = reparsed.entities().next().unwrap();
+        match entity.specific {
+            EntityType::Spline(ref spline) => assert_eq!(vec![1.0, 0.5], spline.weights),
+            _ => panic!("expected a spline"),
+        }
+    }
+
+    #[test]
+    fn save_spline_with_wrong_knot_count() {
+        let spline = Spline {
+            degree_of_curve: 3,
+            control_points: vec![Point::origin(); 4],
+            knot_values: vec![0.0; 4],
+            ..Default::default()
+        };
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Spline(spline)));
+
+        // validation is opt-in
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+
+        let options = SaveOptions {
+            validate: true,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        match drawing.save_with_options(&mut buf, &options) {
+            Err(DxfError::ValidationError(ref s)) => assert!(s.contains("requires 8 knot values")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn rational_spline_requires_weight_per_control_point() {
+        let mut spline = Spline {
+            degree_of_curve: 1,
+            control_points: vec![Point::origin(); 2],
+            knot_values: vec![0.0, 0.0, 1.0, 1.0],
+            weights: vec![1.0],
+            ..Default::default()
+        };
+        assert!(spline.validate().is_ok());
+        spline.set_is_rational(true);
+        assert!(spline.validate().is_err());
+    }
+
+    #[test]
+    fn closed_lw_polyline_segments_wrap() {
+        let mut poly = LwPolyline::default();
+        for (x, y) in &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            poly.vertices.push(LwPolylineVertex {
+                x: *x,
+                y: *y,
+                ..Default::default()
+            });
+        }
+        poly.vertices[3].bulge = 0.5;
+        assert_eq!(3, poly.segments().len());
+        assert_eq!(4, poly.points(true).len());
+
+        poly.set_is_closed(true);
+        assert_eq!(4, poly.points(false).len());
+        assert_eq!(5, poly.points(true).len());
+        let segments = poly.segments();
+        assert_eq!(4, segments.len());
+        assert_eq!(
+            (Point::new(0.0, 1.0, 0.0), Point::origin(), 0.5),
+            segments[3]
+        );
+    }
+
+    #[test]
+    fn closed_polyline_segments_wrap() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline::default();
+        for (x, y) in &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            poly.add_vertex(&mut drawing, Vertex::new(Point::new(*x, *y, 0.0)));
+        }
+        poly.set_is_closed(true);
+        let segments = poly.segments();
+        assert_eq!(4, segments.len());
+        assert_eq!(Point::origin(), segments[3].1);
+    }
+
+    #[test]
+    fn point_marker_geometry() {
+        let point = ModelPoint::new(Point::new(1.0, 1.0, 0.0));
+        let marker = point.marker_geometry(0, 2.0);
+        assert_eq!(1, marker.len());
+        assert!(matches!(marker[0].specific, EntityType::ModelPoint(_)));
+
+        assert!(point.marker_geometry(1, 2.0).is_empty());
+
+        let marker = point.marker_geometry(2, 2.0);
+        assert_eq!(2, marker.len());
+        match marker[0].specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(0.0, 1.0, 0.0), line.p1);
+                assert_eq!(Point::new(2.0, 1.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+
+        // X with circle and square
+        let marker = point.marker_geometry(3 + 32 + 64, 2.0);
+        assert_eq!(7, marker.len());
+        let circles = marker
+            .iter()
+            .filter_map(|e| match e.specific {
+                EntityType::Circle(ref c) => Some(c),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(1, circles.len());
+        assert_eq!(1.0, circles[0].radius);
+    }
+
+    #[test]
+    fn round_trip_entity_transparency() {
+        let mut line = Entity::new_line(Point::origin(), Point::new(1.0, 1.0, 0.0));
+        line.common.transparency = Some(Transparency::from_percent(50.0));
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.add_entity(line);
+        assert_contains_pairs(&drawing, vec![CodePair::new_i32(440, 0x0200_0080)]);
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let transparency = reparsed
+            .entities()
+            .next()
+            .unwrap()
+            .common
+            .transparency
+            .unwrap();
+        assert_eq!(Transparency::from_percent(50.0), transparency);
+        let percent = transparency.percent().unwrap();
+        assert!((percent - 50.0).abs() < 0.5);
+
+        assert_eq!(None, Transparency::by_layer().percent());
+        assert_eq!(None, Transparency::by_block().percent());
+        assert!(Transparency::by_block().is_by_block());
+        assert!(Transparency::default().is_by_layer());
+        assert_eq!(Some(0.0), Transparency::from_percent(0.0).percent());
+        assert_eq!(
+            0x0200_0080,
+            Transparency::from_raw_value(0x0200_0080).raw_value()
+        );
+    }
+
+    #[test]
+    fn by_layer_transparency_is_not_written() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.add_entity(Entity::new_line(Point::origin(), Point::origin()));
+        assert_not_contains_pairs(&drawing, vec![CodePair::new_i32(440, 0)]);
+    }
+
+    #[test]
+    fn mtext_plain_text() {
+        let mtext = MText {
+            extended_text: vec![String::from(r"{\fArial|b1;Bold} and \C1;red\P")],
+            text: String::from(r"\H2.5x;big \S1/2; \Lunder\l 50\\50 \{x\}"),
+            ..Default::default()
+        };
+        assert_eq!("Bold and red\nbig 1/2 under 50\\50 {x}", mtext.plain_text());
+    }
+
+    #[test]
+    fn convenience_transforms() {
+        let assert_point = |expected: Point, actual: &Point| {
+            assert!(approx_eq!(f64, expected.x, actual.x, epsilon = 1e-10));
+            assert!(approx_eq!(f64, expected.y, actual.y, epsilon = 1e-10));
+            assert!(approx_eq!(f64, expected.z, actual.z, epsilon = 1e-10));
+        };
+
+        let mut line = Entity::new_line(Point::new(1.0, 0.0, 0.0), Point::new(2.0, 0.0, 0.0));
+        line.translate(Vector::new(1.0, 1.0, 0.0));
+        line.rotate(Point::new(2.0, 1.0, 0.0), 90.0);
+        line.scale(Point::new(2.0, 1.0, 0.0), 2.0);
+        match line.specific {
+            EntityType::Line(ref line) => {
+                assert_point(Point::new(2.0, 1.0, 0.0), &line.p1);
+                assert_point(Point::new(2.0, 3.0, 0.0), &line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+
+        // mirror across the vertical line x = 1
+        let mut arc = Entity::new(EntityType::Arc(Arc::new(
+            Point::new(2.0, 0.0, 0.0),
+            1.0,
+            0.0,
+            90.0,
+        )));
+        arc.mirror(Point::new(1.0, 0.0, 0.0), Point::new(1.0, 1.0, 0.0));
+        match arc.specific {
+            EntityType::Arc(ref arc) => {
+                assert_point(Point::new(0.0, 0.0, 0.0), &arc.center);
+                assert!(approx_eq!(f64, 90.0, arc.start_angle, epsilon = 1e-10));
+                assert!(approx_eq!(f64, 180.0, arc.end_angle, epsilon = 1e-10));
+            }
+            _ => panic!("expected an arc"),
+        }
+
+        let mut text = Entity::new_text(Point::new(2.0, 0.0, 0.0), 1.0, "abc");
+        text.mirror(Point::new(1.0, 0.0, 0.0), Point::new(1.0, 1.0, 0.0));
+        match text.specific {
+            EntityType::Text(ref text) => {
+                assert_point(Point::new(0.0, 0.0, 0.0), &text.location);
+                assert!(approx_eq!(f64, 0.0, text.rotation, epsilon = 1e-10));
+                assert!(approx_eq!(f64, 1.0, text.text_height, epsilon = 1e-10));
+            }
+            _ => panic!("expected text"),
+        }
+
+        // mirroring across a horizontal line keeps rotated text readable
+        let mut text = Entity::new_text(Point::new(0.0, 2.0, 0.0), 1.0, "abc");
+        if let EntityType::Text(ref mut t) = text.specific {
+            t.rotation = 30.0;
+        }
+        text.mirror(Point::origin(), Point::new(1.0, 0.0, 0.0));
+        match text.specific {
+            EntityType::Text(ref text) => {
+                assert_point(Point::new(0.0, -2.0, 0.0), &text.location);
+                assert!(approx_eq!(f64, 330.0, text.rotation, epsilon = 1e-10));
+            }
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn proxy_entity_binary_data_round_trip() {
+        let ent = read_entity(
+            "ACAD_PROXY_ENTITY",
+            vec![
+                CodePair::new_str(5, "A1"),
+                CodePair::new_str(330, "1F"),
+                CodePair::new_str(100, "AcDbEntity"),
+                CodePair::new_str(8, "0"),
+                CodePair::new_str(100, "AcDbProxyEntity"),
+                CodePair::new_i32(90, 498),
+                CodePair::new_i32(91, 501),
+                CodePair::new_i32(92, 4),
+                CodePair::new_binary(310, vec![0x01, 0x02]),
+                CodePair::new_binary(310, vec![0x03, 0x04]),
+                CodePair::new_i32(93, 24),
+                CodePair::new_binary(310, vec![0xAA, 0xBB, 0xCC]),
+                CodePair::new_str(330, "2A"),
+                CodePair::new_str(360, "2B"),
+                CodePair::new_str(340, "2C"),
+                CodePair::new_i32(94, 0),
+            ],
+        );
+        assert_eq!(Handle(0x1F), ent.common.__owner_handle);
+        let proxy = match ent.specific {
+            EntityType::ProxyEntity(ref proxy) => proxy.clone(),
+            _ => panic!("expected a proxy entity"),
+        };
+        assert_eq!(501, proxy.application_entity_class_id);
+        assert_eq!(
+            vec![vec![0x01, 0x02], vec![0x03, 0x04]],
+            proxy.graphics_data_string
+        );
+        assert_eq!(vec![0x01, 0x02, 0x03, 0x04], proxy.graphics_data());
+        assert_eq!(vec![0xAA, 0xBB, 0xCC], proxy.entity_data());
+        assert_eq!(
+            vec![
+                (330, String::from("2A")),
+                (360, String::from("2B")),
+                (340, String::from("2C")),
+            ],
+            proxy.object_ids
+        );
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(ent);
+        let mut buf = vec![];
+        drawing.save(&mut buf).ok().unwrap();
+        let reparsed = Drawing::load(&mut buf.as_slice()).ok().unwrap();
+        let entities = reparsed.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::ProxyEntity(ref reparsed) => assert_eq!(&proxy, reparsed),
+            _ => panic!("expected a proxy entity"),
+        }
+    }
+
+    #[test]
+    fn proxy_entity_data_setters() {
+        let mut proxy = ProxyEntity::default();
+        let data = (0..200).map(|b| b as u8).collect::<Vec<_>>();
+        proxy.set_graphics_data(&data);
+        proxy.set_entity_data(&data[..3]);
+        assert_eq!(2, proxy.graphics_data_string.len());
+        assert_eq!(200, proxy.graphics_data_size);
+        assert_eq!(data, proxy.graphics_data());
+        assert_eq!(24, proxy.entity_data_size);
+    }
+
+    #[test]
+    fn effective_linetype_scale_combines_entity_and_global_scale() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.header.line_type_scale = 2.5;
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.line_type_scale = 4.0;
+        drawing.add_entity(line);
+        assert_contains_pairs(&drawing, vec![CodePair::new_f64(48, 4.0)]);
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let line = reparsed.entities().next().unwrap();
+        assert_eq!(4.0, line.common.line_type_scale);
+        assert_eq!(10.0, line.effective_linetype_scale(&reparsed));
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let mut ent = Entity::new_line(Point::origin(), Point::new(1.0, 0.0, 0.0));
+        assert_eq!(Point::new(1.0, 0.0, 0.0), ent.as_line().unwrap().p2);
+        assert!(ent.as_circle().is_none());
+        assert!(ent.as_lw_polyline_mut().is_none());
+        ent.as_line_mut().unwrap().p2 = Point::new(2.0, 0.0, 0.0);
+        assert_eq!(Point::new(2.0, 0.0, 0.0), ent.as_line().unwrap().p2);
+
+        let text = Entity::new_text(Point::origin(), 1.0, "abc");
+        assert_eq!("abc", text.as_text().unwrap().value);
+        assert!(text.as_mtext().is_none());
+    }
+
+    #[test]
+    fn insert_array_positions() {
+        let insert = Insert {
+            location: Point::new(10.0, 20.0, 0.0),
+            row_count: 2,
+            column_count: 3,
+            row_spacing: 5.0,
+            column_spacing: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                Point::new(10.0, 20.0, 0.0),
+                Point::new(12.0, 20.0, 0.0),
+                Point::new(14.0, 20.0, 0.0),
+                Point::new(10.0, 25.0, 0.0),
+                Point::new(12.0, 25.0, 0.0),
+                Point::new(14.0, 25.0, 0.0),
+            ],
+            insert.array_positions()
+        );
+
+        let single = Insert {
+            location: Point::new(1.0, 2.0, 3.0),
+            row_count: 0,
+            ..Default::default()
+        };
+        assert_eq!(vec![Point::new(1.0, 2.0, 3.0)], single.array_positions());
+    }
+
+    #[test]
+    fn simplify_lw_polyline() {
+        let mut poly = LwPolyline::default();
+        for x in 0..5 {
+            poly.vertices.push(LwPolylineVertex {
+                x: f64::from(x),
+                y: if x == 2 { 0.0001 } else { 0.0 },
+                ..Default::default()
+            });
+        }
+        assert_eq!(3, poly.simplify(0.001));
+        assert_eq!(2, poly.vertices.len());
+        assert_eq!((0.0, 4.0), (poly.vertices[0].x, poly.vertices[1].x));
+
+        // arc vertices are kept
+        let mut poly = LwPolyline::default();
+        for x in 0..4 {
+            poly.vertices.push(LwPolylineVertex {
+                x: f64::from(x),
+                ..Default::default()
+            });
+        }
+        poly.vertices[1].bulge = 1.0;
+        assert_eq!(0, poly.simplify(0.001));
+        assert_eq!(4, poly.vertices.len());
+        // a slowly curving run drifts far from the chord even though each vertex is close to the
+        // line through its neighbors
+        let mut poly = LwPolyline::default();
+        for i in 0..=10 {
+            let angle = f64::from(i) * 0.01;
+            poly.vertices.push(LwPolylineVertex {
+                x: 100.0 * angle.sin(),
+                y: 100.0 * (1.0 - angle.cos()),
+                ..Default::default()
+            });
+        }
+        assert_eq!(8, poly.simplify(0.05));
+        assert_eq!(3, poly.vertices.len());
+        assert!(approx_eq!(f64, 100.0 * 0.05_f64.sin(), poly.vertices[1].x));
+    }
+
+    #[test]
+    fn read_helix_with_spline_data() {
+        let ent = read_entity(
+            "HELIX",
+            vec![
+                CodePair::new_str(8, "springs"),
+                CodePair::new_str(100, "AcDbSpline"),
+                CodePair::new_f64(10, 9.0),
+                CodePair::new_f64(20, 9.0),
+                CodePair::new_f64(30, 9.0),
+                CodePair::new_f64(40, 9.0),
+                CodePair::new_str(100, "AcDbHelix"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_f64(20, 2.0),
+                CodePair::new_f64(30, 3.0),
+                CodePair::new_f64(40, 4.0),
+                CodePair::new_f64(41, 2.5),
+                CodePair::new_bool(290, true),
+            ],
+        );
+        assert_eq!("springs", ent.common.layer);
+        match ent.specific {
+            EntityType::Helix(ref helix) => {
+                assert_eq!(Point::new(1.0, 2.0, 3.0), helix.axis_base_point);
+                assert_eq!(4.0, helix.radius);
+                assert_eq!(2.5, helix.number_of_turns);
+                assert!(helix.is_right_handed);
+                assert_eq!(vec![Point::new(9.0, 9.0, 9.0)], helix.spline.control_points);
+                assert_eq!(vec![9.0], helix.spline.knot_values);
+            }
+            _ => panic!("expected a helix"),
+        }
+    }
+
+    #[test]
+    fn round_trip_helix() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2007;
+        drawing.add_entity(Entity::new(EntityType::Helix(Helix {
+            number_of_turns: 3.0,
+            is_right_handed: true,
+            spline: Spline {
+                degree_of_curve: 1,
+                control_points: vec![Point::new(1.0, 0.0, 0.0), Point::new(0.0, 1.0, 1.0)],
+                knot_values: vec![0.0, 0.0, 1.0, 1.0],
+                ..Default::default()
+            },
+            ..Default::default()
+        })));
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load(&mut buf.as_slice()).unwrap();
+        let helix = reparsed.entities().next().unwrap().as_helix().unwrap();
+        assert_eq!(3.0, helix.number_of_turns);
+        assert!(helix.is_right_handed);
+        assert_eq!(
+            vec![Point::new(1.0, 0.0, 0.0), Point::new(0.0, 1.0, 1.0)],
+            helix.spline.control_points
+        );
+        assert_eq!(vec![0.0, 0.0, 1.0, 1.0], helix.spline.knot_values);
+    }
+
+    #[test]
+    fn tessellate_helix() {
+        let helix = Helix {
+            start_point: Point::new(1.0, 0.0, 0.0),
+            radius: 1.0,
+            number_of_turns: 2.0,
+            turn_height: 3.0,
+            is_right_handed: true,
+            ..Default::default()
+        };
+        let points = helix.tessellate(8);
+        assert_eq!(9, points.len());
+        assert_eq!(Point::new(1.0, 0.0, 0.0), points[0]);
+        // a quarter turn counter-clockwise
+        assert!(approx_eq!(f64, 0.0, points[1].x, epsilon = 1e-9));
+        assert!(approx_eq!(f64, 1.0, points[1].y, epsilon = 1e-9));
+        assert!(approx_eq!(f64, 0.75, points[1].z, epsilon = 1e-9));
+        assert!(approx_eq!(f64, 1.0, points[8].x, epsilon = 1e-9));
+        assert!(approx_eq!(f64, 6.0, points[8].z, epsilon = 1e-9));
+
+        let left = Helix {
+            is_right_handed: false,
+            ..helix
+        };
+        assert!(approx_eq!(
+            f64,
+            -1.0,
+            left.tessellate(8)[1].y,
+            epsilon = 1e-9
+        ));
+    }
+
+    #[test]
+    fn tessellate_helix_uses_radius() {
+        let helix = Helix {
+            start_point: Point::new(1.0, 0.0, 0.0),
+            radius: 2.0,
+            number_of_turns: 1.0,
+            is_right_handed: true,
+            ..Default::default()
+        };
+        let points = helix.tessellate(4);
+        assert_eq!(Point::new(2.0, 0.0, 0.0), points[0]);
+        assert!(approx_eq!(f64, 2.0, points[1].y, epsilon = 1e-9));
+
+        // the start point is on the axis so only the radius gives the size
+        let on_axis = Helix {
+            start_point: Point::origin(),
+            ..helix
+        };
+        let start = &on_axis.tessellate(4)[0];
+        assert!(approx_eq!(
+            f64,
+            2.0,
+            (start.x * start.x + start.y * start.y).sqrt(),
+            epsilon = 1e-9
+        ));
+    }
+
+    #[test]
+    fn spline_from_fit_points_interpolates() {
+        let points = vec![
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 3.0, 1.0),
+            Point::new(4.0, 1.0, 0.0),
+            Point::new(6.0, 0.0, 0.0),
+        ];
+        let spline = Spline::from_fit_points(&points, 3);
+        assert_eq!(3, spline.degree_of_curve);
+        assert_eq!(5, spline.control_points.len());
+        assert!(spline.validate().is_ok());
+
+        let mut lengths = vec![0.0];
+        for pair in points.windows(2) {
+            let d = ((pair[1].x - pair[0].x).powi(2)
+                + (pair[1].y - pair[0].y).powi(2)
+                + (pair[1].z - pair[0].z).powi(2))
+            .sqrt();
+            lengths.push(lengths[lengths.len() - 1] + d);
+        }
+        let total = lengths[lengths.len() - 1];
+        for (point, length) in points.iter().zip(lengths) {
+            let actual = spline.point_at(length / total).unwrap();
+            assert!(approx_eq!(f64, point.x, actual.x, epsilon = 1e-9));
+            assert!(approx_eq!(f64, point.y, actual.y, epsilon = 1e-9));
+            assert!(approx_eq!(f64, point.z, actual.z, epsilon = 1e-9));
+        }
+
+        // too few points for the requested degree
+        let line = Spline::from_fit_points(&points[..2], 3);
+        assert_eq!(1, line.degree_of_curve);
+        assert_eq!(Some(Point::new(0.5, 1.0, 0.0)), line.point_at(0.5));
+
+        // repeated points are dropped instead of making the system singular
+        let repeated = vec![
+            points[0].clone(),
+            points[1].clone(),
+            points[1].clone(),
+            points[2].clone(),
+            points[3].clone(),
+            points[4].clone(),
+        ];
+        let deduped = Spline::from_fit_points(&repeated, 3);
+        assert_eq!(points, deduped.fit_points);
+        assert_eq!(spline.control_points, deduped.control_points);
+        assert!(deduped
+            .control_points
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite()));
+        let single = Spline::from_fit_points(&[points[0].clone(), points[0].clone()], 3);
+        assert_eq!(1, single.fit_points.len());
+        assert!(single.control_points.is_empty());
+    }
+
+    #[test]
+    fn dimension_associated_entities() {
+        let mut drawing = Drawing::new();
+        let line = drawing
+            .add_entity(Entity::new_line(Point::origin(), Point::new(4.0, 0.0, 0.0)))
+            .common
+            .handle;
+        drawing.add_entity(Entity::new_line(Point::origin(), Point::new(0.0, 4.0, 0.0)));
+        let dimension = drawing
+            .add_entity(Entity::new(
+                EntityType::RotatedDimension(Default::default()),
+            ))
+            .common
+            .handle;
+        drawing.add_object(Object::new(ObjectType::DimensionAssoc(DimensionAssoc {
+            __dimension_handle: dimension,
+            __main_object_handle: line,
+            ..Default::default()
+        })));
+
+        let entities = drawing.entities().collect::<Vec<_>>();
+        let associated = entities[2].associated_entities(&drawing);
+        assert_eq!(1, associated.len());
+        assert_eq!(line, associated[0].common.handle);
+        assert!(entities[1].associated_entities(&drawing).is_empty());
+    }
+
+    #[test]
+    fn round_trip_text_width_factor_and_oblique_angle() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Text(Text {
+            value: String::from("narrow"),
+            relative_x_scale_factor: 0.8,
+            oblique_angle: 15.0,
+            ..Default::default()
+        })));
+        assert_contains_pairs(
+            &drawing,
+            vec![CodePair::new_f64(41, 0.8), CodePair::new_f64(51, 15.0)],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let text = reparsed.entities().next().unwrap().as_text().unwrap();
+        assert_eq!(0.8, text.relative_x_scale_factor);
+        assert_eq!(15.0, text.oblique_angle);
+    }
+
+    #[test]
+    fn round_trip_mtext_columns() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        drawing.add_entity(Entity::new(EntityType::MText(MText {
+            text: String::from("two columns"),
+            reference_rectangle_width: 12.0,
+            column_type: 2,
+            column_count: 2,
+            column_width: 5.0,
+            column_gutter: 1.0,
+            column_heights: vec![3.0, 4.0],
+            ..Default::default()
+        })));
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let mtext = reparsed.entities().next().unwrap().as_mtext().unwrap();
+        assert_eq!(12.0, mtext.reference_rectangle_width);
+        assert_eq!(2, mtext.column_type);
+        assert_eq!(2, mtext.column_count);
+        assert_eq!(5.0, mtext.column_width);
+        assert_eq!(1.0, mtext.column_gutter);
+        assert_eq!(vec![3.0, 4.0], mtext.column_heights);
+    }
+
+    #[test]
+    fn flatten_tilted_circle_to_ellipse() {
+        let angle = std::f64::consts::FRAC_PI_4;
+        let mut circle = Circle::new(Point::new(0.0, 0.0, 5.0), 2.0);
+        circle.normal = Vector::new(0.0, angle.sin(), angle.cos());
+        let mut ent = Entity::new(EntityType::Circle(circle));
+        assert!(ent.flatten_to_2d());
+        match ent.specific {
+            EntityType::Ellipse(ref ellipse) => {
+                assert!(approx_eq!(f64, 0.0, ellipse.center.x, epsilon = 1e-9));
+                assert!(approx_eq!(
+                    f64,
+                    5.0 * angle.sin(),
+                    ellipse.center.y,
+                    epsilon = 1e-9
+                ));
+                assert_eq!(0.0, ellipse.center.z);
+                assert!(approx_eq!(
+                    f64,
+                    2.0,
+                    ellipse.major_axis.length(),
+                    epsilon = 1e-9
+                ));
+                assert_eq!(0.0, ellipse.major_axis.z);
+                assert!(approx_eq!(
+                    f64,
+                    angle.cos(),
+                    ellipse.minor_axis_ratio,
+                    epsilon = 1e-9
+                ));
+                assert_eq!(Vector::z_axis(), ellipse.normal);
+            }
+            _ => panic!("expected an ellipse"),
+        }
+    }
+
+    #[test]
+    fn flatten_edge_on_circle_to_line() {
+        // the center is in OCS, i.e., at world X = 1
+        let mut circle = Circle::new(Point::new(0.0, 0.0, 1.0), 2.0);
+        circle.normal = Vector::x_axis();
+        let mut ent = Entity::new(EntityType::Circle(circle));
+        assert!(ent.flatten_to_2d());
+        match ent.specific {
+            EntityType::Line(ref line) => {
+                assert!(approx_eq!(f64, 1.0, line.p1.x, epsilon = 1e-9));
+                assert!(approx_eq!(f64, 1.0, line.p2.x, epsilon = 1e-9));
+                assert!(approx_eq!(
+                    f64,
+                    4.0,
+                    (line.p2.y - line.p1.y).abs(),
+                    epsilon = 1e-9
+                ));
+            }
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn flatten_arc_with_negative_normal() {
+        let mut arc = Arc::new(Point::new(1.0, 2.0, 3.0), 1.0, 0.0, 90.0);
+        arc.normal = Vector::new(0.0, 0.0, -1.0);
+        let mut ent = Entity::new(EntityType::Arc(arc));
+        assert!(ent.flatten_to_2d());
+        match ent.specific {
+            EntityType::Arc(ref arc) => {
+                // OCS x is flipped for a -Z normal
+                assert_eq!(Point::new(-1.0, 2.0, 0.0), arc.center);
+                assert!(approx_eq!(f64, 90.0, arc.start_angle));
+                assert!(approx_eq!(f64, 180.0, arc.end_angle));
+                assert_eq!(Vector::z_axis(), arc.normal);
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn flatten_tilted_lw_polyline_uses_elevation() {
+        let mut poly = LwPolyline {
+            extrusion_direction: Vector::x_axis(),
+            ..Default::default()
+        };
+        poly.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 2.0,
+            ..Default::default()
+        });
+        let mut ent = Entity::new(EntityType::LwPolyline(poly));
+        ent.common.elevation = 5.0;
+        assert!(ent.flatten_to_2d());
+        assert_eq!(0.0, ent.common.elevation);
+        match ent.specific {
+            EntityType::LwPolyline(ref poly) => {
+                // OCS z lies along world x for an x-axis extrusion
+                assert!(approx_eq!(f64, 5.0, poly.vertices[0].x, epsilon = 1e-9));
+                assert!(approx_eq!(f64, 1.0, poly.vertices[0].y, epsilon = 1e-9));
+                assert_eq!(Vector::z_axis(), poly.extrusion_direction);
+            }
+            _ => panic!("expected an lw polyline"),
+        }
+    }
+
+    #[test]
+    fn round_trip_material_and_plot_style_handles() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2007;
+        let material = drawing
+            .add_object(Object::new(ObjectType::Material(Material::default())))
+            .clone();
+        let mut ent = Entity::new(EntityType::Line(Line::default()));
+        ent.common.set_material(&material).unwrap();
+        ent.common.__plot_style_handle = Handle(0x42);
+        drawing.add_entity(ent);
+        assert_contains_pairs(
+            &drawing,
+            vec![CodePair::new_string(
+                347,
+                &material.common.handle.as_string(),
+            )],
+        );
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let ent = reparsed.entities().next().unwrap();
+        assert_eq!(material.common.handle, ent.common.__material_handle);
+        assert!(ent.common.material(&reparsed).is_some());
+        assert_eq!(Handle(0x42), ent.common.__plot_style_handle);
+    }
+
+    #[test]
+    fn dont_write_empty_material_and_plot_style_handles() {
+        let ent = Entity::new(EntityType::Line(Line::default()));
+        let mut pairs = vec![];
+        ent.add_code_pairs(&mut pairs, AcadVersion::R2007, true);
+        assert!(!pairs.iter().any(|p| p.code == 347 || p.code == 390));
+    }
+
+    #[test]
+    fn mtext_to_text_lines() {
+        let mtext = MText {
+            insertion_point: Point::new(1.0, 10.0, 0.0),
+            initial_text_height: 3.0,
+            text: String::from("first\\Psecond"),
+            ..Default::default()
+        };
+        let lines = mtext.to_text_lines();
+        assert_eq!(2, lines.len());
+        assert_eq!("first", lines[0].value);
+        assert_eq!("second", lines[1].value);
+        // top-left attachment; the first baseline is one text height below the insertion point
+        assert_eq!(Point::new(1.0, 7.0, 0.0), lines[0].location);
+        assert!(approx_eq!(f64, 1.0, lines[1].location.x));
+        assert!(approx_eq!(f64, 2.0, lines[1].location.y));
+        assert_eq!(3.0, lines[1].text_height);
+        assert_eq!(
+            HorizontalTextJustification::Left,
+            lines[1].horizontal_text_justification
+        );
+    }
+
+    #[test]
+    fn round_trip_shape() {
+        let mut shape = Shape::new("BOX", Point::new(1.0, 2.0, 3.0), 2.5);
+        shape.rotation_angle = 30.0;
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Shape(shape)));
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_f64(20, 2.0),
+                CodePair::new_f64(30, 3.0),
+                CodePair::new_f64(40, 2.5),
+                CodePair::new_str(2, "BOX"),
+                CodePair::new_f64(50, 30.0),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let shape = reparsed.entities().next().unwrap().as_shape().unwrap();
+        assert_eq!("BOX", shape.name);
+        assert_eq!(Point::new(1.0, 2.0, 3.0), shape.location);
+        assert_eq!(2.5, shape.size);
+        assert_eq!(30.0, shape.rotation_angle);
+    }
+
+    #[test]
+    fn clone_polyline_to_layer_gets_new_handles() {
+        let mut drawing = Drawing::new();
+        let mut poly = Polyline::default();
+        poly.add_vertex(&mut drawing, Vertex::default());
+        let original = drawing
+            .add_entity(Entity::new(EntityType::Polyline(poly)))
+            .clone();
+        let copy = drawing.add_entity(original.clone_to_layer("other")).clone();
+        assert_eq!("other", copy.common.layer);
+        assert_ne!(original.common.handle, copy.common.handle);
+        let vertex_handle = |e: &Entity| match e.specific {
+            EntityType::Polyline(ref p) => p.__vertices_and_handles[0].1,
+            _ => panic!("expected a polyline"),
+        };
+        assert_ne!(vertex_handle(&original), vertex_handle(&copy));
+    }
+
+    #[test]
+    fn circle_from_diameter() {
+        let circle = Circle::from_diameter(Point::new(1.0, 2.0, 0.0), 5.0);
+        assert_eq!(2.5, circle.radius);
+        let circle = Circle::from_two_points_diameter(
+            Point::new(-1.0, 2.0, 0.0),
+            Point::new(5.0, 10.0, 0.0),
+        );
+        assert_eq!(Point::new(2.0, 6.0, 0.0), circle.center);
+        assert_eq!(5.0, circle.radius);
+    }
+
+    #[test]
+    fn ellipse_from_axes() {
+        let ellipse = Ellipse::from_axes(Point::origin(), Vector::new(4.0, 0.0, 0.0), 0.5);
+        assert_eq!(Vector::new(4.0, 0.0, 0.0), ellipse.major_axis);
+        assert_eq!(0.5, ellipse.minor_axis_ratio);
+        assert_eq!(Vector::z_axis(), ellipse.normal);
+
+        // a ratio over 1 swaps the axes
+        let ellipse = Ellipse::from_axes(Point::origin(), Vector::new(2.0, 0.0, 0.0), 2.0);
+        assert_eq!(Vector::new(0.0, 4.0, 0.0), ellipse.major_axis);
+        assert_eq!(0.5, ellipse.minor_axis_ratio);
+
+        let ellipse =
+            Ellipse::from_bounding_box(Point::new(0.0, 0.0, 0.0), Point::new(2.0, 8.0, 0.0));
+        assert_eq!(Point::new(1.0, 4.0, 0.0), ellipse.center);
+        assert_eq!(Vector::new(0.0, 4.0, 0.0), ellipse.major_axis);
+        assert_eq!(0.25, ellipse.minor_axis_ratio);
+    }
+
+    #[test]
+    fn extrusion_direction_defaults_to_z_axis() {
+        let line = read_entity("LINE", vec![CodePair::new_f64(10, 1.0)]);
+        assert_eq!(Vector::z_axis(), line.extrusion_direction());
+
+        let circle = read_entity(
+            "CIRCLE",
+            vec![
+                CodePair::new_f64(210, 0.0),
+                CodePair::new_f64(220, 0.0),
+                CodePair::new_f64(230, -1.0),
+            ],
+        );
+        assert_eq!(Vector::new(0.0, 0.0, -1.0), circle.extrusion_direction());
+
+        let unset = Entity::new(EntityType::Line(Line {
+            extrusion_direction: Vector::zero(),
+            ..Default::default()
+        }));
+        assert_eq!(Vector::z_axis(), unset.extrusion_direction());
+
+        let face = Entity::new(EntityType::Face3D(Face3D::default()));
+        assert_eq!(Vector::z_axis(), face.extrusion_direction());
+    }
+
+    #[test]
+    fn dimension_base_of_dimensions() {
+        let mut dim = Entity::new(EntityType::RadialDimension(RadialDimension::default()));
+        dim.dimension_base_mut().unwrap().block_name = String::from("*D1");
+        assert_eq!("*D1", dim.dimension_base().unwrap().block_name);
+
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        assert!(line.dimension_base().is_none());
+        assert!(line.dimension_base_mut().is_none());
+    }
+
+    #[test]
+    fn read_interleaved_x_data() {
+        let ent = read_entity(
+            "LINE",
+            vec![
+                CodePair::new_str(1001, "APP1"),
+                CodePair::new_str(1000, "first"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_str(1001, "APP2"),
+                CodePair::new_i16(1070, 2),
+                CodePair::new_str(1001, "APP3"),
+                CodePair::new_f64(11, 3.0),
+            ],
+        );
+        let line = ent.as_line().unwrap();
+        assert_eq!(Point::new(1.0, 0.0, 0.0), line.p1);
+        assert_eq!(Point::new(3.0, 0.0, 0.0), line.p2);
+        let x_data = ent
+            .common
+            .x_data
+            .iter()
+            .map(|x| (x.application_name.as_str(), x.items.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("APP1", vec![XDataItem::Str(String::from("first"))]),
+                ("APP2", vec![XDataItem::Integer(2)]),
+                ("APP3", vec![]),
+            ],
+            x_data
+        );
+
+        // entities with a custom reader
+        for entity_type in ["MTEXT", "LWPOLYLINE", "SPLINE"] {
+            let ent = read_entity(
+                entity_type,
+                vec![
+                    CodePair::new_str(1001, "APP1"),
+                    CodePair::new_str(1000, "first"),
+                    CodePair::new_str(8, "layer"),
+                    CodePair::new_str(1001, "APP2"),
+                    CodePair::new_i16(1070, 2),
+                ],
+            );
+            assert_eq!("layer", ent.common.layer);
+            assert_eq!(2, ent.common.x_data.len());
+            assert_eq!("APP2", ent.common.x_data[1].application_name);
+        }
+    }
+
+    #[test]
+    fn polyline_widths_at_vertices() {
+        let poly = read_entity(
+            "LWPOLYLINE",
+            vec![
+                CodePair::new_i32(90, 3),
+                CodePair::new_f64(43, 0.5),
+                CodePair::new_f64(10, 0.0),
+                CodePair::new_f64(20, 0.0),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_f64(20, 0.0),
+                CodePair::new_f64(40, 1.0),
+                CodePair::new_f64(41, 2.0),
+                CodePair::new_f64(10, 2.0),
+                CodePair::new_f64(20, 0.0),
+                CodePair::new_f64(41, 3.0),
+            ],
+        );
+        let poly = poly.as_lw_polyline().unwrap();
+        assert_eq!((0.5, 0.5), poly.width_at_vertex(0));
+        assert_eq!((1.0, 2.0), poly.width_at_vertex(1));
+        assert_eq!((0.0, 3.0), poly.width_at_vertex(2));
+
+        let mut poly = Polyline {
+            default_starting_width: 0.25,
+            default_ending_width: 0.75,
+            ..Default::default()
+        };
+        let mut drawing = Drawing::new();
+        poly.add_vertex(&mut drawing, Vertex::new(Point::origin()));
+        poly.add_vertex(
+            &mut drawing,
+            Vertex {
+                starting_width: 1.5,
+                ending_width: 1.5,
+                ..Vertex::new(Point::new(1.0, 0.0, 0.0))
+            },
+        );
+        assert_eq!((0.25, 0.75), poly.width_at_vertex(0));
+        assert_eq!((1.5, 1.5), poly.width_at_vertex(1));
+    }
 }
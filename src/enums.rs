@@ -1,5 +1,8 @@
 extern crate num;
 
+extern crate encoding_rs;
+use self::encoding_rs::Encoding;
+
 use crate::{DxfError, DxfResult};
 use std::fmt;
 
@@ -18,7 +21,7 @@ pub enum Dwf3DPrecision {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum AcadVersion {
     Version_1_0,
@@ -96,6 +99,20 @@ impl AcadVersion {
             _ => AcadVersion::R12, // default to R12
         }
     }
+    /// Returns `true` for R13 and later, which always write handles, subclass markers, and the
+    /// `CLASSES` and `OBJECTS` sections.
+    pub fn is_r13_or_later(self) -> bool {
+        self >= AcadVersion::R13
+    }
+    /// Returns `true` if 24-bit colors can be written, i.e., R2004 and later.
+    pub fn supports_true_color(self) -> bool {
+        self >= AcadVersion::R2004
+    }
+    /// Returns `true` if text is written as UTF-8 rather than in the drawing's code page, i.e.,
+    /// R2007 and later.
+    pub fn is_utf8(self) -> bool {
+        self >= AcadVersion::R2007
+    }
 }
 
 impl fmt::Display for AcadVersion {
@@ -193,6 +210,91 @@ pub enum BottomTextAttachmentDirection {
 }
 }
 
+/// The text encoding named by `$DWGCODEPAGE`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum CodePage {
+    Ansi874,
+    Ansi932,
+    Ansi936,
+    Ansi949,
+    Ansi950,
+    Ansi1250,
+    Ansi1251,
+    Ansi1252,
+    Ansi1253,
+    Ansi1254,
+    Ansi1255,
+    Ansi1256,
+    Ansi1257,
+    Ansi1258,
+    Utf8,
+}
+
+impl CodePage {
+    /// Parses a `$DWGCODEPAGE` value such as `ANSI_1252`, ignoring case.
+    pub fn from_name(name: &str) -> Option<CodePage> {
+        let code_page = match &*name.trim().to_ascii_uppercase() {
+            "ANSI_874" => CodePage::Ansi874,
+            "ANSI_932" => CodePage::Ansi932,
+            "ANSI_936" => CodePage::Ansi936,
+            "ANSI_949" => CodePage::Ansi949,
+            "ANSI_950" => CodePage::Ansi950,
+            "ANSI_1250" => CodePage::Ansi1250,
+            "ANSI_1251" => CodePage::Ansi1251,
+            "ANSI_1252" => CodePage::Ansi1252,
+            "ANSI_1253" => CodePage::Ansi1253,
+            "ANSI_1254" => CodePage::Ansi1254,
+            "ANSI_1255" => CodePage::Ansi1255,
+            "ANSI_1256" => CodePage::Ansi1256,
+            "ANSI_1257" => CodePage::Ansi1257,
+            "ANSI_1258" => CodePage::Ansi1258,
+            "UTF-8" | "UTF8" => CodePage::Utf8,
+            _ => return None,
+        };
+        Some(code_page)
+    }
+    /// Returns the `$DWGCODEPAGE` name of the code page.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CodePage::Ansi874 => "ANSI_874",
+            CodePage::Ansi932 => "ANSI_932",
+            CodePage::Ansi936 => "ANSI_936",
+            CodePage::Ansi949 => "ANSI_949",
+            CodePage::Ansi950 => "ANSI_950",
+            CodePage::Ansi1250 => "ANSI_1250",
+            CodePage::Ansi1251 => "ANSI_1251",
+            CodePage::Ansi1252 => "ANSI_1252",
+            CodePage::Ansi1253 => "ANSI_1253",
+            CodePage::Ansi1254 => "ANSI_1254",
+            CodePage::Ansi1255 => "ANSI_1255",
+            CodePage::Ansi1256 => "ANSI_1256",
+            CodePage::Ansi1257 => "ANSI_1257",
+            CodePage::Ansi1258 => "ANSI_1258",
+            CodePage::Utf8 => "UTF-8",
+        }
+    }
+    pub(crate) fn encoding(&self) -> &'static Encoding {
+        match self {
+            CodePage::Ansi874 => encoding_rs::WINDOWS_874,
+            CodePage::Ansi932 => encoding_rs::SHIFT_JIS,
+            CodePage::Ansi936 => encoding_rs::GBK,
+            CodePage::Ansi949 => encoding_rs::EUC_KR,
+            CodePage::Ansi950 => encoding_rs::BIG5,
+            CodePage::Ansi1250 => encoding_rs::WINDOWS_1250,
+            CodePage::Ansi1251 => encoding_rs::WINDOWS_1251,
+            CodePage::Ansi1252 => encoding_rs::WINDOWS_1252,
+            CodePage::Ansi1253 => encoding_rs::WINDOWS_1253,
+            CodePage::Ansi1254 => encoding_rs::WINDOWS_1254,
+            CodePage::Ansi1255 => encoding_rs::WINDOWS_1255,
+            CodePage::Ansi1256 => encoding_rs::WINDOWS_1256,
+            CodePage::Ansi1257 => encoding_rs::WINDOWS_1257,
+            CodePage::Ansi1258 => encoding_rs::WINDOWS_1258,
+            CodePage::Utf8 => encoding_rs::UTF_8,
+        }
+    }
+}
+
 enum_from_primitive! {
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -1,5 +1,5 @@
 /// Represents the expected data type of a `CodePair`.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ExpectedType {
     Boolean,
     Integer,
@@ -0,0 +1,238 @@
+//! Basic 2D intersection routines for snapping and analysis.
+//!
+//! All calculations are done in the XY plane: Z coordinates are ignored, the returned points have
+//! a Z of `0.0`, and curves are assumed to have a normal of +Z.  Lines are treated as bounded
+//! segments.  Tangent curves produce a single point and overlapping curves produce none.
+
+use std::f64::consts::PI;
+
+use crate::entities::{Arc, Circle, Ellipse, Line};
+use crate::Point;
+
+const EPSILON: f64 = 1e-10;
+
+/// Returns the intersection point of two line segments, if any.
+pub fn line_line(a: &Line, b: &Line) -> Vec<Point> {
+    let (dax, day) = (a.p2.x - a.p1.x, a.p2.y - a.p1.y);
+    let (dbx, dby) = (b.p2.x - b.p1.x, b.p2.y - b.p1.y);
+    let denominator = dax * dby - day * dbx;
+    if denominator.abs() < EPSILON {
+        // parallel or degenerate
+        return vec![];
+    }
+
+    let (ox, oy) = (b.p1.x - a.p1.x, b.p1.y - a.p1.y);
+    let t = (ox * dby - oy * dbx) / denominator;
+    let u = (ox * day - oy * dax) / denominator;
+    if is_on_segment(t) && is_on_segment(u) {
+        vec![Point::new(a.p1.x + dax * t, a.p1.y + day * t, 0.0)]
+    } else {
+        vec![]
+    }
+}
+
+/// Returns the intersection points of a line segment and a circle.
+pub fn line_circle(line: &Line, circle: &Circle) -> Vec<Point> {
+    line_axis_aligned_ellipse(
+        line,
+        &circle.center,
+        circle.radius,
+        circle.radius,
+        (1.0, 0.0),
+    )
+    .into_iter()
+    .map(|(p, _)| p)
+    .collect()
+}
+
+/// Returns the intersection points of a line segment and an arc.
+pub fn line_arc(line: &Line, arc: &Arc) -> Vec<Point> {
+    let start = arc.start_angle.to_radians();
+    let end = arc.end_angle.to_radians();
+    line_axis_aligned_ellipse(line, &arc.center, arc.radius, arc.radius, (1.0, 0.0))
+        .into_iter()
+        .filter(|(_, angle)| is_angle_between(*angle, start, end))
+        .map(|(p, _)| p)
+        .collect()
+}
+
+/// Returns the intersection points of a line segment and an ellipse, respecting the ellipse's
+/// start and end parameters.
+pub fn line_ellipse(line: &Line, ellipse: &Ellipse) -> Vec<Point> {
+    let major = (ellipse.major_axis.x.powi(2) + ellipse.major_axis.y.powi(2)).sqrt();
+    if major < EPSILON {
+        return vec![];
+    }
+
+    let axis = (ellipse.major_axis.x / major, ellipse.major_axis.y / major);
+    line_axis_aligned_ellipse(
+        line,
+        &ellipse.center,
+        major,
+        major * ellipse.minor_axis_ratio,
+        axis,
+    )
+    .into_iter()
+    .filter(|(_, parameter)| {
+        is_angle_between(*parameter, ellipse.start_parameter, ellipse.end_parameter)
+    })
+    .map(|(p, _)| p)
+    .collect()
+}
+
+/// Returns the intersection points of two circles.
+pub fn circle_circle(a: &Circle, b: &Circle) -> Vec<Point> {
+    let (dx, dy) = (b.center.x - a.center.x, b.center.y - a.center.y);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance < EPSILON
+        || distance > a.radius + b.radius + EPSILON
+        || distance < (a.radius - b.radius).abs() - EPSILON
+    {
+        // concentric, separate, or one inside the other
+        return vec![];
+    }
+
+    // distance from `a`'s center to the chord between the intersection points
+    let along =
+        (a.radius * a.radius - b.radius * b.radius + distance * distance) / (2.0 * distance);
+    let (ux, uy) = (dx / distance, dy / distance);
+    let (mx, my) = (a.center.x + ux * along, a.center.y + uy * along);
+    let h_squared = a.radius * a.radius - along * along;
+    if h_squared <= EPSILON * a.radius.max(1.0) {
+        return vec![Point::new(mx, my, 0.0)];
+    }
+
+    let h = h_squared.sqrt();
+    vec![
+        Point::new(mx - uy * h, my + ux * h, 0.0),
+        Point::new(mx + uy * h, my - ux * h, 0.0),
+    ]
+}
+
+fn is_on_segment(t: f64) -> bool {
+    (-EPSILON..=1.0 + EPSILON).contains(&t)
+}
+
+/// Returns whether `angle` lies on the counter-clockwise sweep from `start` to `end`, all in
+/// radians.
+fn is_angle_between(angle: f64, start: f64, end: f64) -> bool {
+    let full = 2.0 * PI;
+    let sweep = (end - start).rem_euclid(full);
+    let sweep = if sweep < EPSILON { full } else { sweep };
+    (angle - start).rem_euclid(full) <= sweep + EPSILON
+}
+
+/// Intersects a line segment with the ellipse centered at `center` with semi-axes `a` along
+/// `axis` and `b` perpendicular to it.  Returns each point with its angular parameter.
+fn line_axis_aligned_ellipse(
+    line: &Line,
+    center: &Point,
+    a: f64,
+    b: f64,
+    axis: (f64, f64),
+) -> Vec<(Point, f64)> {
+    if a < EPSILON || b < EPSILON {
+        return vec![];
+    }
+
+    // map the line into the ellipse's frame scaled to a unit circle
+    let to_local = |x: f64, y: f64| {
+        let (x, y) = (x - center.x, y - center.y);
+        (
+            (x * axis.0 + y * axis.1) / a,
+            (-x * axis.1 + y * axis.0) / b,
+        )
+    };
+    let (px, py) = to_local(line.p1.x, line.p1.y);
+    let (qx, qy) = to_local(line.p2.x, line.p2.y);
+    let (dx, dy) = (qx - px, qy - py);
+    let qa = dx * dx + dy * dy;
+    if qa < EPSILON {
+        return vec![];
+    }
+
+    let qb = 2.0 * (px * dx + py * dy);
+    let qc = px * px + py * py - 1.0;
+    let discriminant = qb * qb - 4.0 * qa * qc;
+    if discriminant < -EPSILON {
+        return vec![];
+    }
+
+    let ts = if discriminant.abs() <= EPSILON {
+        vec![-qb / (2.0 * qa)]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(-qb - root) / (2.0 * qa), (-qb + root) / (2.0 * qa)]
+    };
+    ts.into_iter()
+        .filter(|t| is_on_segment(*t))
+        .map(|t| {
+            let (x, y) = (px + dx * t, py + dy * t);
+            let point = Point::new(
+                center.x + a * x * axis.0 - b * y * axis.1,
+                center.y + a * x * axis.1 + b * y * axis.0,
+                0.0,
+            );
+            (point, y.atan2(x).rem_euclid(2.0 * PI))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::geometry::*;
+    use crate::*;
+    use float_cmp::approx_eq;
+
+    fn assert_points(expected: &[(f64, f64)], actual: &[Point]) {
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(actual) {
+            assert!(approx_eq!(f64, e.0, a.x, epsilon = 1e-9));
+            assert!(approx_eq!(f64, e.1, a.y, epsilon = 1e-9));
+        }
+    }
+
+    #[test]
+    fn two_circles() {
+        let a = Circle::new(Point::origin(), 5.0);
+        let b = Circle::new(Point::new(8.0, 0.0, 0.0), 5.0);
+        assert_points(&[(4.0, 3.0), (4.0, -3.0)], &circle_circle(&a, &b));
+
+        let tangent = Circle::new(Point::new(10.0, 0.0, 0.0), 5.0);
+        assert_points(&[(5.0, 0.0)], &circle_circle(&a, &tangent));
+
+        let separate = Circle::new(Point::new(11.0, 0.0, 0.0), 5.0);
+        assert!(circle_circle(&a, &separate).is_empty());
+        assert!(circle_circle(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn two_lines() {
+        let a = Line::new(Point::origin(), Point::new(2.0, 2.0, 0.0));
+        let b = Line::new(Point::new(0.0, 2.0, 0.0), Point::new(2.0, 0.0, 0.0));
+        assert_points(&[(1.0, 1.0)], &line_line(&a, &b));
+
+        let short = Line::new(Point::new(0.0, 2.0, 0.0), Point::new(0.5, 1.5, 0.0));
+        assert!(line_line(&a, &short).is_empty());
+        let parallel = Line::new(Point::new(1.0, 0.0, 0.0), Point::new(3.0, 2.0, 0.0));
+        assert!(line_line(&a, &parallel).is_empty());
+    }
+
+    #[test]
+    fn line_and_curves() {
+        let line = Line::new(Point::new(-10.0, 0.0, 0.0), Point::new(10.0, 0.0, 0.0));
+        let circle = Circle::new(Point::origin(), 2.0);
+        assert_points(&[(-2.0, 0.0), (2.0, 0.0)], &line_circle(&line, &circle));
+
+        let arc = Arc::new(Point::origin(), 2.0, 270.0, 90.0);
+        assert_points(&[(2.0, 0.0)], &line_arc(&line, &arc));
+
+        let ellipse = Ellipse {
+            major_axis: Vector::new(0.0, 4.0, 0.0),
+            minor_axis_ratio: 0.5,
+            ..Default::default()
+        };
+        assert_points(&[(-2.0, 0.0), (2.0, 0.0)], &line_ellipse(&line, &ellipse));
+    }
+}
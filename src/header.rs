@@ -3,11 +3,143 @@
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::enums::*;
 use crate::helper_functions::*;
-use crate::{CodePair, DxfError, DxfResult};
+use crate::tables::Ucs;
+use crate::{CodePair, DxfResult, Handle};
+
+extern crate chrono;
+use self::chrono::{Local, NaiveDateTime, TimeZone};
+
+extern crate uuid;
+use self::uuid::Uuid;
 
 pub use crate::generated::header::*;
 
+/// The commonly used `$DIM*` header variables used to lay out dimensions.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DimensionSettings {
+    /// The current dimension style name.  `$DIMSTYLE`
+    pub style_name: String,
+    /// The overall dimensioning scale factor.  `$DIMSCALE`
+    pub scale: f64,
+    /// The dimensioning text height.  `$DIMTXT`
+    pub text_height: f64,
+    /// The dimensioning text style.  `$DIMTXSTY`
+    pub text_style: String,
+    /// The dimensioning arrow size.  `$DIMASZ`
+    pub arrow_size: f64,
+    /// The dimensioning tick size; 0 draws arrows instead.  `$DIMTSZ`
+    pub tick_size: f64,
+    /// The extension line offset from the origin points.  `$DIMEXO`
+    pub extension_line_offset: f64,
+    /// The extension line extension beyond the dimension line.  `$DIMEXE`
+    pub extension_line_extension: f64,
+    /// The dimension line increment for continued dimensions.  `$DIMDLI`
+    pub line_increment: f64,
+    /// The gap between the dimension line and its text.  `$DIMGAP`
+    pub line_gap: f64,
+    /// The size of center marks.  `$DIMCEN`
+    pub center_mark_size: f64,
+}
+
 impl Header {
+    /// Creates a `Header` for a new drawing of the specified version with handles enabled and the
+    /// handle seed reset.
+    pub fn for_version(version: AcadVersion) -> Self {
+        Header {
+            version,
+            handles_enabled: true,
+            next_available_handle: Handle(1),
+            ..Default::default()
+        }
+    }
+    /// Sets `$INSUNITS` and the matching English or metric `$MEASUREMENT` value.
+    pub fn with_units(mut self, units: Units) -> Self {
+        self.default_drawing_units = units;
+        self.drawing_units = match units {
+            Units::Unitless => self.drawing_units,
+            Units::Millimeters
+            | Units::Centimeters
+            | Units::Meters
+            | Units::Kilometers
+            | Units::Angstroms
+            | Units::Nanometers
+            | Units::Microns
+            | Units::Decimeters
+            | Units::Decameters
+            | Units::Hectometers
+            | Units::Gigameters => DrawingUnits::Metric,
+            _ => DrawingUnits::English,
+        };
+        self
+    }
+    /// Returns the common dimensioning variables.
+    pub fn dimension_settings(&self) -> DimensionSettings {
+        DimensionSettings {
+            style_name: self.dimension_style_name.clone(),
+            scale: self.dimensioning_scale_factor,
+            text_height: self.dimensioning_text_height,
+            text_style: self.dimension_text_style.clone(),
+            arrow_size: self.dimensioning_arrow_size,
+            tick_size: self.dimensioning_tick_size,
+            extension_line_offset: self.dimension_extension_line_offset,
+            extension_line_extension: self.dimension_extension_line_extension,
+            line_increment: self.dimension_line_increment,
+            line_gap: self.dimension_line_gap,
+            center_mark_size: self.center_mark_size,
+        }
+    }
+    /// Sets the common dimensioning variables.
+    pub fn set_dimension_settings(&mut self, settings: &DimensionSettings) {
+        self.dimension_style_name = settings.style_name.clone();
+        self.dimensioning_scale_factor = settings.scale;
+        self.dimensioning_text_height = settings.text_height;
+        self.dimension_text_style = settings.text_style.clone();
+        self.dimensioning_arrow_size = settings.arrow_size;
+        self.dimensioning_tick_size = settings.tick_size;
+        self.dimension_extension_line_offset = settings.extension_line_offset;
+        self.dimension_extension_line_extension = settings.extension_line_extension;
+        self.dimension_line_increment = settings.line_increment;
+        self.dimension_line_gap = settings.line_gap;
+        self.center_mark_size = settings.center_mark_size;
+    }
+    /// Returns `true` if entity and table handles are written, either because the version requires
+    /// them or because `$HANDLING` is set.  `$HANDLING` is set by default, so handles are written
+    /// for every version unless it's cleared, in which case R12 and earlier drawings are written
+    /// without handles even if entities have them assigned.
+    pub fn supports_handles(&self) -> bool {
+        self.version.is_r13_or_later() || self.handles_enabled
+    }
+    /// Returns `true` if the drawing was saved with model space active and `false` if a paper space
+    /// layout was active, from `$TILEMODE`.
+    pub fn is_model_space_active(&self) -> bool {
+        self.previous_release_tile_compatability
+    }
+    /// Sets `$TILEMODE` to make model space or the current paper space layout active.
+    pub fn set_is_model_space_active(&mut self, val: bool) {
+        self.previous_release_tile_compatability = val;
+    }
+    /// Assigns a new `$VERSIONGUID` to mark the drawing as modified.  `$FINGERPRINTGUID` is kept.
+    pub fn regenerate_version_guid(&mut self) {
+        self.version_guid = Uuid::new_v4();
+    }
+    /// Returns the active UCS described by `$UCSNAME`, `$UCSORG`, `$UCSXDIR`, and `$UCSYDIR`.
+    pub fn current_ucs(&self) -> Ucs {
+        Ucs {
+            name: self.ucs_name.clone(),
+            origin: self.ucs_origin.clone(),
+            x_axis: self.ucs_x_axis.clone(),
+            y_axis: self.ucs_y_axis.clone(),
+            ..Default::default()
+        }
+    }
+    /// Sets the active UCS header variables from `ucs`.
+    pub fn set_current_ucs(&mut self, ucs: &Ucs) {
+        self.ucs_name = ucs.name.clone();
+        self.ucs_origin = ucs.origin.clone();
+        self.ucs_x_axis = ucs.x_axis.clone();
+        self.ucs_y_axis = ucs.y_axis.clone();
+    }
     /// Ensure all values are valid.
     pub fn normalize(&mut self) {
         ensure_positive_or_default(&mut self.default_text_height, 0.2);
@@ -18,6 +150,114 @@ impl Header {
         default_if_empty(&mut self.dimension_style_name, "STANDARD");
         default_if_empty(&mut self.file_name, ".");
     }
+    /// Returns the `$DWGCODEPAGE` value, or `None` if it isn't a recognized code page.
+    pub fn code_page(&self) -> Option<CodePage> {
+        CodePage::from_name(&self.drawing_code_page)
+    }
+    /// Sets the `$DWGCODEPAGE` value.  R2007 and later files are always written as UTF-8.
+    pub fn set_code_page(&mut self, code_page: CodePage) {
+        self.drawing_code_page = String::from(code_page.name());
+    }
+    /// Returns the custom document properties as `(tag, value)` pairs.  Only written for R2010
+    /// and later.
+    pub fn custom_properties(&self) -> &[(String, String)] {
+        &self.__custom_properties
+    }
+    /// Sets the value of a custom document property, adding it if it doesn't exist.
+    pub fn set_custom_property(&mut self, tag: &str, value: &str) {
+        match self.__custom_properties.iter_mut().find(|p| p.0 == tag) {
+            Some(property) => property.1 = String::from(value),
+            None => self
+                .__custom_properties
+                .push((String::from(tag), String::from(value))),
+        }
+    }
+    /// Removes the custom document property with the specified tag.
+    pub fn remove_custom_property(&mut self, tag: &str) {
+        self.__custom_properties.retain(|p| p.0 != tag);
+    }
+    /// Returns the `$TDCREATE` value as a calendar date and time.
+    pub fn creation_date_naive(&self) -> NaiveDateTime {
+        self.creation_date.naive_local()
+    }
+    /// Sets the `$TDCREATE` value from a calendar date and time.
+    pub fn set_creation_date_naive(&mut self, date: NaiveDateTime) {
+        self.creation_date = Local
+            .from_local_datetime(&date)
+            .earliest()
+            .unwrap_or_else(|| Local.from_utc_datetime(&date));
+    }
+    /// Returns the raw `$TDCREATE` value as a Julian date.
+    pub fn creation_date_julian(&self) -> f64 {
+        as_double_local(self.creation_date)
+    }
+    /// Sets the `$TDCREATE` value from a raw Julian date.
+    pub fn set_creation_date_julian(&mut self, date: f64) {
+        self.creation_date = as_datetime_local(date);
+    }
+    /// Returns the `$TDUPDATE` value as a calendar date and time.
+    pub fn update_date_naive(&self) -> NaiveDateTime {
+        self.update_date.naive_local()
+    }
+    /// Sets the `$TDUPDATE` value from a calendar date and time.
+    pub fn set_update_date_naive(&mut self, date: NaiveDateTime) {
+        self.update_date = Local
+            .from_local_datetime(&date)
+            .earliest()
+            .unwrap_or_else(|| Local.from_utc_datetime(&date));
+    }
+    /// Returns the raw `$TDUPDATE` value as a Julian date.
+    pub fn update_date_julian(&self) -> f64 {
+        as_double_local(self.update_date)
+    }
+    /// Sets the `$TDUPDATE` value from a raw Julian date.
+    pub fn set_update_date_julian(&mut self, date: f64) {
+        self.update_date = as_datetime_local(date);
+    }
+    /// Returns the header variables that were read but aren't otherwise supported, with their code
+    /// pairs.  They're written back unchanged when saving.
+    pub fn unknown_variables(&self) -> &[(String, Vec<CodePair>)] {
+        &self.__unknown_variables
+    }
+    /// Formats an angle in degrees using `$AUNITS` and `$AUPREC`, similar to AutoLISP's `angtos`,
+    /// e.g., `45.00`, `45d30'0"`, `50.0g`, `0.785r`, or `N 45d0'0" E`.  The angle is formatted as
+    /// given; `$ANGBASE` and `$ANGDIR` aren't applied.
+    pub fn format_angle(&self, degrees: f64) -> String {
+        let precision = self.angle_unit_precision.clamp(0, 8) as usize;
+        match self.angle_unit_format {
+            AngleFormat::DecimalDegrees => format!("{:.*}", precision, degrees),
+            AngleFormat::DegreesMinutesSeconds => {
+                format_degrees_minutes_seconds(degrees, precision)
+            }
+            AngleFormat::Gradians => format!("{:.*}g", precision, degrees * 400.0 / 360.0),
+            AngleFormat::Radians => format!("{:.*}r", precision, degrees.to_radians()),
+            AngleFormat::SurveyorsUnits => {
+                // bearings are measured from north or south towards east or west
+                let degrees = degrees.rem_euclid(360.0);
+                let (north_south, east_west, bearing) = if degrees <= 90.0 {
+                    ('N', 'E', 90.0 - degrees)
+                } else if degrees <= 180.0 {
+                    ('N', 'W', degrees - 90.0)
+                } else if degrees <= 270.0 {
+                    ('S', 'W', 270.0 - degrees)
+                } else {
+                    ('S', 'E', degrees - 270.0)
+                };
+                if bearing == 0.0 {
+                    String::from(north_south)
+                } else if bearing == 90.0 {
+                    String::from(east_west)
+                } else {
+                    format!(
+                        "{} {} {}",
+                        north_south,
+                        format_degrees_minutes_seconds(bearing, precision),
+                        east_west
+                    )
+                }
+            }
+        }
+    }
     pub(crate) fn read(iter: &mut CodePairPutBack) -> DxfResult<Header> {
         let mut header = Header::default();
         loop {
@@ -30,6 +270,12 @@ impl Header {
                         }
                         9 => {
                             let last_header_variable = pair.assert_string()?;
+                            let is_known = Header::is_known_variable(&last_header_variable);
+                            if !is_known {
+                                header
+                                    .__unknown_variables
+                                    .push((last_header_variable.clone(), vec![]));
+                            }
                             loop {
                                 match iter.next() {
                                     Some(Ok(pair)) => {
@@ -37,13 +283,47 @@ impl Header {
                                             // ENDSEC or a new header variable
                                             iter.put_back(Ok(pair));
                                             break;
+                                        } else if last_header_variable == "$CUSTOMPROPERTYTAG" {
+                                            header
+                                                .__custom_properties
+                                                .push((pair.assert_string()?, String::new()));
+                                        } else if last_header_variable == "$PREVIEWIMAGE" {
+                                            // the 90 code is the length of the 310 data
+                                            if pair.code == 310 {
+                                                header
+                                                    .__preview_image_data
+                                                    .extend(pair.assert_binary()?);
+                                            }
+                                        } else if last_header_variable == "$CUSTOMPROPERTY" {
+                                            if let Some(property) =
+                                                header.__custom_properties.last_mut()
+                                            {
+                                                property.1 = pair.assert_string()?;
+                                            }
+                                        } else if !is_known {
+                                            if let Some(variable) =
+                                                header.__unknown_variables.last_mut()
+                                            {
+                                                variable.1.push(pair);
+                                            }
                                         } else {
+                                            let pair = Header::with_expected_code(
+                                                &last_header_variable,
+                                                pair,
+                                            );
                                             header
                                                 .set_header_value(&last_header_variable, &pair)?;
                                             if last_header_variable == "$ACADVER"
-                                                && header.version >= AcadVersion::R2007
+                                                && header.version.is_utf8()
+                                            {
+                                                iter.read_with_encoding(encoding_rs::UTF_8);
+                                            } else if last_header_variable == "$DWGCODEPAGE"
+                                                && !header.version.is_utf8()
+                                                && iter.use_code_page()
                                             {
-                                                iter.read_as_utf8();
+                                                if let Some(code_page) = header.code_page() {
+                                                    iter.read_with_encoding(code_page.encoding());
+                                                }
                                             }
                                         }
                                     }
@@ -52,7 +332,7 @@ impl Header {
                                 }
                             }
                         }
-                        _ => return Err(DxfError::UnexpectedCodePair(pair, String::from(""))),
+                        _ => (), // stray pair outside of a variable
                     }
                 }
                 Some(Err(e)) => return Err(e),
@@ -62,19 +342,82 @@ impl Header {
 
         Ok(header)
     }
+    /// Returns `pair` with the code the spec expects for `variable` when it's one of the values
+    /// other applications are known to write with a different code, e.g., LibreCAD writes `$DIMZIN`
+    /// with code 280.  Any other unexpected code is still an error.
+    fn with_expected_code(variable: &str, pair: CodePair) -> CodePair {
+        match (variable, pair.code) {
+            ("$DIMZIN", 280) => CodePair::new(70, pair.value, pair.offset),
+            _ => pair,
+        }
+    }
     pub(crate) fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
         pairs.push(CodePair::new_str(0, "SECTION"));
         pairs.push(CodePair::new_str(2, "HEADER"));
         self.add_code_pairs_internal(pairs);
+        if self.version >= AcadVersion::R2010 {
+            for (tag, value) in &self.__custom_properties {
+                pairs.push(CodePair::new_str(9, "$CUSTOMPROPERTYTAG"));
+                pairs.push(CodePair::new_string(1, tag));
+                pairs.push(CodePair::new_str(9, "$CUSTOMPROPERTY"));
+                pairs.push(CodePair::new_string(1, value));
+            }
+        }
+        if !self.__preview_image_data.is_empty() {
+            pairs.push(CodePair::new_str(9, "$PREVIEWIMAGE"));
+            pairs.push(CodePair::new_i32(
+                90,
+                self.__preview_image_data.len() as i32,
+            ));
+            for chunk in self.__preview_image_data.chunks(128) {
+                pairs.push(CodePair::new_binary(310, chunk.to_vec()));
+            }
+        }
+        for (name, values) in &self.__unknown_variables {
+            pairs.push(CodePair::new_string(9, name));
+            pairs.extend(values.iter().cloned());
+        }
         pairs.push(CodePair::new_str(0, "ENDSEC"));
     }
 }
 
+/// Formats degrees as `DdM'S"`, with the precision selecting which parts are included as in
+/// AutoLISP's `angtos`.
+fn format_degrees_minutes_seconds(degrees: f64, precision: usize) -> String {
+    let sign = if degrees < 0.0 { "-" } else { "" };
+    let degrees = degrees.abs();
+    match precision {
+        0 => format!("{}{}d", sign, degrees.round()),
+        1 | 2 => {
+            let total_minutes = (degrees * 60.0).round();
+            format!(
+                "{}{}d{}'",
+                sign,
+                (total_minutes / 60.0).floor(),
+                total_minutes % 60.0
+            )
+        }
+        _ => {
+            let decimals = precision.saturating_sub(4);
+            let scale = 10f64.powi(decimals as i32);
+            let total_seconds = (degrees * 3600.0 * scale).round() / scale;
+            let whole_degrees = (total_seconds / 3600.0).floor();
+            let minutes = ((total_seconds - whole_degrees * 3600.0) / 60.0).floor();
+            let seconds = total_seconds - whole_degrees * 3600.0 - minutes * 60.0;
+            format!(
+                "{}{}d{}'{:.*}\"",
+                sign, whole_degrees, minutes, decimals, seconds
+            )
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::entities::*;
     use crate::enums::*;
     use crate::helper_functions::tests::*;
+    use crate::tables::*;
     use crate::*;
     use std::time::Duration;
 
@@ -204,6 +547,61 @@ mod tests {
         )
     }
 
+    #[test]
+    fn read_unexpected_code_written_by_librecad() {
+        let drawing = from_section(
+            "HEADER",
+            vec![CodePair::new_str(9, "$DIMZIN"), CodePair::new_i16(280, 1)],
+        );
+        assert_eq!(
+            UnitZeroSuppression::IncludeZeroFeetAndZeroInches,
+            drawing.header.dimension_unit_zero_suppression
+        );
+    }
+
+    #[test]
+    fn read_other_unexpected_code_is_an_error() {
+        let contents = [
+            "0",
+            "SECTION",
+            "2",
+            "HEADER",
+            "9",
+            "$TEXTSIZE",
+            "70",
+            "2",
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]
+        .join("\n");
+        match Drawing::load(&mut contents.as_bytes()) {
+            Err(DxfError::UnexpectedCode(70, _)) => (),
+            other => panic!("expected an unexpected code error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn round_trip_unknown_variable() {
+        let drawing = from_section(
+            "HEADER",
+            vec![
+                CodePair::new_str(9, "$NOTAVARIABLE"),
+                CodePair::new_f64(40, 0.55),
+                CodePair::new_i16(70, 3),
+            ],
+        );
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$NOTAVARIABLE"),
+                CodePair::new_f64(40, 0.55),
+                CodePair::new_i16(70, 3),
+            ],
+        );
+    }
+
     #[test]
     fn write_multiple_value_variable() {
         let mut drawing = Drawing::new();
@@ -375,11 +773,12 @@ mod tests {
             Point::origin(),
             Point::origin(),
         ))));
+        // the mandatory table records the file is missing take the handles after the line
         assert_contains_pairs(
             &drawing,
             vec![
                 CodePair::new_str(9, "$HANDSEED"),
-                CodePair::new_str(5, "15"),
+                CodePair::new_str(5, "21"),
             ],
         );
     }
@@ -397,4 +796,354 @@ mod tests {
             vec![CodePair::new_str(9, "$HIDETEXT"), CodePair::new_i16(290, 0)],
         );
     }
+
+    #[test]
+    fn read_creation_date_as_calendar_date() {
+        let drawing = from_section(
+            "HEADER",
+            vec![
+                CodePair::new_str(9, "$TDCREATE"),
+                CodePair::new_f64(40, 2_451_544.915_682_87),
+            ],
+        );
+        let expected = chrono::NaiveDate::from_ymd_opt(1999, 12, 31)
+            .unwrap()
+            .and_hms_opt(21, 58, 35)
+            .unwrap();
+        assert_eq!(expected, drawing.header.creation_date_naive());
+        assert!(approx_eq!(
+            f64,
+            2_451_544.915_682_87,
+            drawing.header.creation_date_julian(),
+            epsilon = 1e-6
+        ));
+    }
+
+    #[test]
+    fn write_update_date_from_calendar_date() {
+        let mut drawing = Drawing::new();
+        drawing.header.set_update_date_naive(
+            chrono::NaiveDate::from_ymd_opt(1999, 12, 31)
+                .unwrap()
+                .and_hms_opt(21, 58, 35)
+                .unwrap(),
+        );
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$TDUPDATE"),
+                CodePair::new_f64(40, 2_451_544.915_682_870_4),
+            ],
+        );
+    }
+
+    #[test]
+    fn read_dimension_settings() {
+        let drawing = from_section_pairs(
+            "HEADER",
+            vec![
+                CodePair::new_str(9, "$DIMSCALE"),
+                CodePair::new_f64(40, 2.5),
+                CodePair::new_str(9, "$DIMTXT"),
+                CodePair::new_f64(40, 0.25),
+            ],
+        );
+        let settings = drawing.header.dimension_settings();
+        assert!(approx_eq!(f64, 2.5, settings.scale));
+        assert!(approx_eq!(f64, 0.25, settings.text_height));
+        assert_eq!("STANDARD", settings.style_name);
+    }
+
+    #[test]
+    fn round_trip_dimension_settings() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let mut settings = drawing.header.dimension_settings();
+        settings.scale = 4.0;
+        settings.text_height = 0.125;
+        settings.arrow_size = 0.2;
+        settings.text_style = String::from("STANDARD");
+        drawing.header.set_dimension_settings(&settings);
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$DIMSCALE"),
+                CodePair::new_f64(40, 4.0),
+                CodePair::new_str(9, "$DIMASZ"),
+                CodePair::new_f64(40, 0.2),
+            ],
+        );
+
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(settings, reparsed.header.dimension_settings());
+    }
+
+    #[test]
+    fn round_trip_custom_properties() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2010;
+        drawing.header.set_custom_property("Project", "1234");
+        drawing.header.set_custom_property("Client", "ACME");
+        drawing.header.set_custom_property("Project", "5678");
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$CUSTOMPROPERTYTAG"),
+                CodePair::new_str(1, "Project"),
+                CodePair::new_str(9, "$CUSTOMPROPERTY"),
+                CodePair::new_str(1, "5678"),
+                CodePair::new_str(9, "$CUSTOMPROPERTYTAG"),
+                CodePair::new_str(1, "Client"),
+                CodePair::new_str(9, "$CUSTOMPROPERTY"),
+                CodePair::new_str(1, "ACME"),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(
+            &[
+                (String::from("Project"), String::from("5678")),
+                (String::from("Client"), String::from("ACME")),
+            ],
+            reparsed.header.custom_properties()
+        );
+
+        drawing.header.version = AcadVersion::R2007;
+        assert_not_contains_pairs(&drawing, vec![CodePair::new_str(9, "$CUSTOMPROPERTYTAG")]);
+    }
+
+    #[test]
+    fn preset_header_saves_to_loadable_file() {
+        let mut drawing = Drawing::new();
+        drawing.header = Header::for_version(AcadVersion::R2018).with_units(Units::Millimeters);
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+        assert_eq!(AcadVersion::R2018, reparsed.header.version);
+        assert_eq!(Units::Millimeters, reparsed.header.default_drawing_units);
+        assert_eq!(DrawingUnits::Metric, reparsed.header.drawing_units);
+        assert_eq!(1, reparsed.entities().count());
+    }
+
+    #[test]
+    fn round_trip_display_flags() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.header.point_display_mode = 35;
+        drawing.header.point_display_size = -5.0;
+        drawing.header.display_linewieght_in_model_and_layout_tab = true;
+        drawing.header.drawing_units = DrawingUnits::Metric;
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$PDMODE"),
+                CodePair::new_i16(70, 35),
+                CodePair::new_str(9, "$PDSIZE"),
+                CodePair::new_f64(40, -5.0),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(35, reparsed.header.point_display_mode);
+        assert_eq!(-5.0, reparsed.header.point_display_size);
+        assert!(reparsed.header.display_linewieght_in_model_and_layout_tab);
+        assert_eq!(DrawingUnits::Metric, reparsed.header.drawing_units);
+    }
+
+    #[test]
+    fn round_trip_current_ucs() {
+        let mut drawing = Drawing::new();
+        let ucs = Ucs {
+            name: String::from("shifted"),
+            origin: Point::new(10.0, 20.0, 0.0),
+            x_axis: Vector::y_axis(),
+            y_axis: Vector::new(-1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        drawing.header.set_current_ucs(&ucs);
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$UCSORG"),
+                CodePair::new_f64(10, 10.0),
+                CodePair::new_f64(20, 20.0),
+                CodePair::new_f64(30, 0.0),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let current = reparsed.header.current_ucs();
+        assert_eq!("shifted", current.name);
+        assert_eq!(Point::new(10.0, 20.0, 0.0), current.origin);
+        assert_eq!(Vector::y_axis(), current.x_axis);
+        assert_eq!(
+            Point::new(11.0, 20.0, 0.0),
+            current.ucs_to_world(&Point::new(0.0, -1.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn round_trip_guids() {
+        let drawing = from_section_pairs(
+            "HEADER",
+            vec![
+                CodePair::new_str(9, "$ACADVER"),
+                CodePair::new_str(1, "AC1015"),
+                CodePair::new_str(9, "$FINGERPRINTGUID"),
+                CodePair::new_str(2, "{A2A7A23E-975B-4B54-968C-150D4C32A9B6}"),
+                CodePair::new_str(9, "$VERSIONGUID"),
+                CodePair::new_str(2, "{0F4D3B8C-1E2A-4C5B-9D6E-7F8091A2B3C4}"),
+            ],
+        );
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$FINGERPRINTGUID"),
+                CodePair::new_str(2, "{A2A7A23E-975B-4B54-968C-150D4C32A9B6}"),
+                CodePair::new_str(9, "$VERSIONGUID"),
+                CodePair::new_str(2, "{0F4D3B8C-1E2A-4C5B-9D6E-7F8091A2B3C4}"),
+            ],
+        );
+
+        let mut header = drawing.header.clone();
+        header.regenerate_version_guid();
+        assert_eq!(drawing.header.fingerprint_guid, header.fingerprint_guid);
+        assert_ne!(drawing.header.version_guid, header.version_guid);
+    }
+
+    #[test]
+    fn round_trip_curve_quality_settings() {
+        let mut drawing = Drawing::new();
+        drawing.header.line_segments_per_spline_patch = 24;
+        drawing.header.pedit_spline_curve_type =
+            PolylineCurvedAndSmoothSurfaceType::QuadraticBSpline;
+        drawing.header.display_spline_polygon_control = true;
+        drawing.header.mesh_tabulations_in_first_direction = 12;
+        drawing.header.mesh_tabulations_in_second_direction = 16;
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$SPLINESEGS"),
+                CodePair::new_i16(70, 24),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(24, reparsed.header.line_segments_per_spline_patch);
+        assert_eq!(
+            PolylineCurvedAndSmoothSurfaceType::QuadraticBSpline,
+            reparsed.header.pedit_spline_curve_type
+        );
+        assert!(reparsed.header.display_spline_polygon_control);
+        assert_eq!(12, reparsed.header.mesh_tabulations_in_first_direction);
+        assert_eq!(16, reparsed.header.mesh_tabulations_in_second_direction);
+    }
+
+    #[test]
+    fn round_trip_paper_space_limits() {
+        let mut drawing = Drawing::new();
+        drawing.header.paperspace_minimum_drawing_limits = Point::new(-1.0, -2.0, 0.0);
+        drawing.header.paperspace_maximum_drawing_limits = Point::new(17.0, 11.0, 0.0);
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$PLIMMAX"),
+                CodePair::new_f64(10, 17.0),
+                CodePair::new_f64(20, 11.0),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(
+            Point::new(-1.0, -2.0, 0.0),
+            reparsed.header.paperspace_minimum_drawing_limits
+        );
+        assert_eq!(
+            Point::new(17.0, 11.0, 0.0),
+            reparsed.header.paperspace_maximum_drawing_limits
+        );
+    }
+
+    #[test]
+    fn round_trip_tile_mode_in_paper_space() {
+        let mut drawing = Drawing::new();
+        assert!(drawing.header.is_model_space_active());
+        drawing.header.set_is_model_space_active(false);
+        assert_contains_pairs(
+            &drawing,
+            vec![CodePair::new_str(9, "$TILEMODE"), CodePair::new_i16(70, 0)],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert!(!reparsed.header.is_model_space_active());
+    }
+
+    #[test]
+    fn version_predicates() {
+        assert!(!AcadVersion::R12.is_r13_or_later());
+        assert!(AcadVersion::R13.is_r13_or_later());
+        assert!(!AcadVersion::R2000.supports_true_color());
+        assert!(AcadVersion::R2004.supports_true_color());
+        assert!(!AcadVersion::R2004.is_utf8());
+        assert!(AcadVersion::R2007.is_utf8());
+
+        let mut header = Header::for_version(AcadVersion::R12);
+        header.handles_enabled = false;
+        assert!(!header.supports_handles());
+        header.handles_enabled = true;
+        assert!(header.supports_handles());
+        header.handles_enabled = false;
+        header.version = AcadVersion::R13;
+        assert!(header.supports_handles());
+    }
+
+    #[test]
+    fn round_trip_dimension_associativity() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.header.dimension_object_associativity = DimensionAssociativity::AssociativeObjects;
+        assert_contains_pairs(
+            &drawing,
+            vec![CodePair::new_str(9, "$DIMASSOC"), CodePair::new_i16(280, 2)],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(
+            DimensionAssociativity::AssociativeObjects,
+            reparsed.header.dimension_object_associativity
+        );
+    }
+
+    #[test]
+    fn round_trip_angular_units() {
+        let mut drawing = Drawing::new();
+        drawing.header.angle_unit_format = AngleFormat::Radians;
+        drawing.header.angle_unit_precision = 3;
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$AUNITS"),
+                CodePair::new_i16(70, 3),
+                CodePair::new_str(9, "$AUPREC"),
+                CodePair::new_i16(70, 3),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        assert_eq!(AngleFormat::Radians, reparsed.header.angle_unit_format);
+        assert_eq!(3, reparsed.header.angle_unit_precision);
+        assert_eq!("0.785r", reparsed.header.format_angle(45.0));
+    }
+
+    #[test]
+    fn format_angles() {
+        let mut header = Header {
+            angle_unit_precision: 2,
+            ..Default::default()
+        };
+        assert_eq!("45.50", header.format_angle(45.5));
+        header.angle_unit_format = AngleFormat::Gradians;
+        assert_eq!("50.00g", header.format_angle(45.0));
+        header.angle_unit_format = AngleFormat::DegreesMinutesSeconds;
+        assert_eq!("45d30'", header.format_angle(45.5));
+        header.angle_unit_precision = 4;
+        assert_eq!("45d30'15\"", header.format_angle(45.5 + 15.0 / 3600.0));
+        header.angle_unit_format = AngleFormat::SurveyorsUnits;
+        assert_eq!("N 45d0'0\" E", header.format_angle(45.0));
+        assert_eq!("S 30d0'0\" W", header.format_angle(240.0));
+        assert_eq!("N", header.format_angle(90.0));
+    }
 }
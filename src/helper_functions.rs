@@ -20,7 +20,7 @@ use enum_primitive::FromPrimitive;
 
 use crate::enums::*;
 use crate::tables::Layer;
-use crate::{CodePair, Color, DxfError, DxfResult};
+use crate::{CodePair, Color, DxfError, DxfResult, Point};
 
 pub(crate) fn verify_code(pair: &CodePair, expected: i32) -> DxfResult<()> {
     if expected == pair.code {
@@ -171,7 +171,14 @@ pub(crate) fn as_i16(b: bool) -> i16 {
 }
 
 pub(crate) fn uuid_string(u: &Uuid) -> String {
-    format!("{}", u)
+    // AutoCAD writes GUIDs upper case and wrapped in braces
+    format!("{{{}}}", u.hyphenated().to_string().to_uppercase())
+}
+
+#[test]
+fn write_windows_style_uuid_test() {
+    let uuid = as_uuid(String::from("a2a7a23e-975b-4b54-968c-150d4c32a9b6"));
+    assert_eq!("{A2A7A23E-975B-4B54-968C-150D4C32A9B6}", uuid_string(&uuid));
 }
 
 pub(crate) fn combine_points_2<F, T>(
@@ -238,7 +245,48 @@ pub(crate) fn bool_from_clipping(c: XrefClippingBoundaryVisibility) -> bool {
     c != XrefClippingBoundaryVisibility::NotDisplayedNotPlotted
 }
 
-pub(crate) fn parse_f64(s: String, offset: usize) -> DxfResult<f64> {
+/// Returns the length of one `Units` in meters, or `None` for `Unitless`.
+pub(crate) fn meters_per_unit(units: Units) -> Option<f64> {
+    let meters = match units {
+        Units::Unitless => return None,
+        Units::Inches => 0.0254,
+        Units::Feet => 0.3048,
+        Units::Miles => 1_609.344,
+        Units::Millimeters => 0.001,
+        Units::Centimeters => 0.01,
+        Units::Meters => 1.0,
+        Units::Kilometers => 1_000.0,
+        Units::Microinches => 0.0254e-6,
+        Units::Mils => 0.0254e-3,
+        Units::Yards => 0.9144,
+        Units::Angstroms => 1e-10,
+        Units::Nanometers => 1e-9,
+        Units::Microns => 1e-6,
+        Units::Decimeters => 0.1,
+        Units::Decameters => 10.0,
+        Units::Hectometers => 100.0,
+        Units::Gigameters => 1e9,
+        Units::AstronomicalUnits => 149_597_870_700.0,
+        Units::LightYears => 9_460_730_472_580_800.0,
+        Units::Parsecs => 3.085_677_581_491_367e16,
+        Units::USSurveyFeet => 1_200.0 / 3_937.0,
+        Units::USSurveyInch => 100.0 / 3_937.0,
+        Units::USSurveyYard => 3_600.0 / 3_937.0,
+        Units::USSurveyMile => 6_336_000.0 / 3_937.0,
+    };
+    Some(meters)
+}
+
+/// Pairs consecutive points with the bulge of each segment's starting vertex.
+pub(crate) fn polyline_segments(points: &[Point], bulges: &[f64]) -> Vec<(Point, Point, f64)> {
+    points
+        .windows(2)
+        .zip(bulges.iter())
+        .map(|(pair, bulge)| (pair[0].clone(), pair[1].clone(), *bulge))
+        .collect()
+}
+
+pub(crate) fn parse_f64(s: &str, offset: usize) -> DxfResult<f64> {
     match s.trim().parse::<f64>() {
         Ok(d) => Ok(d),
         Err(e) => Err(DxfError::ParseFloatError(e, offset)),
@@ -248,10 +296,10 @@ pub(crate) fn parse_f64(s: String, offset: usize) -> DxfResult<f64> {
 #[test]
 #[allow(clippy::float_cmp)]
 fn parse_f64_test() {
-    assert_eq!(2.5, parse_f64("  2.5 ".to_string(), 0).unwrap());
+    assert_eq!(2.5, parse_f64("  2.5 ", 0).unwrap());
 }
 
-pub(crate) fn parse_i32(s: String, offset: usize) -> DxfResult<i32> {
+pub(crate) fn parse_i32(s: &str, offset: usize) -> DxfResult<i32> {
     match s.trim().parse::<i32>() {
         Ok(i) => Ok(i),
         Err(e) => Err(DxfError::ParseIntError(e, offset)),
@@ -260,10 +308,10 @@ pub(crate) fn parse_i32(s: String, offset: usize) -> DxfResult<i32> {
 
 #[test]
 fn parse_i32_test() {
-    assert_eq!(2, parse_i32("  2 ".to_string(), 0).unwrap());
+    assert_eq!(2, parse_i32("  2 ", 0).unwrap());
 }
 
-pub(crate) fn parse_i64(s: String, offset: usize) -> DxfResult<i64> {
+pub(crate) fn parse_i64(s: &str, offset: usize) -> DxfResult<i64> {
     match s.trim().parse::<i64>() {
         Ok(l) => Ok(l),
         Err(e) => Err(DxfError::ParseIntError(e, offset)),
@@ -272,10 +320,10 @@ pub(crate) fn parse_i64(s: String, offset: usize) -> DxfResult<i64> {
 
 #[test]
 fn parse_i64_test() {
-    assert_eq!(2, parse_i64("  2 ".to_string(), 0).unwrap());
+    assert_eq!(2, parse_i64("  2 ", 0).unwrap());
 }
 
-pub(crate) fn parse_i16(s: String, offset: usize) -> DxfResult<i16> {
+pub(crate) fn parse_i16(s: &str, offset: usize) -> DxfResult<i16> {
     match s.trim().parse::<f64>() {
         Ok(s) => Ok(s as i16),
         Err(e) => Err(DxfError::ParseFloatError(e, offset)),
@@ -284,10 +332,10 @@ pub(crate) fn parse_i16(s: String, offset: usize) -> DxfResult<i16> {
 
 #[test]
 fn parse_i16_test() {
-    assert_eq!(2, parse_i16("  2 ".to_string(), 0).unwrap());
+    assert_eq!(2, parse_i16("  2 ", 0).unwrap());
 
     // some files write shorts as a double
-    assert_eq!(2, parse_i16(" 2.0 ".to_string(), 0).unwrap());
+    assert_eq!(2, parse_i16(" 2.0 ", 0).unwrap());
 }
 
 pub(crate) fn read_color_value(layer: &mut Layer, color: i16) -> Color {
@@ -305,6 +353,7 @@ where
 {
     let mut bytes = vec![];
     let mut skipping_bom = false;
+    #[allow(clippy::unbuffered_bytes)] // callers wrap file streams in a `BufReader`
     let reader_bytes = reader.bytes();
     for (i, b) in reader_bytes.enumerate() {
         let b = match b {
@@ -436,6 +485,43 @@ macro_rules! vec_last {
     };
 }
 
+// Implements conversions between a 3D type with `x`, `y`, and `z` fields and tuples/arrays; 2D
+// values have a `z` of `0.0`
+macro_rules! impl_xyz_conversions {
+    ($typ: ident) => {
+        impl From<(f64, f64, f64)> for $typ {
+            fn from((x, y, z): (f64, f64, f64)) -> Self {
+                $typ::new(x, y, z)
+            }
+        }
+        impl From<[f64; 3]> for $typ {
+            fn from([x, y, z]: [f64; 3]) -> Self {
+                $typ::new(x, y, z)
+            }
+        }
+        impl From<(f64, f64)> for $typ {
+            fn from((x, y): (f64, f64)) -> Self {
+                $typ::new(x, y, 0.0)
+            }
+        }
+        impl From<[f64; 2]> for $typ {
+            fn from([x, y]: [f64; 2]) -> Self {
+                $typ::new(x, y, 0.0)
+            }
+        }
+        impl From<$typ> for (f64, f64, f64) {
+            fn from(value: $typ) -> Self {
+                (value.x, value.y, value.z)
+            }
+        }
+        impl From<$typ> for [f64; 3] {
+            fn from(value: $typ) -> Self {
+                [value.x, value.y, value.z]
+            }
+        }
+    };
+}
+
 pub(crate) fn read_u8_strict<T: Read>(reader: &mut T) -> DxfResult<u8> {
     let u = try_from_option_io_result!(read_u8(reader));
     Ok(u)
@@ -513,7 +599,7 @@ pub(crate) fn parse_hex_string(data: &str, bytes: &mut Vec<u8>, offset: usize) -
         Ok(value)
     }
 
-    let mut complete_byte = data.len() % 2 != 0; // handles strings with an odd number of bytes
+    let mut complete_byte = !data.len().is_multiple_of(2); // handles strings with an odd number of bytes
     let mut current_byte = 0u8;
     for c in data.chars() {
         let value = char_to_value(c, offset)?;
@@ -554,7 +640,7 @@ pub mod tests {
         println!("reading from pairs: {:?}", pairs);
         let iter = DirectCodePairIter::new(pairs);
         let iter = Box::new(iter);
-        unwrap_drawing(Drawing::load_from_iter(iter))
+        unwrap_drawing(Drawing::load_from_iter(iter, true))
     }
 
     pub fn parse_drawing(s: &str) -> Drawing {
@@ -612,7 +698,7 @@ pub mod tests {
         assert!(actual.contains(&contents));
     }
 
-    fn try_find_index<T>(superset: &Vec<T>, subset: &Vec<T>) -> Option<usize>
+    fn try_find_index<T>(superset: &[T], subset: &[T]) -> Option<usize>
     where
         T: PartialEq,
     {
@@ -634,7 +720,7 @@ pub mod tests {
         None
     }
 
-    pub fn assert_vec_contains<T>(actual: &Vec<T>, expected: &Vec<T>)
+    pub fn assert_vec_contains<T>(actual: &[T], expected: &[T])
     where
         T: PartialEq,
     {
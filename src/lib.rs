@@ -17,7 +17,7 @@
 //! ```
 //!
 //! > Note that `serde` support is intended to aid in debugging and since the serialized format is heavily
-//! dependent on the layout of the structures, it may change at any time.
+//! > dependent on the layout of the structures, it may change at any time.
 //!
 //! And finally add:
 //!
@@ -158,6 +158,9 @@ mod thumbnail;
 mod drawing;
 pub use crate::drawing::Drawing;
 
+mod drawing_writer;
+pub use crate::drawing_writer::DrawingWriter;
+
 mod drawing_item;
 pub use crate::drawing_item::{DrawingItem, DrawingItemMut};
 
@@ -170,16 +173,21 @@ pub use crate::section_type_settings::SectionTypeSettings;
 mod table_cell_style;
 pub use crate::table_cell_style::TableCellStyle;
 
+mod transparency;
+pub use crate::transparency::Transparency;
+
 mod transformation_matrix;
 pub use crate::transformation_matrix::TransformationMatrix;
 
 pub mod enums;
 
+pub mod geometry;
+
 mod color;
 pub use crate::color::Color;
 
 mod point;
-pub use crate::point::Point;
+pub use crate::point::{ocs_point_to_wcs, Point};
 
 mod vector;
 pub use crate::vector::Vector;
@@ -208,13 +216,13 @@ mod class;
 pub use crate::class::Class;
 
 mod header;
-pub use crate::header::Header;
+pub use crate::header::{DimensionSettings, Header};
 
 mod line_weight;
 pub use crate::line_weight::LineWeight;
 
 mod entity;
-pub use crate::entity::LwPolylineVertex;
+pub use crate::entity::{LwPolylineVertex, TableCell};
 
 mod object;
 pub use crate::object::{GeoMeshPoint, MLineStyleElement};
@@ -225,6 +233,18 @@ pub use crate::dxf_error::DxfError;
 mod dxf_result;
 pub use crate::dxf_result::DxfResult;
 
+mod load_options;
+pub use crate::load_options::{LoadOptions, LoadReport};
+
+mod normalize_options;
+pub use crate::normalize_options::NormalizeOptions;
+
+mod save_options;
+pub use crate::save_options::{DxfSection, SaveOptions};
+
+mod value_formatter;
+pub use crate::value_formatter::{DefaultValueFormatter, ValueFormatter};
+
 mod entity_iter;
 mod object_iter;
 
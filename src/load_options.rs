@@ -0,0 +1,23 @@
+use crate::Handle;
+
+/// Controls how a `Drawing` is read.
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    /// When `true`, items that share a non-empty handle with an earlier item are given a new
+    /// handle.  See `Drawing::repair_handles`.
+    pub repair_handles: bool,
+}
+
+impl LoadOptions {
+    /// Creates a new `LoadOptions` with the default behavior.
+    pub fn new() -> Self {
+        LoadOptions::default()
+    }
+}
+
+/// Describes the changes made to a `Drawing` while it was read.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoadReport {
+    /// The `(old, new)` handle of each item whose duplicate handle was replaced.
+    pub repaired_handles: Vec<(Handle, Handle)>,
+}
@@ -12,7 +12,7 @@ use self::image::{DynamicImage, GenericImageView};
 #[test]
 fn read_string_with_control_characters() {
     let drawing = parse_drawing(
-        vec![
+        [
             "0",
             "SECTION",
             "2",
@@ -433,6 +433,17 @@ fn dont_write_utf8_bom() {
     assert_eq!(b'0', vec[2]);
 }
 
+fn assert_contains_bytes(drawing: &Drawing, expected: &[u8]) {
+    let mut buf = Cursor::new(vec![]);
+    drawing.save(&mut buf).ok().unwrap();
+    let actual = buf.into_inner();
+    assert!(
+        actual.windows(expected.len()).any(|w| w == expected),
+        "expected {:?}",
+        String::from_utf8_lossy(expected)
+    );
+}
+
 #[test]
 fn write_unicode_as_ascii() {
     let mut drawing = Drawing::new();
@@ -440,21 +451,99 @@ fn write_unicode_as_ascii() {
     drawing.header.project_name = String::from("è");
     assert_contains(
         &drawing,
-        vec!["  9", "$PROJECTNAME", "  1", "\\U+00E8"].join("\r\n"),
+        ["  9", "$PROJECTNAME", "  1", "\\U+00E8"].join("\r\n"),
     );
 }
 
 #[test]
-fn write_unicode_as_utf8() {
+fn write_text_with_code_page() {
     let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R2004;
+    drawing.header.project_name = String::from("\u{042F}");
+    drawing.header.set_code_page(CodePage::Ansi1251);
+    assert_eq!("ANSI_1251", drawing.header.drawing_code_page);
+    assert_eq!(Some(CodePage::Ansi1251), drawing.header.code_page());
+    assert_contains_bytes(&drawing, b"$PROJECTNAME\r\n  1\r\n\xDF\r\n");
+
+    // R2007 and later are always UTF-8
     drawing.header.version = AcadVersion::R2007;
-    drawing.header.project_name = String::from("è");
     assert_contains(
         &drawing,
-        vec!["  9", "$PROJECTNAME", "  1", "è"].join("\r\n"),
+        ["  9", "$PROJECTNAME", "  1", "\u{042F}"].join("\r\n"),
     );
 }
 
+#[test]
+fn round_trip_text_with_code_page() {
+    let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R2004;
+    drawing.header.set_code_page(CodePage::Ansi1251);
+    drawing.add_layer(tables::Layer {
+        name: String::from("Стены"),
+        ..Default::default()
+    });
+    drawing.header.project_name = String::from("Стены è");
+
+    let mut buf = Cursor::new(vec![]);
+    drawing.save(&mut buf).ok().unwrap();
+    buf.seek(SeekFrom::Start(0)).ok().unwrap();
+    let reparsed = unwrap_drawing(Drawing::load(&mut buf));
+    assert!(reparsed.layers().any(|l| l.name == "Стены"));
+
+    // `è` isn't in the code page and is escaped
+    assert_eq!("Стены è", reparsed.header.project_name);
+}
+
+#[test]
+fn explicit_encoding_overrides_code_page() {
+    let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R2004;
+    drawing.header.set_code_page(CodePage::Ansi1251);
+    drawing.header.project_name = String::from("\u{042F}");
+    let mut buf = vec![];
+    drawing.save(&mut buf).ok().unwrap();
+
+    // `0xDF` is `Я` in ANSI_1251 and `ß` in Windows-1252
+    let reparsed = unwrap_drawing(Drawing::load(&mut buf.as_slice()));
+    assert_eq!("\u{042F}", reparsed.header.project_name);
+    let reparsed = unwrap_drawing(Drawing::load_with_encoding(
+        &mut buf.as_slice(),
+        encoding_rs::WINDOWS_1252,
+    ));
+    assert_eq!("\u{00DF}", reparsed.header.project_name);
+    assert_eq!(Some(CodePage::Ansi1251), reparsed.header.code_page());
+}
+
+#[test]
+fn round_trip_binary_text_with_code_page() {
+    let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R2004;
+    drawing.header.set_code_page(CodePage::Ansi1251);
+    drawing.header.project_name = String::from("Стены è");
+
+    let mut buf = Cursor::new(vec![]);
+    drawing.save_binary(&mut buf).ok().unwrap();
+    buf.seek(SeekFrom::Start(0)).ok().unwrap();
+    let reparsed = unwrap_drawing(Drawing::load(&mut buf));
+    assert_eq!("Стены è", reparsed.header.project_name);
+}
+
+#[test]
+fn parse_code_page_names() {
+    assert_eq!(Some(CodePage::Ansi1252), CodePage::from_name("ansi_1252"));
+    assert_eq!(Some(CodePage::Utf8), CodePage::from_name("UTF-8"));
+    assert_eq!(None, CodePage::from_name("not-a-code-page"));
+    assert_eq!("ANSI_932", CodePage::Ansi932.name());
+}
+
+#[test]
+fn write_unicode_as_utf8() {
+    let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R2007;
+    drawing.header.project_name = String::from("è");
+    assert_contains(&drawing, ["  9", "$PROJECTNAME", "  1", "è"].join("\r\n"));
+}
+
 #[test]
 fn write_binary_file() {
     for version in &[AcadVersion::R12, AcadVersion::R13] {
@@ -508,13 +597,13 @@ fn thumbnail_round_trip_grayscale() {
 fn round_trip_thumbnail(thumbnail: image::DynamicImage) -> image::DynamicImage {
     // write drawing with thumbnail
     let mut drawing = Drawing::new();
-    drawing.header.version = AcadVersion::R2000; // thumbnails are only written >= R2000
+    drawing.header.version = AcadVersion::R2000; // the THUMBNAILIMAGE section is only written >= R2000
     drawing.thumbnail = Some(thumbnail);
 
     let drawing_pairs = drawing.code_pairs().unwrap();
     assert_vec_contains(
         &drawing_pairs,
-        &vec![
+        &[
             CodePair::new_str(0, "SECTION"),
             CodePair::new_str(2, "THUMBNAILIMAGE"),
         ],
@@ -523,3 +612,40 @@ fn round_trip_thumbnail(thumbnail: image::DynamicImage) -> image::DynamicImage {
     let drawing = drawing_from_pairs(drawing_pairs);
     drawing.thumbnail.unwrap()
 }
+
+#[test]
+fn header_preview_image_is_resaved_as_thumbnail_section() {
+    let mut imgbuf = image::ImageBuffer::new(1, 1);
+    imgbuf.put_pixel(0, 0, image::Rgb([255u8, 0, 0]));
+    let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R14;
+    drawing.thumbnail = Some(DynamicImage::ImageRgb8(imgbuf));
+    let header_pairs = drawing.code_pairs().unwrap();
+    assert!(header_pairs.contains(&CodePair::new_str(9, "$PREVIEWIMAGE")));
+    assert!(!header_pairs.contains(&CodePair::new_str(2, "THUMBNAILIMAGE")));
+
+    // load the header-based preview
+    let mut drawing = drawing_from_pairs(header_pairs);
+    assert!(drawing.header.__preview_image_data.is_empty());
+    assert_eq!(
+        image::Rgba([255u8, 0, 0, 255]),
+        drawing.thumbnail.as_ref().unwrap().get_pixel(0, 0)
+    );
+
+    // and save it for a version with a THUMBNAILIMAGE section
+    drawing.header.version = AcadVersion::R2000;
+    let section_pairs = drawing.code_pairs().unwrap();
+    assert!(!section_pairs.contains(&CodePair::new_str(9, "$PREVIEWIMAGE")));
+    assert_vec_contains(
+        &section_pairs,
+        &[
+            CodePair::new_str(0, "SECTION"),
+            CodePair::new_str(2, "THUMBNAILIMAGE"),
+        ],
+    );
+    let drawing = drawing_from_pairs(section_pairs);
+    assert_eq!(
+        image::Rgba([255u8, 0, 0, 255]),
+        drawing.thumbnail.unwrap().get_pixel(0, 0)
+    );
+}
@@ -1,10 +1,12 @@
 use crate::entities::*;
 use crate::enums::*;
+use crate::helper_functions::tests::*;
 use crate::*;
 
 use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str::from_utf8;
 use std::thread::panicking;
 use std::time::SystemTime;
 
@@ -32,14 +34,14 @@ impl Oda {
     pub fn convert_drawing(&self, drawing: &mut Drawing, version: AcadVersion) -> Drawing {
         drawing.header.version = version;
         drawing
-            .save_file(&format!("{}/drawing.dxf", self.input_path))
+            .save_file(format!("{}/drawing.dxf", self.input_path))
             .unwrap();
         // e.g.,
         //   ODAFileConverter.exe input_dir output_dir ACAD2000 DXF 0 1
         let mut oda_convert = Command::new(&self.oda_path)
             .arg(&self.input_path)
             .arg(&self.output_path)
-            .arg(&Oda::version_string(version))
+            .arg(Oda::version_string(version))
             .arg("DXF")
             .arg("0") // recurse
             .arg("1") // audit
@@ -151,7 +153,7 @@ impl AutoCAD {
     pub fn convert_drawing(&self, drawing: &mut Drawing, version: AcadVersion) -> Drawing {
         drawing.header.version = version;
         drawing
-            .save_file(&format!("{}/input.dxf", self.temp_path))
+            .save_file(format!("{}/input.dxf", self.temp_path))
             .unwrap();
         // e.g.,
         //   accoreconsole.exe /i /path/to/input.dxf /b script.scr
@@ -362,3 +364,161 @@ fn block_insert_can_be_read_by_acad_r12() {
         _ => panic!("expected a line"),
     }
 }
+
+#[test]
+fn read_librecad_style_file_with_out_of_order_and_unknown_header_variables() {
+    // the fixture is hand-written to mimic what LibreCAD's exports are reported to contain, not an
+    // actual LibreCAD export: `$HANDSEED` precedes `$ACADVER`, `$DIMZIN` uses code 280, and
+    // `$MEASUREINIT` and `$LWDISPSCALE` aren't otherwise supported
+    let drawing = unwrap_drawing(Drawing::load_file("./src/misc_tests/librecad-style.dxf"));
+    assert_eq!(AcadVersion::R2000, drawing.header.version);
+    assert_eq!(2.5, drawing.header.default_text_height);
+    assert_eq!(Units::Millimeters, drawing.header.default_drawing_units);
+    assert_eq!(
+        UnitZeroSuppression::IncludeZeroFeetAndZeroInches,
+        drawing.header.dimension_unit_zero_suppression
+    );
+    let unknown = drawing
+        .header
+        .unknown_variables()
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["$MEASUREINIT", "$LWDISPSCALE"], unknown);
+    assert_eq!(
+        vec![CodePair::new_f64(40, 0.55)],
+        drawing.header.unknown_variables()[1].1
+    );
+    assert_eq!(1, drawing.entities().count());
+
+    // unknown variables survive a round trip
+    let mut buf = vec![];
+    drawing.save(&mut buf).unwrap();
+    let reparsed = unwrap_drawing(Drawing::load_from_bytes(&buf));
+    assert_eq!(
+        drawing.header.unknown_variables(),
+        reparsed.header.unknown_variables()
+    );
+}
+
+#[test]
+fn read_file_without_eof() {
+    let drawing = parse_drawing(
+        [
+            "0", "SECTION", "2", "ENTITIES", "0", "LINE", "10", "1.0", "0", "ENDSEC", "", "  ", "",
+        ]
+        .join("\r\n")
+        .as_str(),
+    );
+    assert_eq!(1, drawing.entities().count());
+}
+
+#[test]
+fn read_file_with_comments_between_sections_and_no_eof() {
+    let drawing = parse_drawing(
+        [
+            "999",
+            "written by a generator",
+            "0",
+            "SECTION",
+            "2",
+            "HEADER",
+            "9",
+            "$ACADVER",
+            "1",
+            "AC1015",
+            "0",
+            "ENDSEC",
+            "999",
+            "between sections",
+            "0",
+            "SECTION",
+            "2",
+            "ENTITIES",
+            "0",
+            "LINE",
+            "10",
+            "1.0",
+            "0",
+            "ENDSEC",
+            "999",
+            "trailing comment",
+            "999",
+        ]
+        .join("\n")
+        .as_str(),
+    );
+    assert_eq!(AcadVersion::R2000, drawing.header.version);
+    assert_eq!(1, drawing.entities().count());
+}
+
+#[test]
+fn write_leading_comments() {
+    let mut drawing = Drawing::new();
+    drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+    let options = SaveOptions {
+        comments: vec![String::from("generated by a test\nsecond line")],
+        ..Default::default()
+    };
+    let mut buf = vec![];
+    drawing.save_with_options(&mut buf, &options).unwrap();
+    let text = from_utf8(&buf).unwrap();
+    assert!(text.starts_with("999\r\ngenerated by a test\r\n999\r\nsecond line\r\n  0\r\nSECTION"));
+
+    let reparsed = Drawing::load_from_bytes(&buf).unwrap();
+    assert_eq!(1, reparsed.entities().count());
+}
+
+#[test]
+fn read_file_with_interspersed_comments() {
+    let drawing = parse_drawing(
+        [
+            "0",
+            "SECTION",
+            "999",
+            "c",
+            "2",
+            "TABLES",
+            "999",
+            "c",
+            "0",
+            "TABLE",
+            "2",
+            "LAYER",
+            "999",
+            "c",
+            "0",
+            "LAYER",
+            "999",
+            "c",
+            "2",
+            "walls",
+            "0",
+            "ENDTAB",
+            "999",
+            "c",
+            "0",
+            "ENDSEC",
+            "0",
+            "SECTION",
+            "2",
+            "OBJECTS",
+            "999",
+            "c",
+            "0",
+            "DICTIONARY",
+            "999",
+            "c",
+            "0",
+            "ENDSEC",
+            "999",
+            "c",
+            "0",
+            "EOF",
+        ]
+        .join("\n")
+        .as_str(),
+    );
+    assert!(drawing.layers().any(|l| l.name == "walls"));
+    assert_eq!(1, drawing.objects().count());
+}
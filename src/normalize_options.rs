@@ -0,0 +1,15 @@
+/// Controls how a `Drawing` is normalized.
+#[derive(Clone, Debug, Default)]
+pub struct NormalizeOptions {
+    /// When `true`, `Drawing::link_block_records` is also run so every block has a block record
+    /// with the same name and vice versa.  This is off by default because it adds an empty block
+    /// for every unused block record, e.g., `*MODEL_SPACE`.
+    pub link_block_records: bool,
+}
+
+impl NormalizeOptions {
+    /// Creates a new `NormalizeOptions` with the default behavior.
+    pub fn new() -> Self {
+        NormalizeOptions::default()
+    }
+}
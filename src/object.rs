@@ -8,7 +8,7 @@ extern crate chrono;
 use self::chrono::Duration;
 
 use crate::{
-    CodePair, Color, DataTableValue, DxfError, DxfResult, Point, SectionTypeSettings,
+    CodePair, Color, DataTableValue, DxfError, DxfResult, Handle, Point, SectionTypeSettings,
     TableCellStyle, TransformationMatrix,
 };
 
@@ -36,6 +36,38 @@ impl GeoMeshPoint {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                       GeoData
+//------------------------------------------------------------------------------
+impl GeoData {
+    // long coordinate system definitions are split into 255 character chunks written as `303`
+    // pairs with the last one in code `301`
+    fn coordinate_system_definition_chunks(&self) -> Vec<String> {
+        let chars = self
+            .coordinate_system_definition
+            .chars()
+            .collect::<Vec<_>>();
+        let mut chunks = chars
+            .chunks(255)
+            .map(|c| c.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        if chunks.is_empty() {
+            chunks.push(String::new());
+        }
+        chunks
+    }
+    pub(crate) fn coordinate_system_definition_leading_chunks(&self) -> Vec<String> {
+        let mut chunks = self.coordinate_system_definition_chunks();
+        chunks.pop();
+        chunks
+    }
+    pub(crate) fn coordinate_system_definition_last_chunk(&self) -> String {
+        self.coordinate_system_definition_chunks()
+            .pop()
+            .unwrap_or_default()
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                             MLineStyleElement
 //------------------------------------------------------------------------------
@@ -68,6 +100,26 @@ impl DataTable {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                    Dictionary
+//------------------------------------------------------------------------------
+impl Dictionary {
+    /// Returns the handle of the entry with the specified name.
+    pub fn get(&self, name: &str) -> Option<Handle> {
+        self.value_handles.get(name).copied()
+    }
+}
+
+//------------------------------------------------------------------------------
+//                                                         DictionaryWithDefault
+//------------------------------------------------------------------------------
+impl DictionaryWithDefault {
+    /// Returns the handle of the entry with the specified name.
+    pub fn get(&self, name: &str) -> Option<Handle> {
+        self.value_handles.get(name).copied()
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                  ObjectCommon
 //------------------------------------------------------------------------------
@@ -94,6 +146,22 @@ impl Object {
         self.common.normalize();
         // no object-specific values to set
     }
+    /// Returns the handle of the object.
+    pub fn handle(&self) -> Handle {
+        self.common.handle
+    }
+    /// Sets the handle of the object.
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.common.handle = handle;
+    }
+    /// Returns the handle of the object's owner.
+    pub fn owner_handle(&self) -> Handle {
+        self.common.__owner_handle
+    }
+    /// Sets the handle of the object's owner.
+    pub fn set_owner_handle(&mut self, handle: Handle) {
+        self.common.__owner_handle = handle;
+    }
     pub(crate) fn read(iter: &mut CodePairPutBack) -> DxfResult<Option<Object>> {
         loop {
             match iter.next() {
@@ -164,25 +232,13 @@ impl Object {
     }
     fn post_parse(&mut self) -> DxfResult<()> {
         match self.specific {
-            ObjectType::AcadProxyObject(ref mut proxy) => {
-                for item in &proxy.__object_ids_a {
-                    proxy.object_ids.push(item.clone());
-                }
-                for item in &proxy.__object_ids_b {
-                    proxy.object_ids.push(item.clone());
-                }
-                for item in &proxy.__object_ids_c {
-                    proxy.object_ids.push(item.clone());
-                }
-                for item in &proxy.__object_ids_d {
-                    proxy.object_ids.push(item.clone());
-                }
-                proxy.__object_ids_a.clear();
-                proxy.__object_ids_b.clear();
-                proxy.__object_ids_c.clear();
-                proxy.__object_ids_d.clear();
-            }
             ObjectType::GeoData(ref mut geo) => {
+                if !geo.__coordinate_system_definition_chunks.is_empty() {
+                    let mut definition = geo.__coordinate_system_definition_chunks.concat();
+                    definition.push_str(&geo.coordinate_system_definition);
+                    geo.coordinate_system_definition = definition;
+                    geo.__coordinate_system_definition_chunks.clear();
+                }
                 let mut source_points = vec![];
                 let mut destination_points = vec![];
                 combine_points_2(
@@ -243,16 +299,6 @@ impl Object {
                     .clear();
                 material.__normal_map_transformation_matrix_values.clear();
             }
-            ObjectType::MLineStyle(ref mut mline) => {
-                for (o, (c, l)) in mline.__element_offsets.drain(..).zip(
-                    mline
-                        .__element_colors
-                        .drain(..)
-                        .zip(mline.__element_line_types.drain(..)),
-                ) {
-                    mline.elements.push(MLineStyleElement::new(o, c, l));
-                }
-            }
             ObjectType::VbaProject(ref mut vba) => {
                 // each char in each _hex_data should be added to `data` byte array
                 let mut result = vec![];
@@ -272,6 +318,9 @@ impl Object {
     }
     fn apply_custom_reader(&mut self, iter: &mut CodePairPutBack) -> DxfResult<bool> {
         match self.specific {
+            ObjectType::AcadProxyObject(ref mut proxy) => {
+                Object::apply_custom_reader_acadproxyobject(&mut self.common, proxy, iter)
+            }
             ObjectType::DataTable(ref mut data) => {
                 Object::apply_custom_reader_datatable(&mut self.common, data, iter)
             }
@@ -291,7 +340,9 @@ impl Object {
                 Object::apply_custom_reader_material(&mut self.common, mat, iter)
             }
             ObjectType::MLineStyle(ref mut mline) => {
-                Object::apply_custom_reader_mlinestyle(&mut self.common, mline, iter)
+                let result = Object::apply_custom_reader_mlinestyle(&mut self.common, mline, iter)?;
+                Object::combine_mline_style_elements(mline);
+                Ok(result)
             }
             ObjectType::SectionSettings(ref mut ss) => {
                 Object::apply_custom_reader_sectionsettings(&mut self.common, ss, iter)
@@ -314,6 +365,60 @@ impl Object {
             _ => Ok(false), // no custom reader
         }
     }
+    fn apply_custom_reader_acadproxyobject(
+        common: &mut ObjectCommon,
+        proxy: &mut AcadProxyObject,
+        iter: &mut CodePairPutBack,
+    ) -> DxfResult<bool> {
+        // the owner handle and the proxy object ids are both written with code 330
+        let mut reading_proxy_data = false;
+        loop {
+            let pair = next_pair!(iter);
+            if !reading_proxy_data {
+                match pair.code {
+                    100 if pair.assert_string()? == "AcDbProxyObject" => {
+                        reading_proxy_data = true;
+                    }
+                    90 => {
+                        proxy.proxy_object_class_id = pair.assert_i32()?;
+                        reading_proxy_data = true;
+                    }
+                    _ => {
+                        common.apply_individual_pair(&pair, iter)?;
+                    }
+                }
+                continue;
+            }
+
+            match pair.code {
+                90 => {
+                    proxy.proxy_object_class_id = pair.assert_i32()?;
+                }
+                91 => {
+                    proxy.application_object_class_id = pair.assert_i32()?;
+                }
+                93 => {
+                    proxy.size_in_bits = pair.assert_i32()?;
+                }
+                310 => {
+                    proxy.binary_object_data.push(pair.assert_binary()?);
+                }
+                330 | 340 | 350 | 360 => {
+                    proxy.object_ids.push((pair.code, pair.assert_string()?));
+                }
+                94 => (), // end of object ids
+                95 => {
+                    proxy.__object_drawing_format = pair.assert_i32()? as u32;
+                }
+                70 => {
+                    proxy.is_original_object_format = as_bool(pair.assert_i16()?);
+                }
+                _ => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+    }
     fn apply_custom_reader_datatable(
         common: &mut ObjectCommon,
         data: &mut DataTable,
@@ -415,16 +520,12 @@ impl Object {
                     );
                     current_3d_point = Point::origin();
                 }
-                330 | 331 | 340 | 350 | 360 => {
-                    if read_row_count || read_column_count {
-                        data.set_value(
-                            current_row,
-                            current_column,
-                            DataTableValue::Handle(pair.as_handle()?),
-                        );
-                    } else {
-                        common.apply_individual_pair(&pair, iter)?;
-                    }
+                330 | 331 | 340 | 350 | 360 if read_row_count || read_column_count => {
+                    data.set_value(
+                        current_row,
+                        current_column,
+                        DataTableValue::Handle(pair.as_handle()?),
+                    );
                 }
 
                 _ => {
@@ -1072,6 +1173,16 @@ impl Object {
             }
         }
     }
+    fn combine_mline_style_elements(mline: &mut MLineStyle) {
+        for (o, (c, l)) in mline.__element_offsets.drain(..).zip(
+            mline
+                .__element_colors
+                .drain(..)
+                .zip(mline.__element_line_types.drain(..)),
+        ) {
+            mline.elements.push(MLineStyleElement::new(o, c, l));
+        }
+    }
     fn apply_custom_reader_mlinestyle(
         common: &mut ObjectCommon,
         mline: &mut MLineStyle,
@@ -1553,7 +1664,7 @@ impl Object {
                     ));
                 }
                 let code = if dict.is_hard_owner { 360 } else { 350 };
-                for key in dict.value_handles.keys().sorted_by(|a, b| Ord::cmp(a, b)) {
+                for key in dict.value_handles.keys().sorted_by(Ord::cmp) {
                     if let Some(value) = dict.value_handles.get(key) {
                         pairs.push(CodePair::new_string(3, key));
                         pairs.push(CodePair::new_string(code, &value.as_string()));
@@ -1568,13 +1679,14 @@ impl Object {
                         dict.duplicate_record_handling as i16,
                     ));
                 }
-                pairs.push(CodePair::new_string(340, &dict.default_handle.as_string()));
-                for key in dict.value_handles.keys().sorted_by(|a, b| Ord::cmp(a, b)) {
+                for key in dict.value_handles.keys().sorted_by(Ord::cmp) {
                     if let Some(value) = dict.value_handles.get(key) {
                         pairs.push(CodePair::new_string(3, key));
                         pairs.push(CodePair::new_string(350, &value.as_string()));
                     }
                 }
+                pairs.push(CodePair::new_str(100, "AcDbDictionaryWithDefault"));
+                pairs.push(CodePair::new_string(340, &dict.default_handle.as_string()));
             }
             ObjectType::LightList(ref ll) => {
                 pairs.push(CodePair::new_str(100, "AcDbLightList"));
@@ -2033,6 +2145,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn round_trip_dictionary_with_default() {
+        let dict = read_object(
+            "ACDBDICTIONARYWDFLT",
+            vec![
+                CodePair::new_str(100, "AcDbDictionary"),
+                CodePair::new_i16(281, 1),
+                CodePair::new_str(3, "Normal"),
+                CodePair::new_str(350, "F"),
+                CodePair::new_str(100, "AcDbDictionaryWithDefault"),
+                CodePair::new_str(340, "F"),
+            ],
+        );
+        match dict.specific {
+            ObjectType::DictionaryWithDefault(ref dict) => {
+                assert_eq!(
+                    DictionaryDuplicateRecordHandling::KeepExisting,
+                    dict.duplicate_record_handling
+                );
+                assert_eq!(Some(&Handle(0xF)), dict.value_handles.get("Normal"));
+                assert_eq!(Handle(0xF), dict.default_handle);
+            }
+            _ => panic!("expected a dictionary with default"),
+        }
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_object(dict);
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(3, "Normal"),
+                CodePair::new_str(350, "F"),
+                CodePair::new_str(100, "AcDbDictionaryWithDefault"),
+                CodePair::new_str(340, "F"),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let dict = reparsed
+            .objects()
+            .find_map(|o| match o.specific {
+                ObjectType::DictionaryWithDefault(ref dict) => Some(dict),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(Some(&Handle(0xF)), dict.value_handles.get("Normal"));
+        assert_eq!(Handle(0xF), dict.default_handle);
+    }
+
     #[test]
     fn read_sunstudy() {
         // validates that code 290 values (ideally boolean) can be read as integers, too
@@ -2302,4 +2463,102 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn proxy_object_binary_data_round_trip() {
+        let obj = read_object(
+            "ACAD_PROXY_OBJECT",
+            vec![
+                CodePair::new_str(5, "A1"),
+                CodePair::new_str(330, "1F"),
+                CodePair::new_str(100, "AcDbProxyObject"),
+                CodePair::new_i32(90, 499),
+                CodePair::new_i32(91, 501),
+                CodePair::new_i32(93, 24),
+                CodePair::new_binary(310, vec![0xAA, 0xBB, 0xCC]),
+                CodePair::new_str(330, "2A"),
+                CodePair::new_str(360, "2B"),
+                CodePair::new_str(340, "2C"),
+                CodePair::new_i32(94, 0),
+                CodePair::new_i32(95, 0),
+                CodePair::new_i16(70, 1),
+            ],
+        );
+        assert_eq!(Handle(0x1F), obj.common.__owner_handle);
+        let proxy = match obj.specific {
+            ObjectType::AcadProxyObject(ref proxy) => proxy.clone(),
+            _ => panic!("expected a proxy object"),
+        };
+        assert_eq!(vec![vec![0xAA, 0xBB, 0xCC]], proxy.binary_object_data);
+        assert_eq!(
+            vec![
+                (330, String::from("2A")),
+                (360, String::from("2B")),
+                (340, String::from("2C")),
+            ],
+            proxy.object_ids
+        );
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_object(obj);
+        let mut buf = vec![];
+        drawing.save(&mut buf).ok().unwrap();
+        let reparsed = Drawing::load(&mut buf.as_slice()).ok().unwrap();
+        let reparsed = reparsed
+            .objects()
+            .find_map(|o| match o.specific {
+                ObjectType::AcadProxyObject(ref proxy) => Some(proxy.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(proxy, reparsed);
+    }
+
+    #[test]
+    fn round_trip_geo_data() {
+        let crs = format!("<Alias id=\"EPSG:3857\">{}</Alias>", "x".repeat(600));
+        let geo = read_object(
+            "GEODATA",
+            vec![
+                CodePair::new_str(100, "AcDbGeoData"),
+                CodePair::new_i32(90, 2),
+                CodePair::new_f64(11, 1.0),
+                CodePair::new_f64(21, 2.0),
+                CodePair::new_f64(31, 3.0),
+                CodePair::new_string(303, &crs[..255]),
+                CodePair::new_string(303, &crs[255..510]),
+                CodePair::new_string(301, &crs[510..]),
+            ],
+        );
+        match geo.specific {
+            ObjectType::GeoData(ref geo) => {
+                assert_eq!(crs, geo.coordinate_system_definition);
+                assert_eq!(Point::new(1.0, 2.0, 3.0), geo.reference_point);
+            }
+            _ => panic!("expected geo data"),
+        }
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2010;
+        drawing.add_object(geo);
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_string(303, &crs[..255]),
+                CodePair::new_string(303, &crs[255..510]),
+                CodePair::new_string(301, &crs[510..]),
+            ],
+        );
+        let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let geo = reparsed
+            .objects()
+            .find_map(|o| match o.specific {
+                ObjectType::GeoData(ref geo) => Some(geo),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(crs, geo.coordinate_system_definition);
+        assert_eq!(Point::new(1.0, 2.0, 3.0), geo.reference_point);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{CodePair, DxfError, DxfResult};
+use crate::{CodePair, DxfError, DxfResult, Vector};
 
 /// Represents a simple point in Cartesian space.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -40,6 +40,35 @@ impl Point {
     pub fn tuple(&self) -> (f64, f64, f64) {
         (self.x, self.y, self.z)
     }
+    /// Converts this point from the object coordinate system (OCS) defined by `extrusion` to world
+    /// coordinates.
+    pub fn to_wcs_with(&self, extrusion: &Vector) -> Point {
+        ocs_point_to_wcs(self.clone(), extrusion.clone())
+    }
+}
+
+impl_xyz_conversions!(Point);
+
+/// Converts a point in the object coordinate system (OCS) defined by `extrusion` to world
+/// coordinates using the DXF arbitrary axis algorithm.  A zero extrusion leaves the point as-is.
+pub fn ocs_point_to_wcs(point: Point, extrusion: Vector) -> Point {
+    let az = extrusion.normalize();
+    if az == Vector::zero() {
+        return point;
+    }
+
+    let ax = if az.x.abs() < 1.0 / 64.0 && az.y.abs() < 1.0 / 64.0 {
+        Vector::y_axis().cross(&az)
+    } else {
+        Vector::z_axis().cross(&az)
+    }
+    .normalize();
+    let ay = az.cross(&ax).normalize();
+    Point::new(
+        point.x * ax.x + point.y * ay.x + point.z * az.x,
+        point.x * ax.y + point.y * ay.y + point.z * az.y,
+        point.x * ax.z + point.y * ay.z + point.z * az.z,
+    )
 }
 
 #[cfg(test)]
@@ -56,4 +85,43 @@ mod tests {
         dbg!(&t);
         assert_eq!(t, p.tuple())
     }
+
+    fn assert_point_eq(expected: Point, actual: Point) {
+        assert!((expected.x - actual.x).abs() < 1e-12, "{:?}", actual);
+        assert!((expected.y - actual.y).abs() < 1e-12, "{:?}", actual);
+        assert!((expected.z - actual.z).abs() < 1e-12, "{:?}", actual);
+    }
+
+    #[test]
+    fn ocs_to_wcs_with_default_extrusion_is_unchanged() {
+        let p = Point::new(1.0, 2.0, 3.0);
+        assert_point_eq(p.clone(), ocs_point_to_wcs(p, Vector::z_axis()));
+    }
+
+    #[test]
+    fn ocs_to_wcs_with_tilted_extrusion() {
+        // extrusion tilted 45 degrees from the Z axis towards -Y
+        let extrusion = Vector::new(0.0, -1.0, 1.0);
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        assert_point_eq(
+            Point::new(1.0, s, s),
+            ocs_point_to_wcs(Point::new(1.0, 1.0, 0.0), extrusion.clone()),
+        );
+        assert_point_eq(
+            Point::new(1.0, 0.0, 2.0 * s),
+            Point::new(1.0, 1.0, 1.0).to_wcs_with(&extrusion),
+        );
+    }
+
+    #[test]
+    fn tuple_and_array_conversions() {
+        assert_eq!(Point::new(1.0, 2.0, 3.0), Point::from((1.0, 2.0, 3.0)));
+        assert_eq!(Point::new(1.0, 2.0, 3.0), Point::from([1.0, 2.0, 3.0]));
+        assert_eq!(Point::new(1.0, 2.0, 0.0), Point::from((1.0, 2.0)));
+        assert_eq!(Point::new(1.0, 2.0, 0.0), [1.0, 2.0].into());
+        let t: (f64, f64, f64) = Point::new(1.0, 2.0, 3.0).into();
+        assert_eq!((1.0, 2.0, 3.0), t);
+        let a: [f64; 3] = Point::new(1.0, 2.0, 3.0).into();
+        assert_eq!([1.0, 2.0, 3.0], a);
+    }
 }
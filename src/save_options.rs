@@ -0,0 +1,83 @@
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::ValueFormatter;
+
+/// A top-level section of a DXF file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DxfSection {
+    Header,
+    Classes,
+    Tables,
+    Blocks,
+    Entities,
+    Objects,
+    Thumbnail,
+}
+
+/// Controls how a `Drawing` is written.
+#[derive(Clone, Default)]
+pub struct SaveOptions {
+    /// When `true`, the drawing is written with the greater of `header.version` and the minimum
+    /// version required by its entities and objects.  The drawing itself is not modified.
+    pub bump_version_to_minimum_required: bool,
+    /// Overrides how values are formatted in ASCII files.  `None` uses `DefaultValueFormatter`.
+    pub value_formatter: Option<Arc<dyn ValueFormatter>>,
+    /// The sections to write.  `None` writes every section; the header is always written.
+    pub sections: Option<Vec<DxfSection>>,
+    /// When `true`, entities that were read from a file are written in the order they appeared
+    /// in that file, even if the drawing's entities have since been reordered.  Entities are
+    /// always written in their current order by default, which for a freshly loaded drawing is
+    /// the order of the input file.
+    pub preserve_order: bool,
+    /// When `true`, entities are grouped by layer name, ignoring case, when written.  Entities on
+    /// the same layer keep their relative order and the drawing itself is not modified.
+    pub sort_entities_by_layer: bool,
+    /// Comments written as `999` code pairs at the start of the file, one pair per line.  Comments
+    /// are always skipped when reading.
+    pub comments: Vec<String>,
+    /// When `true`, saving fails with `DxfError::Unsupported` if an entity or object can't be
+    /// written in the target version.  By default such items are silently skipped.
+    pub fail_on_unsupported: bool,
+    /// When `true`, saving fails with `DxfError::ValidationError` if `Drawing::validate` finds a
+    /// problem with the drawing.  By default the drawing is written as-is.
+    pub validate: bool,
+    /// When `true`, the drawing is written exactly as it is.  By default the mandatory table
+    /// records that `Drawing::normalize` would add, e.g., layer `0` and line type `CONTINUOUS`,
+    /// are also written if they're missing; nothing else is changed and the drawing itself is not
+    /// modified.
+    pub raw: bool,
+}
+
+impl SaveOptions {
+    /// Creates a new `SaveOptions` with the default behavior.
+    pub fn new() -> Self {
+        SaveOptions::default()
+    }
+    pub(crate) fn includes_section(&self, section: DxfSection) -> bool {
+        match self.sections {
+            Some(ref sections) => section == DxfSection::Header || sections.contains(&section),
+            None => true,
+        }
+    }
+}
+
+impl Debug for SaveOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SaveOptions")
+            .field(
+                "bump_version_to_minimum_required",
+                &self.bump_version_to_minimum_required,
+            )
+            .field("value_formatter", &self.value_formatter.is_some())
+            .field("sections", &self.sections)
+            .field("preserve_order", &self.preserve_order)
+            .field("sort_entities_by_layer", &self.sort_entities_by_layer)
+            .field("comments", &self.comments)
+            .field("fail_on_unsupported", &self.fail_on_unsupported)
+            .field("validate", &self.validate)
+            .field("raw", &self.raw)
+            .finish()
+    }
+}
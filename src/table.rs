@@ -1,6 +1,6 @@
 use crate::helper_functions::*;
 use crate::tables::*;
-use crate::Color;
+use crate::{Color, Drawing, Point, TransformationMatrix, Vector};
 
 //------------------------------------------------------------------------------
 //                                                                         Layer
@@ -27,6 +27,54 @@ impl Style {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                           Ucs
+//------------------------------------------------------------------------------
+impl Ucs {
+    /// Returns the transformation from UCS coordinates to world coordinates.  The axes are
+    /// normalized and the Y axis is made perpendicular to the X axis.
+    pub fn to_matrix(&self) -> TransformationMatrix {
+        let (x, y, z) = self.axes();
+        TransformationMatrix {
+            m11: x.x,
+            m12: y.x,
+            m13: z.x,
+            m14: self.origin.x,
+            m21: x.y,
+            m22: y.y,
+            m23: z.y,
+            m24: self.origin.y,
+            m31: x.z,
+            m32: y.z,
+            m33: z.z,
+            m34: self.origin.z,
+            m44: 1.0,
+            ..Default::default()
+        }
+    }
+    /// Converts a point in world coordinates to this UCS.
+    pub fn world_to_ucs(&self, p: &Point) -> Point {
+        let (x, y, z) = self.axes();
+        let d = Vector::new(
+            p.x - self.origin.x,
+            p.y - self.origin.y,
+            p.z - self.origin.z,
+        );
+        let dot = |a: &Vector| a.x * d.x + a.y * d.y + a.z * d.z;
+        Point::new(dot(&x), dot(&y), dot(&z))
+    }
+    /// Converts a point in this UCS to world coordinates.
+    pub fn ucs_to_world(&self, p: &Point) -> Point {
+        self.to_matrix().transform_point(p)
+    }
+    fn axes(&self) -> (Vector, Vector, Vector) {
+        let x = self.x_axis.normalize();
+        let z = x.cross(&self.y_axis).normalize();
+        let y = z.cross(&x);
+        (x, y, z)
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                          View
 //------------------------------------------------------------------------------
@@ -43,6 +91,33 @@ impl View {
 //                                                                      ViewPort
 //------------------------------------------------------------------------------
 impl ViewPort {
+    /// Returns the `*ACTIVE` view port describing the view the drawing was saved with.
+    pub fn active(drawing: &Drawing) -> Option<&ViewPort> {
+        drawing
+            .view_ports()
+            .find(|v| v.name.eq_ignore_ascii_case("*ACTIVE"))
+    }
+    /// Returns the width of the view, derived from its height and aspect ratio.
+    pub fn view_width(&self) -> f64 {
+        self.view_height * self.view_port_aspect_ratio
+    }
+    /// Returns the lower-left and upper-right corners of the view in display coordinates.
+    pub fn view_extents(&self) -> (Point, Point) {
+        let half_width = self.view_width() / 2.0;
+        let half_height = self.view_height / 2.0;
+        (
+            Point::new(
+                self.view_center.x - half_width,
+                self.view_center.y - half_height,
+                0.0,
+            ),
+            Point::new(
+                self.view_center.x + half_width,
+                self.view_center.y + half_height,
+                0.0,
+            ),
+        )
+    }
     /// Ensure all values are valid.
     pub fn normalize(&mut self) {
         ensure_positive_or_default(&mut self.snap_spacing.x, 1.0);
@@ -425,4 +500,56 @@ mod tests {
         drawing.header.version = AcadVersion::R13;
         assert_contains_pairs(&drawing, vec![CodePair::new_str(0, "BLOCK_RECORD")]);
     }
+
+    #[test]
+    fn ucs_transforms() {
+        let ucs = Ucs {
+            origin: Point::new(10.0, 5.0, 0.0),
+            x_axis: Vector::new(0.0, 2.0, 0.0),
+            y_axis: Vector::new(-1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            Point::new(-5.0, 10.0, 0.0),
+            ucs.world_to_ucs(&Point::origin())
+        );
+        assert_eq!(
+            Point::new(10.0, 6.0, 0.0),
+            ucs.ucs_to_world(&Point::new(1.0, 0.0, 0.0))
+        );
+        let p = Point::new(3.0, -4.0, 7.0);
+        let round_trip = ucs.world_to_ucs(&ucs.to_matrix().transform_point(&p));
+        assert!(approx_eq!(f64, p.x, round_trip.x, epsilon = 1e-12));
+        assert!(approx_eq!(f64, p.y, round_trip.y, epsilon = 1e-12));
+        assert!(approx_eq!(f64, p.z, round_trip.z, epsilon = 1e-12));
+    }
+
+    #[test]
+    fn round_trip_active_view_port() {
+        for version in [AcadVersion::R12, AcadVersion::R2007] {
+            let mut drawing = Drawing::new();
+            drawing.header.version = version;
+            drawing.add_view_port(ViewPort {
+                name: String::from("other"),
+                ..Default::default()
+            });
+            let active = drawing
+                .view_ports_mut()
+                .find(|v| v.name == "*ACTIVE")
+                .unwrap();
+            active.view_center = Point::new(10.0, 20.0, 0.0);
+            active.view_height = 40.0;
+            active.view_port_aspect_ratio = 1.5;
+
+            let reparsed = drawing_from_pairs(drawing.code_pairs().unwrap());
+            let active = ViewPort::active(&reparsed).unwrap();
+            assert_eq!(Point::new(10.0, 20.0, 0.0), active.view_center);
+            assert_eq!(40.0, active.view_height);
+            assert_eq!(60.0, active.view_width());
+            assert_eq!(
+                (Point::new(-20.0, 0.0, 0.0), Point::new(40.0, 40.0, 0.0)),
+                active.view_extents()
+            );
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::io::Cursor;
+
 use crate::code_pair_put_back::CodePairPutBack;
 
 use crate::{CodePair, DxfError, DxfResult};
@@ -10,17 +12,41 @@ const BITMAP_HEADER_PALETTE_COUNT_OFFSET: usize = 32;
 
 pub(crate) fn read_thumbnail(iter: &mut CodePairPutBack) -> DxfResult<Option<image::DynamicImage>> {
     match read_thumbnail_bytes_from_code_pairs(iter)? {
-        Some(mut data) => {
-            if update_thumbnail_data_offset_in_situ(&mut data)? {
-                read_thumbnail_from_bytes(&data)
-            } else {
-                Ok(None)
-            }
-        }
+        Some(data) => thumbnail_from_bitmap_data(&data),
         None => Ok(None),
     }
 }
 
+/// Decodes bitmap data as it's stored in a DXF file, i.e., without the BMP file header.
+pub(crate) fn thumbnail_from_bitmap_data(
+    bitmap_data: &[u8],
+) -> DxfResult<Option<image::DynamicImage>> {
+    // prepend the BMP header that always seems to be missing from DXF files
+    let mut data: Vec<u8> = vec![
+        b'B', b'M', // magic number
+        0x00, 0x00, 0x00, 0x00, // file length (set below)
+        0x00, 0x00, // reserved
+        0x00, 0x00, // reserved
+        0x00, 0x00, 0x00, 0x00, // image data offset (calculated elsewhere)
+    ];
+    data.extend_from_slice(bitmap_data);
+
+    let file_length = data.len();
+    set_i32(&mut data, FILE_LENGTH_OFFSET, file_length as i32)?;
+    if update_thumbnail_data_offset_in_situ(&mut data)? {
+        read_thumbnail_from_bytes(&data)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Encodes the image as bitmap data without the BMP file header, as it's stored in a DXF file.
+pub(crate) fn thumbnail_bitmap_data(image: &image::DynamicImage) -> DxfResult<Vec<u8>> {
+    let mut data = vec![];
+    image.write_to(&mut Cursor::new(&mut data), image::ImageFormat::Bmp)?;
+    Ok(data.split_off(FILE_HEADER_LENGTH))
+}
+
 fn read_thumbnail_bytes_from_code_pairs(iter: &mut CodePairPutBack) -> DxfResult<Option<Vec<u8>>> {
     // get the length; we don't really care about this since we'll just read whatever's there
     let _length = match iter.next() {
@@ -37,16 +63,8 @@ fn read_thumbnail_bytes_from_code_pairs(iter: &mut CodePairPutBack) -> DxfResult
         None => return Ok(None),
     };
 
-    // prepend the BMP header that always seems to be missing from DXF files
-    let mut data: Vec<u8> = vec![
-        b'B', b'M', // magic number
-        0x00, 0x00, 0x00, 0x00, // file length (set below)
-        0x00, 0x00, // reserved
-        0x00, 0x00, // reserved
-        0x00, 0x00, 0x00, 0x00, // image data offset (calculated elsewhere)
-    ];
-
     // read the hex data
+    let mut data = vec![];
     loop {
         match iter.next() {
             Some(Ok(pair @ CodePair { code: 0, .. })) => {
@@ -68,12 +86,10 @@ fn read_thumbnail_bytes_from_code_pairs(iter: &mut CodePairPutBack) -> DxfResult
         }
     }
 
-    let file_length = data.len();
-    set_i32(&mut data, FILE_LENGTH_OFFSET, file_length as i32)?;
     Ok(Some(data))
 }
 
-fn update_thumbnail_data_offset_in_situ(data: &mut Vec<u8>) -> DxfResult<bool> {
+fn update_thumbnail_data_offset_in_situ(data: &mut [u8]) -> DxfResult<bool> {
     // calculate the image data offset
     let dib_header_size = read_i32(data, FILE_HEADER_LENGTH)? as usize;
 
@@ -240,7 +256,7 @@ fn test_get_u32() {
     assert_eq!(0x12345678, value);
 }
 
-fn set_i32(data: &mut Vec<u8>, offset: usize, value: i32) -> DxfResult<()> {
+fn set_i32(data: &mut [u8], offset: usize, value: i32) -> DxfResult<()> {
     let expected_length = offset + 4;
     if data.len() < expected_length {
         return Err(DxfError::UnexpectedEndOfInput);
@@ -1,3 +1,7 @@
+use std::ops::Mul;
+
+use crate::{ocs_point_to_wcs, Point, Vector};
+
 /// Applies a transformation to a point.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -31,6 +35,68 @@ impl TransformationMatrix {
             ..Default::default()
         }
     }
+    /// Creates a matrix that translates by the specified amounts.
+    pub fn translation(dx: f64, dy: f64, dz: f64) -> Self {
+        TransformationMatrix {
+            m14: dx,
+            m24: dy,
+            m34: dz,
+            ..TransformationMatrix::identity()
+        }
+    }
+    /// Creates a matrix that scales each axis by the specified amounts.
+    pub fn scale(sx: f64, sy: f64, sz: f64) -> Self {
+        TransformationMatrix {
+            m11: sx,
+            m22: sy,
+            m33: sz,
+            m44: 1.0,
+            ..Default::default()
+        }
+    }
+    /// Creates a matrix that rotates about the Z axis by the specified angle in degrees.
+    pub fn rotation_about_z(angle: f64) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        TransformationMatrix {
+            m11: cos,
+            m12: -sin,
+            m21: sin,
+            m22: cos,
+            ..TransformationMatrix::identity()
+        }
+    }
+    /// Applies the transformation to the specified point.
+    pub fn transform_point(&self, p: &Point) -> Point {
+        Point::new(
+            self.m11 * p.x + self.m12 * p.y + self.m13 * p.z + self.m14,
+            self.m21 * p.x + self.m22 * p.y + self.m23 * p.z + self.m24,
+            self.m31 * p.x + self.m32 * p.y + self.m33 * p.z + self.m34,
+        )
+    }
+    /// Applies the transformation to the specified vector.  Translation is ignored.
+    pub fn transform_vector(&self, v: &Vector) -> Vector {
+        Vector::new(
+            self.m11 * v.x + self.m12 * v.y + self.m13 * v.z,
+            self.m21 * v.x + self.m22 * v.y + self.m23 * v.z,
+            self.m31 * v.x + self.m32 * v.y + self.m33 * v.z,
+        )
+    }
+}
+
+impl Mul for TransformationMatrix {
+    type Output = TransformationMatrix;
+    /// Combines two transformations; the right-hand side is applied first.
+    fn mul(self, rhs: TransformationMatrix) -> TransformationMatrix {
+        let a = self.values();
+        let b = rhs.values();
+        let mut result = vec![0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                result[row * 4 + col] = (0..4).map(|k| a[row * 4 + k] * b[k * 4 + col]).sum();
+            }
+        }
+        TransformationMatrix::from_vec(&result)
+    }
 }
 
 // internal visibility only
@@ -55,6 +121,25 @@ impl TransformationMatrix {
             m44: TransformationMatrix::value_or_default(values, 15),
         }
     }
+    /// Creates a matrix from the object coordinate system (OCS) defined by `extrusion` to world
+    /// coordinates.
+    pub(crate) fn ocs_to_wcs(extrusion: &Vector) -> Self {
+        let ax = ocs_point_to_wcs(Point::new(1.0, 0.0, 0.0), extrusion.clone());
+        let ay = ocs_point_to_wcs(Point::new(0.0, 1.0, 0.0), extrusion.clone());
+        let az = ocs_point_to_wcs(Point::new(0.0, 0.0, 1.0), extrusion.clone());
+        TransformationMatrix {
+            m11: ax.x,
+            m12: ay.x,
+            m13: az.x,
+            m21: ax.y,
+            m22: ay.y,
+            m23: az.y,
+            m31: ax.z,
+            m32: ay.z,
+            m33: az.z,
+            ..TransformationMatrix::identity()
+        }
+    }
     pub(crate) fn values(&self) -> Vec<f64> {
         vec![
             self.m11, self.m12, self.m13, self.m14, self.m21, self.m22, self.m23, self.m24,
@@ -79,3 +164,40 @@ impl TransformationMatrix {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn assert_point_eq(expected: Point, actual: Point) {
+        assert!(approx_eq!(f64, expected.x, actual.x, epsilon = 1e-10));
+        assert!(approx_eq!(f64, expected.y, actual.y, epsilon = 1e-10));
+        assert!(approx_eq!(f64, expected.z, actual.z, epsilon = 1e-10));
+    }
+
+    #[test]
+    fn transform_point_with_translation() {
+        let m = TransformationMatrix::translation(1.0, 2.0, 3.0);
+        assert_point_eq(
+            Point::new(2.0, 3.0, 4.0),
+            m.transform_point(&Point::new(1.0, 1.0, 1.0)),
+        );
+    }
+
+    #[test]
+    fn transform_vector_ignores_translation() {
+        let m = TransformationMatrix::translation(1.0, 2.0, 3.0);
+        assert_eq!(Vector::x_axis(), m.transform_vector(&Vector::x_axis()));
+    }
+
+    #[test]
+    fn combined_transforms_apply_right_to_left() {
+        let m = TransformationMatrix::translation(10.0, 0.0, 0.0)
+            * TransformationMatrix::rotation_about_z(90.0)
+            * TransformationMatrix::scale(2.0, 2.0, 2.0);
+        assert_point_eq(
+            Point::new(10.0, 2.0, 0.0),
+            m.transform_point(&Point::new(1.0, 0.0, 0.0)),
+        );
+    }
+}
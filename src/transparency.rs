@@ -0,0 +1,52 @@
+/// Represents an entity's transparency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Transparency {
+    raw_value: i32,
+}
+
+const BY_BLOCK_FLAG: i32 = 0x0100_0000;
+const EXPLICIT_FLAG: i32 = 0x0200_0000;
+
+impl Transparency {
+    /// Creates a new `Transparency` from the raw code 440 value.
+    pub fn from_raw_value(v: i32) -> Transparency {
+        Transparency { raw_value: v }
+    }
+    /// Creates a new `Transparency` that defaults back to the containing block's transparency.
+    pub fn by_block() -> Transparency {
+        Transparency::from_raw_value(BY_BLOCK_FLAG)
+    }
+    /// Creates a new `Transparency` that defaults back to the item's layer's transparency.
+    pub fn by_layer() -> Transparency {
+        Transparency::from_raw_value(0)
+    }
+    /// Creates a new `Transparency` from a percentage where `0.0` is opaque and `100.0` is fully
+    /// transparent.  Values outside that range are clamped.
+    pub fn from_percent(percent: f64) -> Transparency {
+        let alpha = (255.0 * (1.0 - percent.clamp(0.0, 100.0) / 100.0)).round() as i32;
+        Transparency::from_raw_value(EXPLICIT_FLAG | alpha)
+    }
+    /// Gets the raw value of the `Transparency`.
+    pub fn raw_value(self) -> i32 {
+        self.raw_value
+    }
+    /// Returns `true` if the `Transparency` is BYBLOCK.
+    pub fn is_by_block(self) -> bool {
+        self.raw_value & BY_BLOCK_FLAG != 0
+    }
+    /// Returns `true` if the `Transparency` is BYLAYER.
+    pub fn is_by_layer(self) -> bool {
+        self.raw_value & (BY_BLOCK_FLAG | EXPLICIT_FLAG) == 0
+    }
+    /// Returns the transparency as a percentage where `0.0` is opaque and `100.0` is fully
+    /// transparent, or `None` if the `Transparency` is BYLAYER or BYBLOCK.
+    pub fn percent(self) -> Option<f64> {
+        if self.raw_value & EXPLICIT_FLAG == 0 {
+            return None;
+        }
+
+        let alpha = f64::from(self.raw_value & 0xFF);
+        Some((1.0 - alpha / 255.0) * 100.0)
+    }
+}
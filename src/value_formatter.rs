@@ -0,0 +1,83 @@
+use crate::code_pair_value::format_f64;
+
+/// Controls how values are formatted when writing ASCII DXF files.
+pub trait ValueFormatter {
+    /// Formats a floating point value written with the specified code.  The default implementation
+    /// writes the shortest form that reads back as the same value, e.g., `1.5` or `6023412.123456789`.
+    fn format_f64(&self, code: i32, value: f64) -> String {
+        let _ = code;
+        format_f64(value)
+    }
+}
+
+/// The `ValueFormatter` used when none is specified.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultValueFormatter;
+
+impl ValueFormatter for DefaultValueFormatter {}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+    use std::sync::Arc;
+
+    struct ScientificFormatter;
+
+    impl ValueFormatter for ScientificFormatter {
+        fn format_f64(&self, code: i32, value: f64) -> String {
+            if code == 40 {
+                format!("{:E}", value)
+            } else {
+                DefaultValueFormatter.format_f64(code, value)
+            }
+        }
+    }
+
+    fn save_to_string(drawing: &Drawing, options: &SaveOptions) -> String {
+        let mut buf = std::io::Cursor::new(vec![]);
+        drawing.save_with_options(&mut buf, options).unwrap();
+        String::from_utf8(buf.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn default_formatter_trims_trailing_zeros() {
+        assert_eq!("1.5", DefaultValueFormatter.format_f64(10, 1.5));
+        assert_eq!("2.0", DefaultValueFormatter.format_f64(10, 2.0));
+    }
+
+    #[test]
+    fn custom_formatter_is_used_when_saving() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::new(1.5, 0.0, 0.0),
+            1250.0,
+        ))));
+        let mut options = SaveOptions::new();
+        options.value_formatter = Some(Arc::new(ScientificFormatter));
+        let contents = save_to_string(&drawing, &options);
+        assert!(contents.contains("\r\n 40\r\n1.25E3\r\n"));
+        assert!(contents.contains("\r\n 10\r\n1.5\r\n"));
+    }
+
+    #[test]
+    fn round_trip_large_coordinates() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::ModelPoint(ModelPoint::new(
+            Point::new(6023412.123456789, 2117834.987654321, 0.0),
+        ))));
+        let contents = save_to_string(&drawing, &SaveOptions::new());
+        assert!(contents.contains("\r\n 10\r\n6023412.123456789\r\n"));
+
+        let reparsed = Drawing::load(&mut contents.as_bytes()).unwrap();
+        let point = reparsed
+            .entities()
+            .next()
+            .unwrap()
+            .as_model_point()
+            .unwrap();
+        assert!((point.location.x - 6023412.123456789).abs() < 1e-6);
+        assert!((point.location.y - 2117834.987654321).abs() < 1e-6);
+        assert_eq!(6023412.123456789, point.location.x);
+    }
+}
@@ -33,6 +33,27 @@ impl Vector {
     pub fn z_axis() -> Vector {
         Vector::new(0.0, 0.0, 1.0)
     }
+    /// Returns the length of the vector.
+    pub fn length(&self) -> f64 {
+        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    }
+    /// Returns a vector in the same direction with a length of 1, or the zero vector if the length is 0.
+    pub fn normalize(&self) -> Vector {
+        let length = self.length();
+        if length == 0.0 {
+            Vector::zero()
+        } else {
+            Vector::new(self.x / length, self.y / length, self.z / length)
+        }
+    }
+    /// Returns the cross product of this vector and `other`.
+    pub fn cross(&self, other: &Vector) -> Vector {
+        Vector::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
     pub(crate) fn set(&mut self, pair: &CodePair) -> DxfResult<()> {
         match pair.code {
             10 => self.x = pair.assert_f64()?,
@@ -49,3 +70,18 @@ impl Vector {
         Ok(())
     }
 }
+
+impl_xyz_conversions!(Vector);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuple_and_array_conversions() {
+        assert_eq!(Vector::new(1.0, 2.0, 3.0), Vector::from((1.0, 2.0, 3.0)));
+        assert_eq!(Vector::new(1.0, 2.0, 0.0), Vector::from([1.0, 2.0]));
+        let a: [f64; 3] = Vector::new(1.0, 2.0, 3.0).into();
+        assert_eq!([1.0, 2.0, 3.0], a);
+    }
+}
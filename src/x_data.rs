@@ -68,7 +68,8 @@ impl XData {
                 None => return Ok(xdata),
             };
             if pair.code == XDATA_APPLICATIONNAME || pair.code < XDATA_STRING {
-                // new xdata or non xdata
+                // new xdata or non xdata; leave it for the caller
+                iter.put_back(Ok(pair));
                 break;
             }
             xdata.items.push(XDataItem::read_item(&pair, iter)?);